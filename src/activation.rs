@@ -1,7 +1,7 @@
 use core::fmt;
 use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Serialize, Deserialize, Clone, Copy)]
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
 pub enum ActivationFunction {
     Sigmoid,
     Tanh,
@@ -9,6 +9,26 @@ pub enum ActivationFunction {
     // Step,
 }
 
+impl ActivationFunction {
+    /// Applies the activation function to a node's weighted input
+    pub fn apply(self, x: f32) -> f32 {
+        match self {
+            ActivationFunction::Sigmoid => 1.0 / (1.0 + ((-x).exp())),
+            ActivationFunction::Linear => 2.0 * x,
+            ActivationFunction::Tanh => x.tanh(),
+        }
+    }
+
+    /// The derivative of the activation function, written in terms of its output
+    pub fn derivative(self, output: f32) -> f32 {
+        match self {
+            ActivationFunction::Sigmoid => output * (1.0 - output),
+            ActivationFunction::Linear => 2.0,
+            ActivationFunction::Tanh => 1.0 - output * output,
+        }
+    }
+}
+
 impl fmt::Display for ActivationFunction {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match self {
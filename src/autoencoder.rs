@@ -0,0 +1,548 @@
+use crate::{
+    activation::ActivationFunction,
+    builder::NetworkBuilder,
+    checkpoint::{Checkpoint, TrainState},
+    config::{ConfigField, Loss, TrainConfig},
+    error::DarjeelingError,
+    layer::{self, LayerSpec, NodeArray},
+    metrics::RegressionReport,
+    neural_network::Model,
+    node::Node,
+    preprocessing::{check_missing, pipeline::Pipeline, Transform},
+    trainer::{self, Gradient, Supervised},
+};
+use rand::Rng;
+use rand_distr::{Distribution, Normal};
+use rand_pcg::Pcg64;
+use serde::{Deserialize, Serialize};
+use std::{borrow::Cow, time::Instant};
+
+/// How inputs are corrupted while a denoising autoencoder trains, it still learns to reconstruct the clean input
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+pub enum Corruption {
+    /// Adds noise from a bell curve around zero to every value
+    Gaussian { std_dev: f32 },
+    /// Sets each value to zero with the given probability
+    Masking { probability: f32 },
+}
+
+impl Corruption {
+    /// Corrupts an input that's already been through the pipeline
+    ///
+    /// ## Err
+    /// ### InvalidCorruption
+    /// The corruption's parameters are out of range, see [`check`](Corruption::check)
+    pub fn corrupt(&self, data: &[f32], rng: &mut impl Rng) -> Result<Box<[f32]>, DarjeelingError> {
+        self.check()?;
+        Ok(match *self {
+            Corruption::Gaussian { std_dev } => {
+                let normal = Normal::new(0.0, std_dev)
+                    .map_err(|err| DarjeelingError::InvalidCorruption(err.to_string()))?;
+                data.iter()
+                    .map(|value| value + normal.sample(rng))
+                    .collect()
+            }
+            Corruption::Masking { probability } => data
+                .iter()
+                .map(|value| {
+                    if rng.gen::<f32>() < probability {
+                        0.0
+                    } else {
+                        *value
+                    }
+                })
+                .collect(),
+        })
+    }
+
+    /// Checks that the standard deviation isn't negative, and the probability is in the range [0, 1)
+    ///
+    /// ## Err
+    /// ### InvalidCorruption
+    /// The corruption's parameters aren't finite, or are out of range
+    pub fn check(&self) -> Result<(), DarjeelingError> {
+        let valid = match *self {
+            Corruption::Gaussian { std_dev } => std_dev.is_finite() && std_dev >= 0.0,
+            Corruption::Masking { probability } => (0.0..1.0).contains(&probability),
+        };
+        if valid {
+            Ok(())
+        } else {
+            Err(DarjeelingError::InvalidCorruption(format!(
+                "{:?} is out of range",
+                self
+            )))
+        }
+    }
+}
+
+/// The autoencoder Neural Network struct, which learns to reconstruct its inputs
+/// through a narrow bottleneck layer.
+///
+/// The narrowest hidden layer is the bottleneck, [`encode`](AutoEncoder::encode) gives its outputs
+/// and [`decode`](AutoEncoder::decode) turns them back into an input.
+/// The answer layer is as wide as the input layer, always uses the linear activation function,
+/// and is trained with the config's [`Loss`].
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct AutoEncoder {
+    node_array: Box<[Box<[Node]>]>,
+    layers: Box<[LayerSpec]>,
+    activation_function: Option<ActivationFunction>,
+    /// The index of the bottleneck layer in the node array
+    bottleneck: usize,
+    /// The config the network was last trained with
+    config: Option<TrainConfig>,
+    /// Every input goes through it before it reaches the network
+    pipeline: Option<Pipeline>,
+    /// Corrupts the inputs while training, so the network learns to denoise them
+    corruption: Option<Corruption>,
+}
+
+impl AutoEncoder {
+    /// Constructor function for an autoencoder
+    /// Fills a Neural Network's node_array with empty nodes.
+    /// Initializes random starting link and bias weights between -.5 and .5
+    ///
+    /// ## Params
+    /// - Inputs: The number of sensors in the input layer, and the number of answer nodes
+    /// - Hidden: The number of hidden nodes per hidden layer of the encoder and decoder
+    /// - Bottleneck: The number of nodes in the bottleneck layer, the width of an encoded input
+    /// - Hidden Layers: The number of hidden layers on each side of the bottleneck
+    /// - Activation Function: Which activation function is used by the hidden layers, the answer layer is always linear
+    ///
+    /// The decoder mirrors the encoder, use a [`NetworkBuilder`] for anything else.
+    ///
+    /// ## Examples
+    /// ``` rust
+    /// use darjeeling::{activation::ActivationFunction, autoencoder::AutoEncoder};
+    ///
+    /// // 64 -> 32 -> 8 -> 32 -> 64
+    /// let net = AutoEncoder::new(64, 32, 8, 1, Some(ActivationFunction::Tanh));
+    /// assert_eq!(net.bottleneck_width(), 8);
+    /// ```
+    pub fn new(
+        input_nodes: usize,
+        hidden_nodes: usize,
+        bottleneck_nodes: usize,
+        hidden_layers: usize,
+        activation_function: Option<ActivationFunction>,
+    ) -> AutoEncoder {
+        let encoder = (0..hidden_layers).fold(NetworkBuilder::new(input_nodes), |builder, _| {
+            builder.hidden(hidden_nodes)
+        });
+        let mut builder = (0..hidden_layers)
+            .fold(encoder.hidden(bottleneck_nodes), |builder, _| {
+                builder.hidden(hidden_nodes)
+            })
+            .hidden(input_nodes);
+        if let Some(activation_function) = activation_function {
+            builder = builder.activation(activation_function);
+        }
+
+        builder
+            .build_autoencoder()
+            .expect("Every layer of the network needs at least one node")
+    }
+
+    /// Expects layers that have been checked by [`NetworkBuilder::build_autoencoder`]
+    pub(crate) fn from_parts(
+        node_array: Box<[Box<[Node]>]>,
+        mut layers: Box<[LayerSpec]>,
+        activation_function: Option<ActivationFunction>,
+    ) -> AutoEncoder {
+        if let Some(answer) = layers.last_mut() {
+            answer.activation = Some(ActivationFunction::Linear);
+        }
+        let bottleneck = (1..layers.len() - 1)
+            .min_by_key(|i| layers[*i].width)
+            .expect("An autoencoder needs a hidden layer");
+
+        AutoEncoder {
+            node_array,
+            layers,
+            activation_function,
+            bottleneck,
+            config: None,
+            pipeline: None,
+            corruption: None,
+        }
+    }
+
+    /// The config the network was last trained with, saved models keep it
+    pub fn config(&self) -> Option<&TrainConfig> {
+        self.config.as_ref()
+    }
+
+    /// Sets the pipeline every input goes through before it reaches the network.
+    /// An unfitted pipeline is fitted on the training data the next time the network is trained,
+    /// and it's saved with the model. Reconstructions are turned back to the scale of the raw inputs.
+    pub fn set_pipeline(&mut self, pipeline: Pipeline) {
+        self.pipeline = Some(pipeline);
+    }
+
+    /// The pipeline every input goes through, saved models keep it
+    pub fn pipeline(&self) -> Option<&Pipeline> {
+        self.pipeline.as_ref()
+    }
+
+    /// How inputs are corrupted while training, none unless the network is a denoising autoencoder
+    pub fn corruption(&self) -> Option<Corruption> {
+        self.corruption
+    }
+
+    /// Sets how inputs are corrupted while training, which turns the network into a denoising autoencoder.
+    /// The corruption is applied after the pipeline, and never while encoding, decoding or scoring.
+    ///
+    /// ## Err
+    /// ### InvalidCorruption
+    /// The corruption's parameters are out of range, the network keeps its old corruption
+    pub fn set_corruption(
+        &mut self,
+        corruption: Option<Corruption>,
+    ) -> Result<(), DarjeelingError> {
+        if let Some(corruption) = corruption {
+            corruption.check()?;
+        }
+        self.corruption = corruption;
+        Ok(())
+    }
+
+    /// The number of nodes in the bottleneck layer, the width of an encoded input
+    pub fn bottleneck_width(&self) -> usize {
+        self.node_array[self.bottleneck].len()
+    }
+
+    /// Trains the neural network model to reconstruct each input.
+    /// Inputs far from zero train better once they're scaled down.
+    ///
+    /// ## Params
+    /// - Data: List of inputs, each as wide as the input layer
+    /// - Config: The hyperparameters of the training, see [`TrainConfig`].
+    ///   Its loss is what's minimized, and its target accuracy is compared to the R² of the reconstructions as a percentage.
+    ///   The model is written to the config's save path once training finishes, if it has one,
+    ///   and checkpoints that [`resume_from`](AutoEncoder::resume_from) continues are written to its checkpoint path.
+    ///
+    /// ## Returns
+    /// The fallible:
+    /// - save path the model was written to, none if the config has no save path
+    /// - the R² of the reconstructions of the last epoch
+    /// - the mean loss of the last epoch
+    ///
+    /// ## Err
+    /// - ### ModelMissingActivationFunction
+    /// The network has no activation function to train with
+    /// - ### InvalidLayerSpec
+    /// An input isn't as wide as the input layer
+    /// - ### NanEncountered
+    /// An input has a missing (NaN) value, or the training diverged
+    /// - ### WriteModelFailed
+    /// There was a problem when saving the model to a file
+    /// - ### UnsupportedConfig
+    /// The config has class weights
+    ///
+    /// ## Examples
+    /// ```ignore
+    /// let mut net = AutoEncoder::new(64, 32, 8, 1, Some(ActivationFunction::Tanh));
+    /// net.set_corruption(Some(Corruption::Gaussian { std_dev: 0.1 })).unwrap();
+    /// let (model_name, r2, loss) = net.train(&data, &TrainConfig::default()).unwrap();
+    /// ```
+    pub fn train(
+        &mut self,
+        data: &[Box<[f32]>],
+        config: &TrainConfig,
+    ) -> Result<(Option<String>, f32, f32), DarjeelingError> {
+        self.train_from(data, config, TrainState::new(config.seed))
+    }
+
+    /// Continues a training run from a checkpoint written during [`train`](AutoEncoder::train),
+    /// exactly where it stopped.
+    /// The config saved in the checkpoint is used, so more epochs can be added by changing its `epochs`.
+    ///
+    /// ## Params
+    /// - Checkpoint: A checkpoint read with [`Checkpoint::read`]
+    /// - Data: The same data the interrupted run was trained on
+    ///
+    /// ## Returns
+    /// The trained network, followed by the same values as [`train`](AutoEncoder::train)
+    pub fn resume_from(
+        checkpoint: Checkpoint<AutoEncoder>,
+        data: &[Box<[f32]>],
+    ) -> Result<(AutoEncoder, Option<String>, f32, f32), DarjeelingError> {
+        let Checkpoint {
+            mut network,
+            config,
+            state,
+            ..
+        } = checkpoint;
+
+        let (model_name, r2, loss) = network.train_from(data, &config, state)?;
+        Ok((network, model_name, r2, loss))
+    }
+
+    /// Runs the training loop from the given state
+    fn train_from(
+        &mut self,
+        data: &[Box<[f32]>],
+        config: &TrainConfig,
+        mut state: TrainState,
+    ) -> Result<(Option<String>, f32, f32), DarjeelingError> {
+        config.check_unused("autoencoder training", &[ConfigField::ClassWeights])?;
+        let activation_function = match self.activation_function {
+            Some(s) => s,
+            None => return Err(DarjeelingError::ModelMissingActivationFunction),
+        };
+        let width = self.node_array[0].len();
+        if let Some(row) = data.iter().find(|row| row.len() != width) {
+            return Err(DarjeelingError::InvalidLayerSpec(format!(
+                "the input layer has {} nodes, but an input has {} values",
+                width,
+                row.len()
+            )));
+        }
+
+        if let Some(pipeline) = self
+            .pipeline
+            .as_mut()
+            .filter(|pipeline| !pipeline.is_fitted())
+        {
+            pipeline.fit_rows(&data.iter().map(|row| row.as_ref()).collect::<Vec<&[f32]>>())?;
+        }
+        let rows = self.preprocess(data)?;
+        check_missing(&rows.iter().map(|row| row.as_ref()).collect::<Vec<&[f32]>>())?;
+
+        let mut last_checkpoint = Instant::now();
+        let mut r2 = 0.0;
+        let mut loss = 0.0;
+        while state.epoch < config.epochs {
+            let reconstructions;
+            (loss, reconstructions) = trainer::epoch(
+                self,
+                activation_function,
+                &rows,
+                config,
+                &mut state,
+                |i, outputs| {
+                    let loss = outputs
+                        .iter()
+                        .zip(rows[i].iter())
+                        .map(|(output, target)| config.loss.loss(*output, *target))
+                        .sum::<f32>()
+                        / width as f32;
+                    let output_grads = outputs
+                        .iter()
+                        .zip(rows[i].iter())
+                        .map(|(output, target)| config.loss.derivative(*output, *target))
+                        .collect();
+                    (loss, Gradient::Outputs(output_grads))
+                },
+            )?;
+            r2 = RegressionReport::new(&rows, &reconstructions).map_or(0.0, |report| report.r2);
+
+            let stop = trainer::end_epoch(
+                config,
+                &mut state,
+                loss,
+                Some(r2 * 100.0),
+                format_args!("Training Reconstruction Loss: {:?}", loss),
+            )?;
+
+            trainer::checkpoint(self, &[], config, &state, &mut last_checkpoint)?;
+            if stop {
+                break;
+            }
+        }
+
+        self.config = Some(config.clone());
+        let model_name = trainer::save(self, config)?;
+
+        println!(
+            "Training: Finished with an R2 of {:?} after {:?} epochs\nloss: {}",
+            r2, state.epoch, loss
+        );
+
+        Ok((model_name, r2, loss))
+    }
+
+    /// Runs a raw input through the whole network
+    ///
+    /// ## Returns
+    /// The reconstruction of the input, on the same scale as the raw input
+    ///
+    /// ## Err
+    /// ### ModelMissingActivationFunction
+    /// The network has no activation function
+    /// ### InvalidLayerSpec
+    /// The input isn't as wide as the input layer
+    /// ### TransformFailed
+    /// The input doesn't have the features the pipeline was fitted on
+    pub fn reconstruct(&mut self, data: &[f32]) -> Result<Box<[f32]>, DarjeelingError> {
+        let data = self.transform(data)?;
+        let output = layer::outputs(
+            &mut self.node_array,
+            &self.layers,
+            self.activation_function,
+            &data,
+        )?;
+        self.inverse_transform(output)
+    }
+
+    /// Runs a raw input through the pipeline and the encoder
+    ///
+    /// ## Returns
+    /// The outputs of the bottleneck layer
+    ///
+    /// ## Err
+    /// The errors of [`reconstruct`](AutoEncoder::reconstruct)
+    ///
+    /// ## Examples
+    /// ```ignore
+    /// let mut net = AutoEncoder::read_model("digits.darj".to_string()).unwrap();
+    /// let code = net.encode(&digit).unwrap();
+    /// let digit = net.decode(&code).unwrap();
+    /// ```
+    pub fn encode(&mut self, data: &[f32]) -> Result<Box<[f32]>, DarjeelingError> {
+        let activation_function = match self.activation_function {
+            Some(s) => s,
+            None => return Err(DarjeelingError::ModelMissingActivationFunction),
+        };
+        let data = self.transform(data)?;
+
+        let bottleneck = self.bottleneck;
+        layer::push_downstream(
+            &mut self.node_array[..=bottleneck],
+            &self.layers[..=bottleneck],
+            activation_function,
+            &data,
+            false,
+            &mut rand::thread_rng(),
+        );
+        Ok(layer::answer_outputs(&self.node_array[..=bottleneck]))
+    }
+
+    /// Runs the outputs of the bottleneck layer through the decoder
+    ///
+    /// ## Returns
+    /// The decoded input, on the same scale as the raw inputs
+    ///
+    /// ## Err
+    /// ### ModelMissingActivationFunction
+    /// The network has no activation function
+    /// ### InvalidLayerSpec
+    /// The code isn't as wide as the bottleneck layer
+    pub fn decode(&mut self, code: &[f32]) -> Result<Box<[f32]>, DarjeelingError> {
+        let activation_function = match self.activation_function {
+            Some(s) => s,
+            None => return Err(DarjeelingError::ModelMissingActivationFunction),
+        };
+        if code.len() != self.bottleneck_width() {
+            return Err(DarjeelingError::InvalidLayerSpec(format!(
+                "the bottleneck layer has {} nodes, but the code has {} values",
+                self.bottleneck_width(),
+                code.len()
+            )));
+        }
+
+        let bottleneck = self.bottleneck;
+        layer::push_downstream(
+            &mut self.node_array[bottleneck..],
+            &self.layers[bottleneck..],
+            activation_function,
+            code,
+            false,
+            &mut rand::thread_rng(),
+        );
+        self.inverse_transform(layer::answer_outputs(&self.node_array))
+    }
+
+    /// Scores how unlike the training data an input is, by how badly the network reconstructs it.
+    /// Inputs the network was trained on score close to its training loss, anomalies score higher.
+    ///
+    /// ## Returns
+    /// The mean squared error between the input and its reconstruction, after the pipeline,
+    /// so every feature counts as much as the others however it's scaled
+    ///
+    /// ## Err
+    /// The errors of [`reconstruct`](AutoEncoder::reconstruct)
+    ///
+    /// ## Examples
+    /// ```ignore
+    /// let scores = net.anomaly_scores(&transactions).unwrap();
+    /// let fraud: Vec<usize> = (0..scores.len()).filter(|i| scores[*i] > threshold).collect();
+    /// ```
+    pub fn anomaly_score(&mut self, data: &[f32]) -> Result<f32, DarjeelingError> {
+        let data = self.transform(data)?;
+        let reconstruction = layer::outputs(
+            &mut self.node_array,
+            &self.layers,
+            self.activation_function,
+            &data,
+        )?;
+        Ok(data
+            .iter()
+            .zip(reconstruction.iter())
+            .map(|(value, output)| Loss::Mse.loss(*output, *value))
+            .sum::<f32>()
+            / data.len().max(1) as f32)
+    }
+
+    /// Scores every input, see [`anomaly_score`](AutoEncoder::anomaly_score)
+    ///
+    /// ## Returns
+    /// The score of each input, in the same order as the data
+    pub fn anomaly_scores(&mut self, data: &[Box<[f32]>]) -> Result<Box<[f32]>, DarjeelingError> {
+        data.iter().map(|row| self.anomaly_score(row)).collect()
+    }
+
+    /// Runs inputs through the pipeline, if the network has one
+    fn preprocess(&self, data: &[Box<[f32]>]) -> Result<Box<[Box<[f32]>]>, DarjeelingError> {
+        data.iter().map(|row| self.transform(row)).collect()
+    }
+
+    /// Runs an input through the pipeline, if the network has one, and checks its width
+    fn transform(&self, data: &[f32]) -> Result<Box<[f32]>, DarjeelingError> {
+        let data = match &self.pipeline {
+            Some(pipeline) => pipeline.transform_row(data)?,
+            None => data.into(),
+        };
+        if data.len() != self.node_array[0].len() {
+            return Err(DarjeelingError::InvalidLayerSpec(format!(
+                "the input layer has {} nodes, but the input has {} values",
+                self.node_array[0].len(),
+                data.len()
+            )));
+        }
+        Ok(data)
+    }
+
+    /// Turns outputs back to the scale of the raw inputs, if the network has a fitted pipeline
+    fn inverse_transform(&self, output: Box<[f32]>) -> Result<Box<[f32]>, DarjeelingError> {
+        match &self.pipeline {
+            Some(pipeline) if pipeline.is_fitted() => pipeline.inverse_transform_row(&output),
+            _ => Ok(output),
+        }
+    }
+
+    /// Sets the activation function of the hidden layers, the answer layer is always linear
+    pub fn set_activation_func(&mut self, new_activation_function: ActivationFunction) {
+        self.activation_function = Some(new_activation_function);
+    }
+}
+
+impl Model for AutoEncoder {}
+
+impl Supervised for AutoEncoder {
+    fn parts(&mut self) -> (&mut NodeArray, &[LayerSpec]) {
+        (&mut self.node_array, &self.layers)
+    }
+
+    fn training_input<'a>(
+        &self,
+        row: &'a [f32],
+        rng: &mut Pcg64,
+    ) -> Result<Cow<'a, [f32]>, DarjeelingError> {
+        match self.corruption {
+            Some(corruption) => Ok(Cow::Owned(corruption.corrupt(row, rng)?.into_vec())),
+            None => Ok(Cow::Borrowed(row)),
+        }
+    }
+}
@@ -0,0 +1,357 @@
+use crate::{
+    activation::ActivationFunction,
+    autoencoder::AutoEncoder,
+    categorize::CatNetwork,
+    error::DarjeelingError,
+    generation::GenNetwork,
+    layer::{self, LayerKind, LayerSpec, NodeArray},
+    multilabel::MultiLabelNetwork,
+    regression::RegNetwork,
+    variational::VarAutoEncoder,
+};
+use serde::{Deserialize, Serialize};
+
+/// Builds a network one layer at a time.
+///
+/// Unlike the positional `new` constructors, every layer can have its own width, activation function,
+/// dropout and normalization. The last layer added is the answer layer.
+///
+/// ## Examples
+/// ``` rust
+/// use darjeeling::{
+///     activation::ActivationFunction,
+///     builder::NetworkBuilder,
+///     layer::LayerSpec
+/// };
+///
+/// // A 64 -> 128 -> 64 -> 10 funnel
+/// let net = NetworkBuilder::new(64)
+///     .activation(ActivationFunction::Sigmoid)
+///     .layer(LayerSpec::new(128).dropout(0.1))
+///     .layer(LayerSpec::new(64).activation(ActivationFunction::Tanh))
+///     .layer(LayerSpec::new(10))
+///     .build_cat()
+///     .unwrap();
+/// ```
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct NetworkBuilder {
+    input: LayerSpec,
+    layers: Vec<LayerSpec>,
+    activation_function: Option<ActivationFunction>,
+}
+
+impl NetworkBuilder {
+    /// Starts a network with the given number of sensors in the input layer
+    pub fn new(input_nodes: usize) -> NetworkBuilder {
+        NetworkBuilder {
+            input: LayerSpec::new(input_nodes),
+            layers: vec![],
+            activation_function: None,
+        }
+    }
+
+    /// Sets the activation function used by every layer that doesn't specify its own
+    pub fn activation(mut self, activation_function: ActivationFunction) -> NetworkBuilder {
+        self.activation_function = Some(activation_function);
+        self
+    }
+
+    /// Sets the probability that each sensor's value is zeroed during training
+    pub fn input_dropout(mut self, dropout: f32) -> NetworkBuilder {
+        self.input.dropout = dropout;
+        self
+    }
+
+    /// Splits the sensors into the given number of channels, one after the other, for the convolution and pooling layers.
+    /// A row of 64 pixels can be 8 channels of 8 pixels, one for each row of the image
+    pub fn input_channels(mut self, channels: usize) -> NetworkBuilder {
+        self.input.channels = channels;
+        self
+    }
+
+    /// Adds a layer after the previously added one
+    pub fn layer(mut self, layer: LayerSpec) -> NetworkBuilder {
+        self.layers.push(layer);
+        self
+    }
+
+    /// Adds a layer of the given width, with the network's activation function
+    pub fn hidden(self, width: usize) -> NetworkBuilder {
+        self.layer(LayerSpec::new(width))
+    }
+
+    pub fn input_nodes(&self) -> usize {
+        self.input.width
+    }
+
+    pub fn layers(&self) -> &[LayerSpec] {
+        &self.layers
+    }
+
+    pub fn activation_function(&self) -> Option<ActivationFunction> {
+        self.activation_function
+    }
+
+    /// A copy of the builder with its hidden layers resized, keeping the answer layer.
+    /// New hidden layers copy the first hidden layer's spec, or are dense layers as wide as the input layer,
+    /// with the network's activation, if there isn't one.
+    ///
+    /// ## Params
+    /// - Width: The width of every hidden layer, unchanged if None
+    /// - Hidden Layers: The number of hidden layers, unchanged if None
+    pub fn with_hidden(
+        &self,
+        width: Option<usize>,
+        hidden_layers: Option<usize>,
+    ) -> NetworkBuilder {
+        let (answer, hidden) = match self.layers.split_last() {
+            Some((answer, hidden)) => (Some(*answer), hidden),
+            None => (None, &[][..]),
+        };
+        let template = hidden
+            .first()
+            .copied()
+            .unwrap_or(LayerSpec::new(self.input.width));
+
+        let mut layers: Vec<LayerSpec> = match hidden_layers {
+            Some(count) => (0..count)
+                .map(|i| hidden.get(i).copied().unwrap_or(template))
+                .collect(),
+            None => hidden.to_vec(),
+        };
+        if let Some(width) = width {
+            layers.iter_mut().for_each(|layer| layer.width = width);
+        }
+        layers.extend(answer);
+
+        NetworkBuilder {
+            layers,
+            ..self.clone()
+        }
+    }
+
+    /// Builds a categorization network, the answer layer should have one node per category
+    ///
+    /// ## Err
+    /// ### InvalidLayerSpec
+    /// The builder has no layers, a layer has no nodes, or a dropout probability isn't in the range [0, 1)
+    pub fn build_cat(&self) -> Result<CatNetwork, DarjeelingError> {
+        let (node_array, layers) = self.build_parts()?;
+        Ok(CatNetwork::from_parts(
+            node_array,
+            layers,
+            self.activation_function,
+        ))
+    }
+
+    /// Builds a generation network, the answer layer should be as wide as the data being generated
+    ///
+    /// ## Err
+    /// ### InvalidLayerSpec
+    /// The builder has no layers, a layer has no nodes, or a dropout probability isn't in the range [0, 1)
+    pub fn build_gen(&self) -> Result<GenNetwork, DarjeelingError> {
+        let (node_array, layers) = self.build_parts()?;
+        Ok(GenNetwork::from_parts(
+            node_array,
+            layers,
+            self.activation_function,
+        ))
+    }
+
+    /// Builds a multi-label network, the answer layer should have one node per label.
+    /// The answer layer always uses the sigmoid function, whatever activation function it was given.
+    ///
+    /// ## Err
+    /// ### InvalidLayerSpec
+    /// The builder has no layers, a layer has no nodes, or a dropout probability isn't in the range [0, 1)
+    pub fn build_multi_label(&self) -> Result<MultiLabelNetwork, DarjeelingError> {
+        let (node_array, layers) = self.build_parts()?;
+        Ok(MultiLabelNetwork::from_parts(
+            node_array,
+            layers,
+            self.activation_function,
+        ))
+    }
+
+    /// Builds a regression network, the answer layer should have one node per predicted value.
+    /// The answer layer is always linear, whatever activation function it was given.
+    ///
+    /// ## Err
+    /// ### InvalidLayerSpec
+    /// The builder has no layers, a layer has no nodes, or a dropout probability isn't in the range [0, 1)
+    pub fn build_reg(&self) -> Result<RegNetwork, DarjeelingError> {
+        let (node_array, layers) = self.build_parts()?;
+        Ok(RegNetwork::from_parts(
+            node_array,
+            layers,
+            self.activation_function,
+        ))
+    }
+
+    /// Builds an autoencoder, the answer layer should be as wide as the input layer.
+    /// The narrowest hidden layer is the bottleneck, the first one if several are as narrow.
+    /// The answer layer is always linear, whatever activation function it was given.
+    ///
+    /// ## Err
+    /// ### InvalidLayerSpec
+    /// The builder has no hidden layers, the answer layer isn't as wide as the input layer, a layer has no nodes,
+    /// or a dropout probability isn't in the range [0, 1)
+    pub fn build_autoencoder(&self) -> Result<AutoEncoder, DarjeelingError> {
+        let (node_array, layers) = self.build_parts()?;
+        if layers.len() < 3 {
+            return Err(DarjeelingError::InvalidLayerSpec(
+                "an autoencoder needs a hidden layer to be its bottleneck".to_string(),
+            ));
+        }
+        if layers[layers.len() - 1].width != layers[0].width {
+            return Err(DarjeelingError::InvalidLayerSpec(format!(
+                "the answer layer has {} nodes, but the input layer has {}",
+                layers[layers.len() - 1].width,
+                layers[0].width
+            )));
+        }
+        Ok(AutoEncoder::from_parts(
+            node_array,
+            layers,
+            self.activation_function,
+        ))
+    }
+
+    /// Builds a variational autoencoder with this builder as its encoder and the given builder as its decoder.
+    /// The encoder's answer layer has a mean node followed by a log variance node for each latent value,
+    /// so it's twice as wide as the decoder's input layer, and the decoder's answer layer is as wide as the encoder's input layer.
+    /// The decoder's layers use its own activation function, and both answer layers are always linear.
+    ///
+    /// ## Err
+    /// ### InvalidLayerSpec
+    /// The widths of the encoder and decoder don't fit together, either builder has no layers, a layer has no nodes,
+    /// or a dropout probability isn't in the range [0, 1)
+    ///
+    /// ## Examples
+    /// ``` rust
+    /// use darjeeling::{activation::ActivationFunction, builder::NetworkBuilder};
+    ///
+    /// let encoder = NetworkBuilder::new(64)
+    ///     .activation(ActivationFunction::Tanh)
+    ///     .hidden(32)
+    ///     .hidden(2 * 4);
+    /// let decoder = NetworkBuilder::new(4)
+    ///     .activation(ActivationFunction::Sigmoid)
+    ///     .hidden(32)
+    ///     .hidden(64);
+    /// let net = encoder.build_vae(&decoder).unwrap();
+    /// assert_eq!(net.latent_width(), 4);
+    /// ```
+    pub fn build_vae(&self, decoder: &NetworkBuilder) -> Result<VarAutoEncoder, DarjeelingError> {
+        let (encoder_array, encoder_layers) = self.build_parts()?;
+        let (decoder_array, mut decoder_layers) = decoder.build_parts()?;
+        let heads = encoder_layers[encoder_layers.len() - 1].width;
+        if heads != 2 * decoder_layers[0].width {
+            return Err(DarjeelingError::InvalidLayerSpec(format!(
+                "the encoder's answer layer has {} nodes, but the decoder takes {} latent values, which need {} mean and log variance nodes",
+                heads,
+                decoder_layers[0].width,
+                2 * decoder_layers[0].width
+            )));
+        }
+        if decoder_layers[decoder_layers.len() - 1].width != encoder_layers[0].width {
+            return Err(DarjeelingError::InvalidLayerSpec(format!(
+                "the decoder's answer layer has {} nodes, but the encoder's input layer has {}",
+                decoder_layers[decoder_layers.len() - 1].width,
+                encoder_layers[0].width
+            )));
+        }
+        // The network has a single activation function, so the decoder's is kept by its layers
+        if let Some(activation_function) = decoder.activation_function {
+            decoder_layers
+                .iter_mut()
+                .filter(|layer| layer.activation.is_none())
+                .for_each(|layer| layer.activation = Some(activation_function));
+        }
+        Ok(VarAutoEncoder::from_parts(
+            encoder_array,
+            encoder_layers,
+            decoder_array,
+            decoder_layers,
+            self.activation_function.or(decoder.activation_function),
+        ))
+    }
+
+    pub(crate) fn build_parts(&self) -> Result<(NodeArray, Box<[LayerSpec]>), DarjeelingError> {
+        if self.layers.is_empty() {
+            return Err(DarjeelingError::InvalidLayerSpec(
+                "the network needs at least an answer layer".to_string(),
+            ));
+        }
+
+        let mut layers: Box<[LayerSpec]> = std::iter::once(self.input)
+            .chain(self.layers.iter().copied())
+            .collect();
+        if layers[0].channels == 0 || layers[0].width % layers[0].channels != 0 {
+            return Err(DarjeelingError::InvalidLayerSpec(format!(
+                "{} sensors can't be split into {} channels",
+                layers[0].width, layers[0].channels
+            )));
+        }
+        for i in 1..layers.len() {
+            layers[i] = resolve_window(i, &layers[i - 1], layers[i])?;
+        }
+
+        for (i, spec) in layers.iter().enumerate() {
+            if spec.width == 0 {
+                return Err(DarjeelingError::InvalidLayerSpec(format!(
+                    "layer {} has no nodes",
+                    i
+                )));
+            }
+            if !(0.0..1.0).contains(&spec.dropout) {
+                return Err(DarjeelingError::InvalidLayerSpec(format!(
+                    "layer {} has a dropout of {}, which isn't in the range [0, 1)",
+                    i, spec.dropout
+                )));
+            }
+        }
+
+        let mut rng = rand::thread_rng();
+        Ok((layer::build_node_array(&layers, &mut rng), layers))
+    }
+}
+
+/// Works out the channels and width of a convolution or pooling layer from the layer before it
+fn resolve_window(
+    i: usize,
+    prev: &LayerSpec,
+    mut spec: LayerSpec,
+) -> Result<LayerSpec, DarjeelingError> {
+    let window = match spec.kind {
+        LayerKind::Dense => {
+            spec.channels = 1;
+            return Ok(spec);
+        }
+        LayerKind::Conv1D { window, .. }
+        | LayerKind::MaxPool1D(window)
+        | LayerKind::AvgPool1D(window) => window,
+    };
+    if window.size == 0 || window.stride == 0 || window.dilation == 0 {
+        return Err(DarjeelingError::InvalidLayerSpec(format!(
+            "layer {} has a window of {:?}, its size, stride and dilation need to be at least one",
+            i, window
+        )));
+    }
+    let input_len = prev.width / prev.channels.max(1);
+    let output_len = window.output_len(input_len).ok_or_else(|| {
+        DarjeelingError::InvalidLayerSpec(format!(
+            "layer {} has a window of {:?}, which is longer than the {} values of each padded channel of the layer before",
+            i,
+            window,
+            input_len + 2 * window.padding
+        ))
+    })?;
+
+    spec.channels = match spec.kind {
+        LayerKind::Conv1D { filters, .. } => filters,
+        _ => prev.channels,
+    };
+    spec.width = spec.channels * output_len;
+    Ok(spec)
+}
@@ -1,20 +1,36 @@
 use crate::{
-    activation::ActivationFunction, bench, dbg_println, error::DarjeelingError,
-    neural_network::NeuralNetwork, node::Node, series::Series, utils::RandomIter, DEBUG,
+    activation::ActivationFunction,
+    bench,
+    builder::NetworkBuilder,
+    checkpoint::{Checkpoint, TrainState},
+    config::{ConfigField, TrainConfig},
+    dataframe::{DataFrame, Value},
+    dbg_println,
+    error::DarjeelingError,
+    layer::{self, LayerSpec},
+    metrics::ClassificationReport,
+    neural_network::{Model, NeuralNetwork},
+    node::Node,
+    preprocessing::{check_missing, feature_rows, pipeline::Pipeline, scaler::Scaler, Transform},
+    series::Series,
+    split, DEBUG,
 };
 use rand::Rng;
-use rayon::prelude::*;
 use serde::{Deserialize, Serialize};
-use std::{
-    fmt::{self, Debug},
-    fs,
-};
+use std::{borrow::Cow, fmt, time::Instant};
 
 /// The categorization Neural Network struct
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct CatNetwork {
     node_array: Box<[Box<[Node]>]>,
+    layers: Box<[LayerSpec]>,
     activation_function: Option<ActivationFunction>,
+    /// The config the network was last trained with
+    config: Option<TrainConfig>,
+    /// Every input goes through it before it reaches the network
+    pipeline: Option<Pipeline>,
+    /// The categories of the answer nodes, in order
+    categories: Option<Box<[String]>>,
 }
 
 impl NeuralNetwork for CatNetwork {
@@ -27,20 +43,23 @@ impl NeuralNetwork for CatNetwork {
     /// - Hidden: The number of hidden nodes per hidden layer
     /// - Answer: The number of answer nodes, or possible categories
     /// - Hidden Layers: The number of different hidden layers
-    /// - Activation Function: Which activation function is used by the network. This can be changed layer with the [`set_activation_func`](fn@set_activation_func) method.
+    /// - Activation Function: Which activation function is used by the network. This can be changed layer with the [`set_activation_func`](CatNetwork::set_activation_func) method.
+    ///
+    /// Every hidden layer has the same width, use a [`NetworkBuilder`] for anything else.
     ///
     /// ## Examples
     /// ``` rust
     /// use darjeeling::{
     ///     activation::ActivationFunction,
-    ///     categorize::CatNetwork
+    ///     categorize::CatNetwork,
+    ///     neural_network::NeuralNetwork
     /// };
     ///
     /// let inputs: usize = 10;
     /// let hidden: usize = 40;
     /// let answer: usize = 2;
     /// let hidden_layers: usize = 1;
-    /// let mut net = CatNetwork::new(inputs, hidden, answer, hidden_layers, ActivationFunction::Sigmoid);
+    /// let mut net = CatNetwork::new(inputs, hidden, answer, hidden_layers, Some(ActivationFunction::Sigmoid));
     /// ```
     fn new(
         input_nodes: usize,
@@ -49,54 +68,18 @@ impl NeuralNetwork for CatNetwork {
         hidden_layers: usize,
         activation_function: Option<ActivationFunction>,
     ) -> CatNetwork {
-        // links point backwards to previous layer
-        let mut rng = rand::thread_rng();
-
-        let mut node_array: Vec<Box<[Node]>> = vec![];
-
-        let input_row: Box<[Node]> = (0..input_nodes)
-            .map(|_| Node::new(vec![].into_boxed_slice(), 1.0))
-            .collect::<Box<[Node]>>();
-        node_array.push(input_row);
-
-        // links point backwards, so the first layer has a different number of them
-        let first_hidden_layer = (0..hidden_nodes)
-            .into_iter()
-            .map(|_| {
-                Node::new(
-                    vec![rng.gen_range(-0.5..0.5); input_nodes].into_boxed_slice(),
-                    rng.gen_range(-0.5..0.5),
-                )
+        let mut builder = (0..hidden_layers.max(1))
+            .fold(NetworkBuilder::new(input_nodes), |builder, _| {
+                builder.hidden(hidden_nodes)
             })
-            .collect();
-        node_array.push(first_hidden_layer);
-
-        // includes the answer node
-        for _ in 1..hidden_layers {
-            let hidden_vec: Box<[Node]> = (0..hidden_nodes)
-                .into_iter()
-                .map(|_| 
-                    Node::new(
-                        vec![rng.gen_range(-0.5..0.5); hidden_nodes].into_boxed_slice(),
-                        rng.gen_range(-0.5..0.5),
-                    )
-                )
-                .collect();
-            node_array.push(hidden_vec);
+            .hidden(answer_nodes);
+        if let Some(activation_function) = activation_function {
+            builder = builder.activation(activation_function);
         }
 
-        let answer_layer = (0..answer_nodes)
-            .into_iter()
-            .map(|_| Node::new(
-                vec![rng.gen_range(-0.5..0.5); hidden_nodes].into_boxed_slice(),
-                rng.gen_range(-0.5..0.5))).collect();
-
-        node_array.push(answer_layer);
-
-        CatNetwork {
-            node_array: node_array.into_boxed_slice(),
-            activation_function,
-        }
+        builder
+            .build_cat()
+            .expect("Every layer of the network needs at least one node")
     }
 
     /// Trains the neural network model to be able to categorize items in a dataset into given categories
@@ -104,27 +87,27 @@ impl NeuralNetwork for CatNetwork {
     /// ## Params
     /// - Data: List of inputs
     /// - Categories: List of Strings, each denoting an answer category.
-    /// The number of answer nodes should be the same of the number of categories
-    /// - Learning Rate: The modifier that is applied to link weights as they're adjusted.
-    /// Try fiddling with this one, but -1.5 - 1.5 is recommended to start.
-    /// - Name: The name of the network
-    /// - Target Error Percent: The error percent at which the network will be stop training, checked at the begining of each new epoch.
-    /// - Write: True of you want to write the model to a file, false otherwise
+    ///   The number of answer nodes should be the same of the number of categories
+    /// - Config: The hyperparameters of the training, see [`TrainConfig`].
+    ///   The model is written to the config's save path once training finishes, if it has one.
     ///
     /// ## Returns
     /// The fallible:
-    /// - name of the model that this neural network trained(the name parameter with a random u32 appended)
-    /// some if write is true, none is write is false
-    /// - the error percentage of the last epoch
-    /// - the mse of the training
+    /// - save path the model was written to, none if the config has no save path
+    /// - the accuracy percentage of the last epoch
+    /// - the mse of the last epoch
     ///
     /// ## Err
+    /// - ### ModelMissingActivationFunction
+    /// The network has no activation function to train with
+    /// - ### InvalidLayerSpec
+    /// An input isn't as wide as the input layer
+    /// - ### NanEncountered
+    /// An input has a missing (NaN) feature, or the training diverged
     /// - ### WriteModelFailed
     /// There was a problem when saving the model to a file
-    /// - ### ModelNameAlreadyExists
-    /// The random model name chosen already exists
-    ///
-    /// Change the name or retrain
+    /// - ### UnsupportedConfig
+    /// The config has a loss other than the default, since the squared error is always minimized
     /// - ### UnknownError
     /// Not sure what happened, but something failed
     ///
@@ -137,10 +120,12 @@ impl NeuralNetwork for CatNetwork {
     /// use darjeeling::{
     /// categorize::CatNetwork,
     /// activation::ActivationFunction,
-    /// input::Input,
+    /// config::TrainConfig,
+    /// neural_network::NeuralNetwork,
+    /// series::Series,
     /// // This file may not be avaliable
     /// // Everything found here will be hyper-specific to your project.
-    /// tests::{categories_str_format, xor_file}
+    /// tests::xor_file
     /// };
     ///
     /// // A file containing all possible inputs and correct outputs still needs to be make by you
@@ -150,88 +135,30 @@ impl NeuralNetwork for CatNetwork {
     /// // 1 1;0
     /// // You also need to write the file input function
     /// // Automatic file reading and formatting function coming soon
-    /// let categories: Vec<String> = categories_str_format(vec!["0", "1"]);
-    /// let mut data: Vec<Input> = xor_file();
-    /// let mut net = CatNetwork::new(2, 2, 2, 1, ActivationFunction::Sigmoid);
-    /// let learning_rate = 1.0;
-    /// let (model_name, error_percentage, mse) = net.train(&data, categories, learning_rate, "xor", 99.0, true).unwrap();
+    /// let categories: Box<[String]> = vec!["0".to_string(), "1".to_string()].into_boxed_slice();
+    /// let data: Box<[Series]> = xor_file();
+    /// let mut net = CatNetwork::new(2, 2, 2, 1, Some(ActivationFunction::Sigmoid));
+    /// let config = TrainConfig {
+    ///     learning_rate: 1.0,
+    ///     target_accuracy: Some(99.0),
+    ///     save_path: Some("xor.darj".to_string()),
+    ///     ..Default::default()
+    /// };
+    /// let (model_name, accuracy, mse) = net.train(&data, categories, &config).unwrap();
     /// ```
     fn train(
         &mut self,
-        data: &Box<[Series]>,
+        data: &[Series],
         categories: Box<[String]>,
-        learning_rate: f32,
-        name: &str,
-        target_err_percent: f32,
-        write: bool,
+        config: &TrainConfig,
     ) -> Result<(Option<String>, f32, f32), DarjeelingError> {
-        let activation_function = match self.activation_function {
-            Some(s) => s,
-            None => return Err(DarjeelingError::ModelMissingActivationFunction),
-        };
-
-        let mut epochs = 0.0;
-        let mut sum = 0.0;
-        let mut count = 0.0;
-        let mut err_percent = 0.0;
-        let mut mse = 0.0;
-
-        dbg_println!("Categorize");
-        bench!(self.categorize(categories));
-
-        while err_percent < target_err_percent {
-            count = 0.0;
-            sum = 0.0;
-            err_percent = 0.00;
-
-            let data_iter = RandomIter::new(data);
-            for series in data_iter {
-                //dbg_println!("Training Checkpoint One Passed");
-
-                //dbg_println!("Assign");
-                self.assign_answers(series);
-
-                //dbg_println!("Push");
-                self.push_downstream(series, activation_function);
-
-                //dbg_println!("Sum: {:?} Count: {:?}", sum, count);
-
-                //dbg_println!("Analysis");
-                self.self_analysis(
-                    &mut Some(epochs),
-                    &mut sum,
-                    &mut count,
-                    series,
-                    &mut mse,
-                );
-
-                //dbg_println!("Sum: {:?} Count: {:?}", sum, count);
-
-                //dbg_println!("Backpropogate");
-                self.backpropogate(learning_rate, activation_function);
-            }
-
-            // let _old_err_percent = err_percent;
-            err_percent = (sum / count) * 100.0;
-            epochs += 1.0;
-            println!("Epoch: {:?}", epochs);
-            println!("Training Accuracy: {:?}", err_percent);
-            //if err_percent - old_err_percent < 0.00000001 { break; }
-        }
-        let mut model_name: Option<String> = None;
-        if write {
-            model_name = Some(self.write_model(&name)?);
-        }
-
-        println!("Training: Finished with accuracy of {:?}/{:?} or {:?} percent after {:?} epochs\nmse: {}", sum, count, err_percent, epochs, mse);
-
-        Ok((model_name, err_percent, mse))
+        self.train_from(data, categories, config, TrainState::new(config.seed))
     }
 
     /// Tests a pretrained model
     fn test(
         &mut self,
-        data: &Box<[Series]>,
+        data: &[Series],
         categories: Box<[String]>,
     ) -> Result<Vec<String>, DarjeelingError> {
         let mut sum = 0.0;
@@ -245,11 +172,16 @@ impl NeuralNetwork for CatNetwork {
             None => return Err(DarjeelingError::ModelMissingActivationFunction),
         };
 
-        self.categorize(categories);
+        self.categorize(&categories);
+        let data = self.preprocess(data)?;
+        data.iter()
+            .try_for_each(|series| layer::check_input(&self.node_array, &series.data))?;
 
         data.iter().for_each(|series| {
             dbg_println!("Testing Checkpoint One Passed");
-            self.push_downstream(series, activation_function);
+            // Loaded models don't keep the correct answers of their last training input
+            self.assign_answers(series);
+            self.push_downstream(series, activation_function, false, &mut rand::thread_rng());
             dbg_println!("Sum: {:?} Count: {:?}", sum, count);
             answers.push(
                 self.self_analysis(&mut None, &mut sum, &mut count, series, &mut mse)
@@ -275,8 +207,418 @@ impl NeuralNetwork for CatNetwork {
 }
 
 impl CatNetwork {
+    pub(crate) fn from_parts(
+        node_array: Box<[Box<[Node]>]>,
+        layers: Box<[LayerSpec]>,
+        activation_function: Option<ActivationFunction>,
+    ) -> CatNetwork {
+        CatNetwork {
+            node_array,
+            layers,
+            activation_function,
+            config: None,
+            pipeline: None,
+            categories: None,
+        }
+    }
+
+    /// The config the network was last trained with, saved models keep it
+    pub fn config(&self) -> Option<&TrainConfig> {
+        self.config.as_ref()
+    }
+
+    /// Sets the pipeline every input goes through before it reaches the network.
+    /// An unfitted pipeline is fitted on the training data the next time the network is trained,
+    /// and it's saved with the model.
+    pub fn set_pipeline(&mut self, pipeline: Pipeline) {
+        self.pipeline = Some(pipeline);
+    }
+
+    /// Sets a pipeline of just an already fitted scaler, see [`set_pipeline`](CatNetwork::set_pipeline)
+    ///
+    /// ## Examples
+    /// ```ignore
+    /// use darjeeling::preprocessing::scaler::StandardScaler;
+    ///
+    /// net.set_scaler(StandardScaler::fit(&data).unwrap());
+    /// net.train(&data, categories, &config).unwrap();
+    /// ```
+    pub fn set_scaler(&mut self, scaler: impl Into<Scaler>) {
+        self.pipeline = Some(Pipeline::from(scaler.into()));
+    }
+
+    /// The pipeline every input goes through, saved models keep it
+    pub fn pipeline(&self) -> Option<&Pipeline> {
+        self.pipeline.as_ref()
+    }
+
+    /// Trains the network on a dataframe, whose categorical columns are encoded by the network's pipeline.
+    /// A network without a pipeline gets an empty one, so it remembers the dataframe's columns.
+    ///
+    /// ## Params
+    /// - Frame: The training data
+    /// - Label: The column of answers, every other column is a feature
+    /// - Categories: The categories of the answer nodes, see [`train`](NeuralNetwork::train)
+    /// - Config: The hyperparameters of the training
+    ///
+    /// ## Returns
+    /// The same values as [`train`](NeuralNetwork::train)
+    ///
+    /// ## Err
+    /// The errors of [`train`](NeuralNetwork::train) and [`Pipeline::fit_frame`]
+    pub fn train_frame(
+        &mut self,
+        frame: &DataFrame,
+        label: &str,
+        categories: Box<[String]>,
+        config: &TrainConfig,
+    ) -> Result<(Option<String>, f32, f32), DarjeelingError> {
+        let mut pipeline = self.pipeline.take().unwrap_or_default();
+        let fitted = if pipeline.is_fitted() {
+            Ok(())
+        } else {
+            pipeline.fit_frame(frame, label)
+        };
+        let data = fitted.and_then(|_| pipeline.encode_frame(frame, Some(label)));
+        self.pipeline = Some(pipeline);
+        self.train(&data?, categories, config)
+    }
+
+    /// Categorizes a single raw input with a trained model, running it through the pipeline first
+    ///
+    /// ## Returns
+    /// The category of the brightest answer node
+    ///
+    /// ## Err
+    /// ### ModelMissingActivationFunction
+    /// The network has no activation function
+    /// ### InvalidLayerSpec
+    /// The input isn't as wide as the input layer
+    /// ### ModelMissingCategories
+    /// The network hasn't been trained, so its answer nodes have no categories
+    /// ### TransformFailed
+    /// The input doesn't have the features the pipeline was fitted on
+    ///
+    /// ## Examples
+    /// ```ignore
+    /// let mut net = CatNetwork::read_model("digits.darj".to_string()).unwrap();
+    /// let digit = net.predict(&pixels).unwrap();
+    /// ```
+    pub fn predict(&mut self, data: &[f32]) -> Result<String, DarjeelingError> {
+        let data = match &self.pipeline {
+            Some(pipeline) => pipeline.transform_row(data)?,
+            None => data.into(),
+        };
+        self.answer(&data)
+    }
+
+    /// Categorizes a single raw record, with a value for every feature column of the dataframe
+    /// the network was trained on with [`train_frame`](CatNetwork::train_frame), in the same order
+    ///
+    /// ## Err
+    /// The errors of [`predict`](CatNetwork::predict) and [`Pipeline::transform_record`]
+    pub fn predict_record(&mut self, values: &[Value]) -> Result<String, DarjeelingError> {
+        let data = self
+            .pipeline
+            .as_ref()
+            .ok_or_else(|| {
+                DarjeelingError::TransformFailed(
+                    "the network wasn't trained on a dataframe, so it doesn't know the record's columns"
+                        .to_string(),
+                )
+            })?
+            .transform_record(values)?;
+        self.answer(&data)
+    }
+
+    /// Categorizes every row of a dataframe, whose feature columns can be in any order.
+    /// The label column is ignored if the dataframe has one.
+    ///
+    /// ## Err
+    /// The errors of [`predict`](CatNetwork::predict) and [`Pipeline::transform_frame`]
+    pub fn predict_frame(&mut self, frame: &DataFrame) -> Result<Vec<String>, DarjeelingError> {
+        let data = match &self.pipeline {
+            Some(pipeline) => pipeline.transform_frame(frame, None)?,
+            None => frame
+                .to_rows()?
+                .into_vec()
+                .into_iter()
+                .map(|row| Series::new(row, ""))
+                .collect(),
+        };
+        data.iter()
+            .map(|series| self.answer(&series.data))
+            .collect()
+    }
+
+    /// The outputs of the answer layer for each input, in the order of the categories the network was trained on
+    ///
+    /// ## Err
+    /// ### ModelMissingActivationFunction
+    /// The network has no activation function
+    /// ### InvalidLayerSpec
+    /// An input isn't as wide as the input layer
+    pub fn scores(&mut self, data: &[Series]) -> Result<Box<[Box<[f32]>]>, DarjeelingError> {
+        let data = self.preprocess(data)?;
+
+        data.iter()
+            .map(|series| {
+                layer::outputs(
+                    &mut self.node_array,
+                    &self.layers,
+                    self.activation_function,
+                    &series.data,
+                )
+            })
+            .collect()
+    }
+
+    /// Evaluates a pretrained model against labelled data
+    ///
+    /// ## Params
+    /// - Data: The inputs to evaluate, with their correct answers
+    /// - Categories: The categories the network was trained on, in the same order
+    ///
+    /// ## Returns
+    /// A report with a confusion matrix, per category metrics, their averages and the log loss
+    ///
+    /// ## Err
+    /// ### ModelMissingActivationFunction
+    /// The network has no activation function
+    /// ### CategoryDoesNotExist
+    /// An input's answer isn't one of the categories
+    ///
+    /// ## Examples
+    /// ```ignore
+    /// let report = net.evaluate(&data, &categories).unwrap();
+    /// println!("{}", report);
+    /// fs::write("report.json", report.to_json().unwrap()).unwrap();
+    /// ```
+    pub fn evaluate(
+        &mut self,
+        data: &[Series],
+        categories: &[String],
+    ) -> Result<ClassificationReport, DarjeelingError> {
+        let scores = self.scores(data)?;
+        let answers: Box<[String]> = data.iter().map(|series| series.answer.clone()).collect();
+        ClassificationReport::new(categories, &answers, &scores)
+    }
+
+    /// Continues a training run from a checkpoint written during [`train`](NeuralNetwork::train),
+    /// exactly where it stopped.
+    /// The config saved in the checkpoint is used, so more epochs can be added by changing its `epochs`.
+    ///
+    /// ## Params
+    /// - Checkpoint: A checkpoint read with [`Checkpoint::read`]
+    /// - Data: The same data the interrupted run was trained on
+    ///
+    /// ## Returns
+    /// The trained network, followed by the same values as [`train`](NeuralNetwork::train)
+    ///
+    /// ## Examples
+    /// ```ignore
+    /// use darjeeling::{categorize::CatNetwork, checkpoint::Checkpoint};
+    ///
+    /// let checkpoint = Checkpoint::<CatNetwork>::read("digits.ckpt").unwrap();
+    /// let (net, model_name, accuracy, mse) = CatNetwork::resume_from(checkpoint, &data).unwrap();
+    /// ```
+    pub fn resume_from(
+        checkpoint: Checkpoint<CatNetwork>,
+        data: &[Series],
+    ) -> Result<(CatNetwork, Option<String>, f32, f32), DarjeelingError> {
+        let Checkpoint {
+            mut network,
+            categories,
+            config,
+            state,
+        } = checkpoint;
+
+        let (model_name, err_percent, mse) =
+            network.train_from(data, categories, &config, state)?;
+        Ok((network, model_name, err_percent, mse))
+    }
+
+    /// Runs the training loop from the given state
+    fn train_from(
+        &mut self,
+        data: &[Series],
+        categories: Box<[String]>,
+        config: &TrainConfig,
+        mut state: TrainState,
+    ) -> Result<(Option<String>, f32, f32), DarjeelingError> {
+        config.check_unused("categorization training", &[ConfigField::Loss])?;
+        let activation_function = match self.activation_function {
+            Some(s) => s,
+            None => return Err(DarjeelingError::ModelMissingActivationFunction),
+        };
+
+        let mut sum = 0.0;
+        let mut count = 0.0;
+        let mut err_percent = 0.0;
+        let mut mse = 0.0;
+        let mut last_checkpoint = Instant::now();
+
+        dbg_println!("Categorize");
+        bench!(self.categorize(&categories));
+        if let Some(pipeline) = self
+            .pipeline
+            .as_mut()
+            .filter(|pipeline| !pipeline.is_fitted())
+        {
+            pipeline.fit(data)?;
+        }
+        let data = self.preprocess(data)?;
+        check_missing(&feature_rows(&data))?;
+        data.iter()
+            .try_for_each(|series| layer::check_input(&self.node_array, &series.data))?;
+        let weights = match &config.class_weights {
+            Some(class_weights) => Some(class_weights.weights(&categories, &data)?),
+            None => None,
+        };
+
+        while state.epoch < config.epochs {
+            count = 0.0;
+            sum = 0.0;
+            mse = 0.0;
+            let learning_rate = config.learning_rate_at(state.epoch);
+            let mut batch_len = 0;
+
+            let shuffled: Vec<&Series> = split::shuffled(&data, &mut state.rng);
+            for series in shuffled {
+                self.assign_answers(series);
+                self.push_downstream(series, activation_function, true, &mut state.rng);
+
+                // mse is only accumulated by self analysis during testing
+                self.self_analysis(
+                    &mut Some(state.epoch as f32),
+                    &mut sum,
+                    &mut count,
+                    series,
+                    &mut mse,
+                );
+                mse += self.squared_error();
+
+                let weight = weights
+                    .as_ref()
+                    .and_then(|weights| {
+                        categories
+                            .iter()
+                            .position(|category| *category == series.answer)
+                            .map(|i| weights[i])
+                    })
+                    .unwrap_or(1.0);
+                self.backpropogate(activation_function, weight);
+                batch_len += 1;
+                if batch_len == config.batch_size.max(1) {
+                    state.optimizer_state.step(
+                        config.optimizer,
+                        &mut self.node_array,
+                        learning_rate,
+                        batch_len,
+                    );
+                    batch_len = 0;
+                }
+            }
+            if batch_len > 0 {
+                state.optimizer_state.step(
+                    config.optimizer,
+                    &mut self.node_array,
+                    learning_rate,
+                    batch_len,
+                );
+            }
+
+            err_percent = (sum / count) * 100.0;
+            mse /= count;
+            if mse.is_nan() {
+                return Err(DarjeelingError::NanEncountered(format!(
+                    "the mse of epoch {} is NaN, try a lower learning rate",
+                    state.epoch + 1
+                )));
+            }
+            state.epoch += 1;
+            state.best_accuracy = state.best_accuracy.max(err_percent);
+            println!("Epoch: {:?}", state.epoch);
+            println!("Training Accuracy: {:?}", err_percent);
+
+            let improved = state.best_mse - mse
+                > config
+                    .early_stopping
+                    .map_or(0.0, |early_stopping| early_stopping.min_delta);
+            if improved {
+                state.best_mse = mse;
+                state.epochs_without_improvement = 0;
+            } else {
+                state.epochs_without_improvement += 1;
+            }
+
+            if let Some(checkpoint) = &config.checkpoint {
+                if checkpoint.is_due(state.epoch, last_checkpoint) {
+                    Checkpoint {
+                        network: self.clone(),
+                        categories: categories.clone(),
+                        config: config.clone(),
+                        state: state.clone(),
+                    }
+                    .write(&checkpoint.path)?;
+                    last_checkpoint = Instant::now();
+                }
+            }
+
+            if config
+                .target_accuracy
+                .map_or(false, |target| err_percent >= target)
+            {
+                break;
+            }
+            if let Some(early_stopping) = config.early_stopping {
+                if state.epochs_without_improvement >= early_stopping.patience {
+                    println!(
+                        "Training: Stopping early, mse hasn't improved in {:?} epochs",
+                        state.epochs_without_improvement
+                    );
+                    break;
+                }
+            }
+        }
+
+        self.config = Some(config.clone());
+        let mut model_name: Option<String> = None;
+        if let Some(save_path) = &config.save_path {
+            self.save_model(save_path)?;
+            model_name = Some(save_path.clone());
+        }
+
+        println!("Training: Finished with accuracy of {:?}/{:?} or {:?} percent after {:?} epochs\nmse: {}", sum, count, err_percent, state.epoch, mse);
+
+        Ok((model_name, err_percent, mse))
+    }
+
+    /// Runs inputs through the pipeline, if the network has one
+    fn preprocess<'a>(&self, data: &'a [Series]) -> Result<Cow<'a, [Series]>, DarjeelingError> {
+        match &self.pipeline {
+            Some(pipeline) => Ok(Cow::Owned(pipeline.transform(data)?.into_vec())),
+            None => Ok(Cow::Borrowed(data)),
+        }
+    }
+
+    /// The category of the brightest answer node for an input that's already been through the pipeline
+    fn answer(&mut self, data: &[f32]) -> Result<String, DarjeelingError> {
+        layer::outputs(
+            &mut self.node_array,
+            &self.layers,
+            self.activation_function,
+            data,
+        )?;
+        match &self.categories {
+            Some(categories) => Ok(categories[self.largest_node()].clone()),
+            None => Err(DarjeelingError::ModelMissingCategories),
+        }
+    }
+
     /// Assigns categories to answer nodes based on a list of given categories
-    fn categorize(&mut self, categories: Box<[String]>) {
+    fn categorize(&mut self, categories: &[String]) {
+        self.categories = Some(categories.into());
         self.node_array
             .last_mut()
             .expect("Network has no answer layer")
@@ -300,37 +642,21 @@ impl CatNetwork {
     }
 
     /// Passes in data to the sensors, pushs data 'downstream' through the network
-    fn push_downstream(&mut self, data: &Series, activation_function: ActivationFunction) {
-        // Pass data to the input layer
-        self.node_array.first_mut().expect("Neural Network has no layers")
-            .iter_mut().enumerate().for_each(|(i, node)| {
-                node.cached_output = Some(data.data[i]);
-            });
-
-        // Push forward hidden and output layers
-        for layer_i in 1..self.node_array.len() {
-            // Clone the cached outputs from the previous layer
-            let prev_cached_outputs: Box<[f32]> = self.node_array[layer_i - 1]
-                .iter()
-                .map(|prev_node| {
-                    prev_node
-                        .cached_output
-                        .expect("Previous nodes do not have cached outputs")
-                })
-                .collect();
-
-            let layer = self.node_array[layer_i].iter_mut();
-
-            layer.for_each(|node| {
-                prev_cached_outputs
-                    .iter()
-                    .enumerate()
-                    .for_each(|(prev_node_i, prev_output)| {
-                        node.link_vals[prev_node_i] = *prev_output;
-                        node.cached_output = Some(node.output(activation_function));
-                    });
-            });
-        }
+    fn push_downstream(
+        &mut self,
+        data: &Series,
+        activation_function: ActivationFunction,
+        training: bool,
+        rng: &mut impl Rng,
+    ) {
+        layer::push_downstream(
+            &mut self.node_array,
+            &self.layers,
+            activation_function,
+            &data.data,
+            training,
+            rng,
+        );
     }
 
     /// Analyses the chosen answer node's result.
@@ -418,110 +744,51 @@ impl CatNetwork {
 
         largest_index
     }
-    /// Goes back through the network adjusting the weights of the all the neurons based on their error signal
-    fn backpropogate(&mut self, learning_rate: f32, activation_function: ActivationFunction) {
-        let hidden_layers = self.node_array.len() - 2;
-
-        self.node_array
-            .last_mut()
-            .expect("Network has no layers")
-            .iter_mut()
-            .for_each(|node|{ node.compute_answer_err_sig(activation_function);});
-
-        self.adjust_hidden_weights(learning_rate, hidden_layers);
-
+    /// The squared error of the answer layer's outputs, summed over every answer node
+    fn squared_error(&self) -> f32 {
         self.node_array
-            .last_mut()
+            .last()
             .expect("Network has no layers")
-            .iter_mut()
-            .for_each(|node| {node.adjust_weights(learning_rate);});
-    }
-
-    /// Adjusts the weights of all the hidden neurons in a network
-    fn adjust_hidden_weights(&mut self, learning_rate: f32, hidden_layers: usize) {
-        for layer in 1..=hidden_layers {
-            for node in 0..self.node_array[layer].len() {
-                self.node_array[layer][node].err_sig = Some(0.0);
-                // link weights, err sigs
-                let mut next_layer: Box<[(f32, f32)]> = self.node_array[layer + 1]
-                    .iter()
-                    .map(|next_node| {
-                        (
-                            next_node.link_weights[node],
-                            next_node.err_sig.unwrap_or(0.00),
-                        )
-                    })
-                    .collect();
-
-                next_layer.iter_mut().for_each(|(next_weight, err_sig)| {
-                    // This changes based on the activation function
-                    self.node_array[layer][node].err_sig =
-                        Some(*err_sig + (*err_sig * *next_weight));
-
-                    dbg_println!("next err sig {:?}", err_sig);
-                    dbg_println!("next weight {:?}", next_weight);
-                });
-
-                let hidden_result = self.node_array[layer][node].cached_output.unwrap();
-                // TODO: This is contains the derivative and changes based on the activation function
-                self.node_array[layer][node].err_sig = Some(self.node_array[layer][node].err_sig.unwrap() * hidden_result * (1.0 - hidden_result));
-
-                dbg_println!("New hidden errsig multiply: {:?}", self.node_array[layer][node].err_sig);
-                dbg_println!("\nLayer: {:?}", layer);
-                dbg_println!("Node: {:?}", node);
-
-                self.node_array[layer][node].adjust_weights(learning_rate);
-            }
-        }
-    }
-
-    /// Serializes a trained model so it can be used later
-    ///
-    /// ## Returns
-    /// The name of the model
-    ///
-    /// ## Err
-    /// ### WriteModelFailed:
-    /// Wraps the models name
-    /// ### UnknownError:
-    /// Wraps error
-    ///  
-    pub fn write_model(&mut self, name: &str) -> Result<String, DarjeelingError> {
-        let bin = match bincode::serialize(self) {
-            Ok(v) => v,
-            Err(err) => return Err(DarjeelingError::SaveModelFailed(err.to_string())),
-        };
-
-        let mut rng = rand::thread_rng();
-        let num = rng.gen_range(0..i32::MAX);
-
-        let model_name = format!("{}_{}.darj", name, num);
-        match fs::write(name, bin) {
-            Ok(_) => Ok(model_name),
-            Err(err) => Err(DarjeelingError::SaveModelFailed(err.to_string())),
-        }
+            .iter()
+            .map(|node| {
+                (node
+                    .correct_answer
+                    .expect("Answer Node Missing Correct Answer")
+                    - node
+                        .cached_output
+                        .expect("Answer Node Missing Cached Output"))
+                .powi(2)
+            })
+            .sum()
     }
 
-    /// Reads a file containing a serizalized Categorization Network
-    ///
-    /// ## Params
-    /// - Model Name: The name(or more helpfully the path) of the model to be read
-    ///
-    /// ## Returns
-    /// A neural network read from a serialized .darj file
-    ///
-    /// ## Err
-    /// If the file cannnot be read, or if the file does not contain a valid serialized Neural Network
-    pub fn read_model(model_name: String) -> Result<CatNetwork, DarjeelingError> {
-        let read_bin = match fs::read(&model_name) {
-            Ok(bin) => bin,
-            Err(err) => return Err(DarjeelingError::LoadModelFailed(err.to_string())),
-        };
+    /// Goes back through the network computing the error signal of all the neurons,
+    /// and adds the resulting gradients to the current batch, scaled by the input's class weight
+    fn backpropogate(&mut self, activation_function: ActivationFunction, weight: f32) {
+        // The derivative of the weighted squared error with respect to each answer node's output
+        let output_grads: Box<[f32]> = self
+            .node_array
+            .last()
+            .expect("Network has no layers")
+            .iter()
+            .map(|node| {
+                weight
+                    * (node
+                        .cached_output
+                        .expect("Answer Node Missing Cached Output")
+                        - node
+                            .correct_answer
+                            .expect("Answer Node Missing Correct Answer"))
+            })
+            .collect();
 
-        match bincode::deserialize(&read_bin) {
-            Ok(net) => Ok(net),
-            Err(err) => Err(DarjeelingError::LoadModelFailed(err.to_string())),
-        }
+        layer::backpropogate(
+            &mut self.node_array,
+            &self.layers,
+            activation_function,
+            &output_grads,
+        );
+        layer::accumulate_grads(&mut self.node_array, &self.layers);
     }
 
     pub fn set_activation_func(&mut self, new_activation_function: ActivationFunction) {
@@ -529,6 +796,8 @@ impl CatNetwork {
     }
 }
 
+impl Model for CatNetwork {}
+
 impl fmt::Display for CatNetwork {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         let mut buff = String::from("");
@@ -0,0 +1,106 @@
+use crate::{config::TrainConfig, error::DarjeelingError, optimizer::OptimizerState};
+use rand::SeedableRng;
+use rand_pcg::Pcg64;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use std::{fs, time::Instant};
+
+/// When and where checkpoints are written during training.
+///
+/// Checkpoints are written at the end of an epoch, once either interval has passed.
+/// Every checkpoint overwrites the previous one.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct CheckpointConfig {
+    pub path: String,
+    /// Write a checkpoint every this many epochs
+    pub every_epochs: Option<usize>,
+    /// Write a checkpoint once this many minutes have passed since the last one
+    pub every_minutes: Option<f32>,
+}
+
+impl CheckpointConfig {
+    /// Whether a checkpoint is due after the given number of completed epochs
+    pub(crate) fn is_due(&self, epochs: usize, last_checkpoint: Instant) -> bool {
+        let epochs_due = self
+            .every_epochs
+            .map_or(false, |every| every > 0 && epochs % every == 0);
+        let minutes_due = self.every_minutes.map_or(false, |every| {
+            last_checkpoint.elapsed().as_secs_f32() / 60.0 >= every
+        });
+        epochs_due || minutes_due
+    }
+}
+
+/// Everything about a training run that isn't the network itself or its config
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct TrainState {
+    /// The number of epochs that have been completed
+    pub epoch: usize,
+    pub optimizer_state: OptimizerState,
+    /// Shuffles the data and applies dropout, saved so a resumed run draws the same numbers
+    pub rng: Pcg64,
+    /// The lowest mse of any epoch so far
+    pub best_mse: f32,
+    /// The highest accuracy percent of any epoch so far
+    pub best_accuracy: f32,
+    /// The number of epochs since the mse last improved, used for early stopping
+    pub epochs_without_improvement: usize,
+}
+
+impl TrainState {
+    /// The state at the start of a training run
+    pub fn new(seed: Option<u64>) -> TrainState {
+        let rng = match seed {
+            Some(seed) => Pcg64::seed_from_u64(seed),
+            None => Pcg64::from_entropy(),
+        };
+
+        TrainState {
+            epoch: 0,
+            optimizer_state: OptimizerState::new(),
+            rng,
+            best_mse: f32::INFINITY,
+            best_accuracy: 0.0,
+            epochs_without_improvement: 0,
+        }
+    }
+}
+
+/// A snapshot of an interrupted training run, which can be resumed exactly where it stopped
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Checkpoint<N> {
+    pub network: N,
+    /// The categories or labels the network was being trained on, empty if it has none
+    pub categories: Box<[String]>,
+    pub config: TrainConfig,
+    pub state: TrainState,
+}
+
+impl<N: Serialize + DeserializeOwned> Checkpoint<N> {
+    /// Serializes the checkpoint to the given path.
+    /// The checkpoint is written to a temporary file first, so a crash while writing never corrupts the previous one.
+    ///
+    /// ## Err
+    /// ### SaveModelFailed
+    /// The checkpoint couldn't be serialized or the file couldn't be written
+    pub fn write(&self, path: &str) -> Result<(), DarjeelingError> {
+        let bin = bincode::serialize(self)
+            .map_err(|err| DarjeelingError::SaveModelFailed(err.to_string()))?;
+
+        let temp_path = format!("{}.tmp", path);
+        fs::write(&temp_path, bin)
+            .and_then(|_| fs::rename(&temp_path, path))
+            .map_err(|err| DarjeelingError::SaveModelFailed(err.to_string()))
+    }
+
+    /// Reads a checkpoint written during training
+    ///
+    /// ## Err
+    /// ### LoadModelFailed
+    /// The file cannnot be read, or doesn't contain a valid checkpoint
+    pub fn read(path: &str) -> Result<Checkpoint<N>, DarjeelingError> {
+        let bin =
+            fs::read(path).map_err(|err| DarjeelingError::LoadModelFailed(err.to_string()))?;
+
+        bincode::deserialize(&bin).map_err(|err| DarjeelingError::LoadModelFailed(err.to_string()))
+    }
+}
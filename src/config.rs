@@ -0,0 +1,302 @@
+use crate::{
+    checkpoint::CheckpointConfig, error::DarjeelingError, optimizer::Optimizer, series::Series,
+};
+use serde::{Deserialize, Serialize};
+use std::{fs, path::Path};
+
+/// How the learning rate changes as training goes on
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Default)]
+pub enum Schedule {
+    #[default]
+    Constant,
+    /// Multiplies the learning rate by `factor` every `every` epochs
+    Step { every: usize, factor: f32 },
+    /// Multiplies the learning rate by `decay` every epoch
+    Exponential { decay: f32 },
+}
+
+impl Schedule {
+    /// The learning rate to use during the given (zero based) epoch
+    pub fn learning_rate(&self, learning_rate: f32, epoch: usize) -> f32 {
+        match *self {
+            Schedule::Constant => learning_rate,
+            Schedule::Step { every, factor } => {
+                learning_rate * factor.powi((epoch / every.max(1)) as i32)
+            }
+            Schedule::Exponential { decay } => learning_rate * decay.powi(epoch as i32),
+        }
+    }
+}
+
+/// What regression and autoencoder training minimize, the difference between each output and its target
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Default)]
+pub enum Loss {
+    /// The mean squared error, which punishes large errors the most
+    #[default]
+    Mse,
+    /// The mean absolute error, which is less swayed by outliers
+    Mae,
+    /// Squared for errors up to `delta` and absolute beyond it
+    Huber { delta: f32 },
+}
+
+impl Loss {
+    /// The loss of a single output
+    pub fn loss(&self, output: f32, target: f32) -> f32 {
+        let error = output - target;
+        match *self {
+            Loss::Mse => error.powi(2),
+            Loss::Mae => error.abs(),
+            Loss::Huber { delta } if error.abs() <= delta => 0.5 * error.powi(2),
+            Loss::Huber { delta } => delta * (error.abs() - 0.5 * delta),
+        }
+    }
+
+    /// The derivative of the loss with respect to the output.
+    /// Like categorization training, the squared error's factor of two is left to the learning rate.
+    pub fn derivative(&self, output: f32, target: f32) -> f32 {
+        let error = output - target;
+        match *self {
+            Loss::Mse => error,
+            Loss::Mae => error.signum(),
+            Loss::Huber { delta } => error.clamp(-delta, delta),
+        }
+    }
+}
+
+/// Stops training once the training loss stops improving
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+pub struct EarlyStopping {
+    /// The number of epochs without improvement before training stops
+    pub patience: usize,
+    /// The smallest decrease in loss that counts as an improvement
+    pub min_delta: f32,
+}
+
+/// A field of a [`TrainConfig`] that not every kind of training uses
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) enum ConfigField {
+    EarlyStopping,
+    TargetAccuracy,
+    Seed,
+    SavePath,
+    Checkpoint,
+    ClassWeights,
+    Loss,
+}
+
+impl ConfigField {
+    fn name(&self) -> &'static str {
+        match self {
+            ConfigField::EarlyStopping => "early_stopping",
+            ConfigField::TargetAccuracy => "target_accuracy",
+            ConfigField::Seed => "seed",
+            ConfigField::SavePath => "save_path",
+            ConfigField::Checkpoint => "checkpoint",
+            ConfigField::ClassWeights => "class_weights",
+            ConfigField::Loss => "loss",
+        }
+    }
+}
+
+/// How much each category's inputs count towards the loss of categorization training
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub enum ClassWeights {
+    /// Weighs every category inversely to how often it's an answer, so they all count as much as each other in total
+    Balanced,
+    /// The weight of each listed category, the rest have a weight of one
+    Custom(Vec<(String, f32)>),
+}
+
+impl ClassWeights {
+    /// The weight of every category, in order
+    ///
+    /// ## Err
+    /// ### CategoryDoesNotExist
+    /// A custom weight is for a category that isn't one of the categories
+    pub fn weights(
+        &self,
+        categories: &[String],
+        data: &[Series],
+    ) -> Result<Box<[f32]>, DarjeelingError> {
+        match self {
+            ClassWeights::Balanced => {
+                let mut counts = vec![0; categories.len()];
+                data.iter().for_each(|series| {
+                    if let Some(i) = categories
+                        .iter()
+                        .position(|category| *category == series.answer)
+                    {
+                        counts[i] += 1;
+                    }
+                });
+                let total: usize = counts.iter().sum();
+                Ok(counts
+                    .iter()
+                    .map(|count| match count {
+                        0 => 1.0,
+                        count => total as f32 / (categories.len() * count) as f32,
+                    })
+                    .collect())
+            }
+            ClassWeights::Custom(weights) => {
+                let mut all = vec![1.0; categories.len()];
+                for (category, weight) in weights.iter() {
+                    let i = categories
+                        .iter()
+                        .position(|existing| existing == category)
+                        .ok_or_else(|| DarjeelingError::CategoryDoesNotExist(category.clone()))?;
+                    all[i] = *weight;
+                }
+                Ok(all.into_boxed_slice())
+            }
+        }
+    }
+}
+
+/// The hyperparameters of a training run.
+///
+/// Every field has a default, so config files only need to contain the fields that differ.
+///
+/// ## Examples
+/// ``` rust
+/// use darjeeling::{config::TrainConfig, optimizer::Optimizer};
+///
+/// let config = TrainConfig {
+///     learning_rate: 0.01,
+///     epochs: 50,
+///     optimizer: Optimizer::adam(),
+///     seed: Some(42),
+///     ..Default::default()
+/// };
+///
+/// let from_toml = TrainConfig::from_toml("learning_rate = 0.01\nepochs = 50\nseed = 42\n[optimizer.Adam]\nbeta1 = 0.9\nbeta2 = 0.999\nepsilon = 1e-8").unwrap();
+/// assert_eq!(config, from_toml);
+/// ```
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(default)]
+pub struct TrainConfig {
+    /// The modifier that is applied to link weights as they're adjusted
+    pub learning_rate: f32,
+    /// The maximum number of epochs training will run for
+    pub epochs: usize,
+    /// The number of inputs whose gradients are averaged before the weights are adjusted
+    pub batch_size: usize,
+    pub optimizer: Optimizer,
+    pub schedule: Schedule,
+    pub early_stopping: Option<EarlyStopping>,
+    /// The training accuracy percent at which training stops, checked at the end of each epoch.
+    /// Generative and variational autoencoder training have no accuracy, so they return an error if it's set
+    pub target_accuracy: Option<f32>,
+    /// Seeds the shuffling and dropout of training, so a run can be repeated exactly
+    pub seed: Option<u64>,
+    /// Where the model is written once training finishes, nothing is written if this is None
+    pub save_path: Option<String>,
+    /// Periodically saves the training run so it can be resumed.
+    /// Categorization, regression, autoencoder and multi-label training write checkpoints, the rest return an error if it's set
+    pub checkpoint: Option<CheckpointConfig>,
+    /// Scales each input's error by the weight of its answer.
+    /// Only categorization training uses them, the rest return an error if they're set
+    pub class_weights: Option<ClassWeights>,
+    /// What regression and autoencoder training minimize, including variational autoencoders.
+    /// The rest return an error if it isn't the default
+    pub loss: Loss,
+}
+
+impl Default for TrainConfig {
+    fn default() -> TrainConfig {
+        TrainConfig {
+            learning_rate: 0.5,
+            epochs: 100,
+            batch_size: 1,
+            optimizer: Optimizer::Sgd,
+            schedule: Schedule::Constant,
+            early_stopping: None,
+            target_accuracy: None,
+            seed: None,
+            save_path: None,
+            checkpoint: None,
+            class_weights: None,
+            loss: Loss::Mse,
+        }
+    }
+}
+
+impl TrainConfig {
+    pub fn from_json(json: &str) -> Result<TrainConfig, DarjeelingError> {
+        serde_json::from_str(json).map_err(|err| DarjeelingError::ConfigReadFailed(err.to_string()))
+    }
+
+    pub fn from_toml(toml: &str) -> Result<TrainConfig, DarjeelingError> {
+        toml::from_str(toml).map_err(|err| DarjeelingError::ConfigReadFailed(err.to_string()))
+    }
+
+    /// Reads a config from a `.json` or `.toml` file, the format is chosen by the file's extension
+    ///
+    /// ## Err
+    /// ### ConfigReadFailed
+    /// The file couldn't be read, has another extension, or doesn't contain a valid config
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<TrainConfig, DarjeelingError> {
+        let path = path.as_ref();
+        let contents = fs::read_to_string(path)
+            .map_err(|err| DarjeelingError::ConfigReadFailed(err.to_string()))?;
+
+        match path.extension().and_then(|extension| extension.to_str()) {
+            Some("json") => TrainConfig::from_json(&contents),
+            Some("toml") => TrainConfig::from_toml(&contents),
+            _ => Err(DarjeelingError::ConfigReadFailed(format!(
+                "{:?} isn't a .json or .toml file",
+                path
+            ))),
+        }
+    }
+
+    pub fn to_json(&self) -> Result<String, DarjeelingError> {
+        serde_json::to_string_pretty(self)
+            .map_err(|err| DarjeelingError::ConfigReadFailed(err.to_string()))
+    }
+
+    pub fn to_toml(&self) -> Result<String, DarjeelingError> {
+        toml::to_string(self).map_err(|err| DarjeelingError::ConfigReadFailed(err.to_string()))
+    }
+
+    /// Checks that none of the given fields are set, because the training would ignore them.
+    /// The loss is set when it isn't the default.
+    ///
+    /// ## Params
+    /// - Training: What's being trained, named in the error
+    /// - Unused: The fields the training doesn't use
+    ///
+    /// ## Err
+    /// ### UnsupportedConfig
+    /// One of the fields is set
+    pub(crate) fn check_unused(
+        &self,
+        training: &str,
+        unused: &[ConfigField],
+    ) -> Result<(), DarjeelingError> {
+        let set = |field: &ConfigField| match field {
+            ConfigField::EarlyStopping => self.early_stopping.is_some(),
+            ConfigField::TargetAccuracy => self.target_accuracy.is_some(),
+            ConfigField::Seed => self.seed.is_some(),
+            ConfigField::SavePath => self.save_path.is_some(),
+            ConfigField::Checkpoint => self.checkpoint.is_some(),
+            ConfigField::ClassWeights => self.class_weights.is_some(),
+            ConfigField::Loss => self.loss != Loss::default(),
+        };
+
+        match unused.iter().find(|field| set(field)) {
+            Some(field) => Err(DarjeelingError::UnsupportedConfig(format!(
+                "{} doesn't use the config's {}, leave it unset",
+                training,
+                field.name()
+            ))),
+            None => Ok(()),
+        }
+    }
+
+    /// The learning rate to use during the given (zero based) epoch, according to the schedule
+    pub fn learning_rate_at(&self, epoch: usize) -> f32 {
+        self.schedule.learning_rate(self.learning_rate, epoch)
+    }
+}
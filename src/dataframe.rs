@@ -1,329 +1,681 @@
-// Deprecated
-
-use std::{fmt::Debug, collections::hash_map::DefaultHasher, vec};
-use serde::Serialize;
-use crate::{types::Types, input::Input, error::DarjeelingError};
-use std::hash::{Hash, Hasher};
-
-static INITIAL_SIZE: usize = 10;
-
-// TODO: Fix hash overflow issue
-#[derive(Debug, Serialize)]
-pub struct DataFrame<'a> {
-    frame: Vec<Vec<Types>>,
-    row_labels: Vec<&'a str>,
-    col_labels: Vec<&'a str>,
-    inner_capacity: usize
+use crate::{dataset::label_string, error::DarjeelingError, series::Series};
+use serde::{Deserialize, Serialize};
+use std::{collections::HashMap, fmt};
+
+/// A single cell of a dataframe
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub enum Value {
+    Float(f32),
+    Integer(i64),
+    Boolean(bool),
+    Categorical(String),
 }
 
-// Managing same capacity is difficult
-impl<'a> DataFrame<'a> {
-    
-    /// Creates an empty dataframe with inner capacity of 10
-    pub fn new() -> DataFrame<'a> {
-        DataFrame { frame: vec![], row_labels: vec![], col_labels: vec![], inner_capacity: 10 }
+impl Value {
+    /// The value as a network input, None for categories
+    pub fn as_f32(&self) -> Option<f32> {
+        match self {
+            Value::Float(float) => Some(*float),
+            Value::Integer(int) => Some(*int as f32),
+            Value::Boolean(boolean) => Some(if *boolean { 1.0 } else { 0.0 }),
+            Value::Categorical(_) => None,
+        }
     }
 
-    /// Creates a new Dataframe
-    pub fn from_2d_array(mut frame: Vec<Vec<Types>>, row_labels: Vec<&'a str>, col_labels: Vec<&'a str>) -> DataFrame<'a> {
-        let mut inner = 0;
-        for i in 0..frame.len() {
-            let len = frame[i].len();
-            if len > inner { inner = len }
+    /// Whether the value is missing, a NaN float or an empty category
+    pub fn is_missing(&self) -> bool {
+        match self {
+            Value::Float(float) => float.is_nan(),
+            Value::Categorical(category) => category.is_empty(),
+            Value::Integer(_) | Value::Boolean(_) => false,
         }
-        for i in 0..frame.len() {
-            let old = frame[i].capacity();
-            frame[i].reserve(inner * 4 - old);
+    }
+
+    fn type_name(&self) -> &'static str {
+        match self {
+            Value::Float(_) => "float",
+            Value::Integer(_) => "integer",
+            Value::Boolean(_) => "boolean",
+            Value::Categorical(_) => "categorical",
         }
-        let outer = frame.len();
-        println!("unordered frame: {:?}", frame);
-        let mut final_frame = DataFrame { frame, row_labels, col_labels, inner_capacity: inner };
-        final_frame.rehash_dataframe(outer * 2);
-        final_frame
-    }
-
-    /// Gets the value at a hash
-    /// # Err
-    /// If the column or row doesn't exist
-    pub fn value_at_index(&self, row: usize, col: usize) -> Result<&Types, DarjeelingError> {
-        if col < self.frame.len() {
-            if row < self.frame[col].len() { return Ok(&&self.frame[col as usize][row as usize]); }
-            return Err(DarjeelingError::RowDoesNotExist(format!("{}", row)));
+    }
+}
+
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Value::Float(float) => write!(f, "{}", float),
+            Value::Integer(int) => write!(f, "{}", int),
+            Value::Boolean(boolean) => write!(f, "{}", boolean),
+            Value::Categorical(category) => write!(f, "{}", category),
         }
-        Err(DarjeelingError::ColumnDoesNotExist(format!("{}", col)))
-    }
-    
-    /// Gets the index at labels
-    /// # Panics 
-    /// If hashing overflows or if labels don't exist
-    pub fn index_at_labels(&self, row: &str, col: &str) -> (usize, usize){
-        (self.label_index_col(row), self.label_index_row(col))
-    }
-
-    /// Gets the value in the dataframe at the coordinates of the labels
-    pub fn value_at_labels(&self, row: &str, col: &str) ->  Result<&Types, DarjeelingError> {
-        let coords: (usize, usize) = self.index_at_labels(row, col);
-        self.value_at_index(coords.0 as usize, coords.1 as usize)
-    }
-
-    /// Adds a row to a dataframe
-    /// # Panics 
-    /// If reallocation fails
-    pub fn add_row(&mut self, label: &'a str, content: Vec<Types>) -> Result<(), DarjeelingError> {
-        // Checks to make sure the dataframe is still valid
-        //assert_eq!(self.row_labels.len(), self.frame.len());
-        if self.row_labels.contains(&label) { return Err(DarjeelingError::RowAlreadyExists(format!("{}", label))); }
-        if content.len() > self.inner_capacity { self.rehash_dataframe(content.len() * 2) }
-        self.row_labels.push(label);
-        let row_index = self.label_index_row(label);
-        self.frame[row_index] = content;
-        Ok(())
     }
+}
+
+/// A column of a dataframe, every value in a column has the same type
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub enum Column {
+    Float(Vec<f32>),
+    Integer(Vec<i64>),
+    Boolean(Vec<bool>),
+    Categorical(Vec<String>),
+}
 
-    /// Adds a column to a dataframe
-    /// # Panics 
-    /// If reallocation fails
-    pub fn add_col(&mut self, label: &'a str, content: Vec<Types>) -> Result<(), DarjeelingError> {
-        // Checks to make sure the dataframe is still valid
-        // This was searching for an index of a label to delete instead of
-        // adding the index to the end
-        println!("inner capacity: {}\nframecapacity: {}", self.inner_capacity, self.frame[0].capacity());
-        if self.frame[0].capacity() != self.inner_capacity {
-            self.fix_inner_capacity();
+impl Column {
+    pub fn len(&self) -> usize {
+        match self {
+            Column::Float(values) => values.len(),
+            Column::Integer(values) => values.len(),
+            Column::Boolean(values) => values.len(),
+            Column::Categorical(values) => values.len(),
         }
-        if self.col_labels.contains(&label) { return Err(DarjeelingError::RowAlreadyExists(format!("{}", label))); }
-        if content.len() > self.inner_capacity { self.rehash_dataframe(content.len() * 2); }
+    }
 
-        self.col_labels.push(label);
-        let col_index = self.label_index_col(label);
-        for i in 0..self.frame.len() { self.frame[i][col_index] = content[i].clone(); }
-        assert_eq!(self.frame[0].capacity(), self.inner_capacity);
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn get(&self, row: usize) -> Option<Value> {
+        match self {
+            Column::Float(values) => values.get(row).map(|value| Value::Float(*value)),
+            Column::Integer(values) => values.get(row).map(|value| Value::Integer(*value)),
+            Column::Boolean(values) => values.get(row).map(|value| Value::Boolean(*value)),
+            Column::Categorical(values) => values
+                .get(row)
+                .map(|value| Value::Categorical(value.clone())),
+        }
+    }
+
+    /// Infers the narrowest type that every value parses as.
+    /// Integers are tried first, then floats, then booleans, and anything else is categorical.
+    ///
+    /// Blank values are missing. They're NaN in a numeric column, which makes integer and
+    /// boolean columns floats (with booleans as one or zero), and empty in a categorical one.
+    pub fn infer(values: &[String]) -> Column {
+        let values: Vec<&str> = values.iter().map(|value| value.trim()).collect();
+        let present: Vec<&str> = values
+            .iter()
+            .copied()
+            .filter(|value| !value.is_empty())
+            .collect();
+        let complete = present.len() == values.len();
+        let numeric = |parse: &dyn Fn(&str) -> Option<f32>| -> Option<Vec<f32>> {
+            values
+                .iter()
+                .map(|value| {
+                    if value.is_empty() {
+                        Some(f32::NAN)
+                    } else {
+                        parse(value)
+                    }
+                })
+                .collect()
+        };
+
+        if !present.is_empty() {
+            if let Ok(ints) = present.iter().map(|value| value.parse::<i64>()).collect() {
+                if complete {
+                    return Column::Integer(ints);
+                }
+            }
+            if let Some(floats) = numeric(&|value| value.parse().ok()) {
+                return Column::Float(floats);
+            }
+            if let Some(booleans) = present.iter().map(|value| parse_bool(value)).collect() {
+                if complete {
+                    return Column::Boolean(booleans);
+                }
+                if let Some(floats) = numeric(&|value| parse_bool(value).map(f32::from)) {
+                    return Column::Float(floats);
+                }
+            }
+        }
+        Column::Categorical(values.iter().map(|value| value.to_string()).collect())
+    }
+
+    /// The number of missing values, NaN floats and empty categories
+    pub fn missing(&self) -> usize {
+        match self {
+            Column::Float(values) => values.iter().filter(|value| value.is_nan()).count(),
+            Column::Categorical(values) => values.iter().filter(|value| value.is_empty()).count(),
+            Column::Integer(_) | Column::Boolean(_) => 0,
+        }
+    }
+
+    fn type_name(&self) -> &'static str {
+        match self {
+            Column::Float(_) => "float",
+            Column::Integer(_) => "integer",
+            Column::Boolean(_) => "boolean",
+            Column::Categorical(_) => "categorical",
+        }
+    }
+
+    /// Appends a value, which must have the column's type
+    fn push(&mut self, value: Value) -> Result<(), Value> {
+        match (self, value) {
+            (Column::Float(values), Value::Float(value)) => values.push(value),
+            (Column::Integer(values), Value::Integer(value)) => values.push(value),
+            (Column::Boolean(values), Value::Boolean(value)) => values.push(value),
+            (Column::Categorical(values), Value::Categorical(value)) => values.push(value),
+            (_, value) => return Err(value),
+        }
         Ok(())
     }
 
-    /// Deletes a row of a dataframe give a label
-    /// # Panics 
-    /// If the index does not exist
-    pub fn delete_row(&mut self, label: &'a str) {
-        let index = self.label_index_row(label);
-        search_remove(&mut self.row_labels, label);
-        self.frame.remove(index);
+    /// A column of the values at the given rows, in their order
+    fn take(&self, rows: &[usize]) -> Column {
+        match self {
+            Column::Float(values) => Column::Float(rows.iter().map(|row| values[*row]).collect()),
+            Column::Integer(values) => {
+                Column::Integer(rows.iter().map(|row| values[*row]).collect())
+            }
+            Column::Boolean(values) => {
+                Column::Boolean(rows.iter().map(|row| values[*row]).collect())
+            }
+            Column::Categorical(values) => {
+                Column::Categorical(rows.iter().map(|row| values[*row].clone()).collect())
+            }
+        }
+    }
+}
+
+fn parse_bool(value: &str) -> Option<bool> {
+    match value.to_lowercase().as_str() {
+        "true" => Some(true),
+        "false" => Some(false),
+        _ => None,
+    }
+}
+
+/// A row of a dataframe, passed to [`DataFrame::filter`]
+pub struct Row<'a> {
+    frame: &'a DataFrame,
+    index: usize,
+}
+
+impl<'a> Row<'a> {
+    pub fn label(&self) -> &'a str {
+        &self.frame.row_labels[self.index]
+    }
+
+    /// The row's value in the given column, None if the column doesn't exist
+    pub fn get(&self, column: &str) -> Option<Value> {
+        self.frame
+            .col_index
+            .get(column)
+            .and_then(|col| self.frame.columns[*col].get(self.index))
     }
+}
+
+/// A table of typed columns, with a unique label for every row and column
+///
+/// ## Examples
+/// ``` rust
+/// use darjeeling::dataframe::{Column, DataFrame, Value};
+///
+/// let mut frame = DataFrame::from_columns(vec![
+///     ("x", Column::Float(vec![0.0, 0.0, 1.0, 1.0])),
+///     ("y", Column::Integer(vec![0, 1, 0, 1])),
+///     ("xor", Column::Boolean(vec![false, true, true, false])),
+/// ])
+/// .unwrap();
+/// frame.append_row("4", vec![Value::Float(0.5), Value::Integer(1), Value::Boolean(true)]).unwrap();
+///
+/// let ones = frame.filter(|row| row.get("y") == Some(Value::Integer(1)));
+/// assert_eq!(ones.rows_len(), 3);
+///
+/// let data = frame.to_series("xor").unwrap();
+/// assert_eq!(data[1].data.as_ref(), &[0.0, 1.0]);
+/// assert_eq!(data[1].answer, "true");
+/// ```
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Default)]
+pub struct DataFrame {
+    row_labels: Vec<String>,
+    col_labels: Vec<String>,
+    columns: Vec<Column>,
+    row_index: HashMap<String, usize>,
+    col_index: HashMap<String, usize>,
+}
 
-    /// Deletes a column of a dataframe given a label
-    /// # Panics 
-    /// If the index does not exist
-    pub fn delete_col(&mut self, label: &'a str) {
-        let index = self.label_index_col(label);
-        search_remove(&mut self.row_labels, label);
-        for i in 0..self.frame.len() {
-            self.frame[i].remove(index);
+impl DataFrame {
+    /// An empty dataframe, with no rows or columns
+    pub fn new() -> DataFrame {
+        DataFrame::default()
+    }
+
+    /// Creates a dataframe from labelled columns, the rows are labelled by their position
+    ///
+    /// ## Err
+    /// ### ColumnAlreadyExists
+    /// Two columns have the same label
+    /// ### ColumnMismatch
+    /// The columns have different lengths
+    pub fn from_columns<S: ToString>(
+        columns: Vec<(S, Column)>,
+    ) -> Result<DataFrame, DarjeelingError> {
+        let mut frame = DataFrame::new();
+        let rows = columns.first().map_or(0, |(_, column)| column.len());
+        frame.row_labels = (0..rows).map(|row| row.to_string()).collect();
+        frame.reindex();
+
+        for (label, column) in columns {
+            frame.add_column(label, column)?;
         }
+        Ok(frame)
     }
 
-    pub fn get_cols_len(&self) -> usize {
-        self.col_labels.len()
+    /// Replaces the labels of the rows
+    ///
+    /// ## Err
+    /// ### RowAlreadyExists
+    /// Two rows have the same label
+    /// ### ColumnMismatch
+    /// There aren't as many labels as rows
+    pub fn with_row_labels<S: ToString>(
+        mut self,
+        labels: Vec<S>,
+    ) -> Result<DataFrame, DarjeelingError> {
+        if labels.len() != self.rows_len() {
+            return Err(DarjeelingError::ColumnMismatch(format!(
+                "there are {} rows, but {} labels",
+                self.rows_len(),
+                labels.len()
+            )));
+        }
+
+        self.row_labels = labels.iter().map(|label| label.to_string()).collect();
+        self.reindex();
+        if self.row_index.len() != self.row_labels.len() {
+            let duplicate = self
+                .row_labels
+                .iter()
+                .enumerate()
+                .find(|(i, label)| self.row_index[*label] != *i)
+                .map(|(_, label)| label.clone())
+                .unwrap_or_default();
+            return Err(DarjeelingError::RowAlreadyExists(duplicate));
+        }
+        Ok(self)
     }
 
-    pub fn get_rows_len(&self) -> usize {
+    pub fn rows_len(&self) -> usize {
         self.row_labels.len()
     }
 
-    pub fn display(&mut self) {
-        for _i in 0..self.row_labels[0].len() + 2 {
-            print!(" ");
+    pub fn cols_len(&self) -> usize {
+        self.col_labels.len()
+    }
+
+    pub fn row_labels(&self) -> &[String] {
+        &self.row_labels
+    }
+
+    pub fn col_labels(&self) -> &[String] {
+        &self.col_labels
+    }
+
+    /// ## Err
+    /// ### ColumnDoesNotExist
+    pub fn column(&self, label: &str) -> Result<&Column, DarjeelingError> {
+        Ok(&self.columns[self.col_position(label)?])
+    }
+
+    /// The value at the given row and column labels
+    ///
+    /// ## Err
+    /// ### PointDoesNotExist
+    /// Either label doesn't exist
+    pub fn value(&self, row: &str, col: &str) -> Result<Value, DarjeelingError> {
+        match (self.row_index.get(row), self.col_index.get(col)) {
+            (Some(row), Some(col)) => self.columns[*col]
+                .get(*row)
+                .ok_or_else(|| DarjeelingError::UnknownError("Dataframe is ragged".to_string())),
+            _ => Err(DarjeelingError::PointDoesNotExist((
+                row.to_string(),
+                col.to_string(),
+            ))),
         }
-        for i in &self.col_labels {
-            print!("{:?} ", i)
+    }
+
+    /// Adds a column after the existing ones
+    ///
+    /// ## Err
+    /// ### ColumnAlreadyExists
+    /// ### ColumnMismatch
+    /// The column doesn't have a value for every row
+    pub fn add_column<S: ToString>(
+        &mut self,
+        label: S,
+        column: Column,
+    ) -> Result<(), DarjeelingError> {
+        let label = label.to_string();
+        if self.col_index.contains_key(&label) {
+            return Err(DarjeelingError::ColumnAlreadyExists(label));
         }
-        print!("\n");
-        for row in &self.row_labels {
-            // self.fix_inner_capacity();
-            let display_row_index = self.label_index_row(row);
-            print!("row name: {}, index: {}", row, display_row_index);
-            let longest = find_longest(&self.row_labels);
-            let shortest = find_shortest(&self.row_labels);
-            if longest != row.len() {
-                for _l in 0..longest - self.row_labels[0].len() {
-                    print!(" ");
-                }
-            }
-            for col in &self.col_labels {
-                let display_col_index = self.label_index_col(col);
-                self.frame[display_row_index as usize][display_col_index as usize].display();
-                if longest != col.len() && shortest != longest{
-                    for _k in 0..col.len() + 2 {
-                        print!(" ");
-                    }
-                } else {
-                    for _k in 0..col.len() {
-                        print!(" ");
-                    }
-                }
-            }
-            print!("\n");
+        if column.len() != self.rows_len() {
+            return Err(DarjeelingError::ColumnMismatch(format!(
+                "column {:?} has {} values, but there are {} rows",
+                label,
+                column.len(),
+                self.rows_len()
+            )));
         }
+
+        self.col_index.insert(label.clone(), self.col_labels.len());
+        self.col_labels.push(label);
+        self.columns.push(column);
+        Ok(())
     }
-    // Refactor to specialize for generative networks
-    pub fn frame_to_categorization_inputs(&self, answers: Vec<i32>) -> Result<Vec<Input>, DarjeelingError> {
-        let mut inputs: Vec<Input> = vec![];
-        for i in 0..self.row_labels.len() {
-            let mut input: Input = Input::new(vec![], Some(self.frame[i][answers[i] as usize].clone()));
-            for j in 0..self.col_labels.len() - 1 {
-                let wrapped = self.value_at_index(i, j);
-                match wrapped {
-                    Ok(val) => {
-                        match val {
-                            Types::Boolean(boolean) => {
-                                if *boolean {
-                                    input.inputs.push(1.0);
-                                } else {
-                                    input.inputs.push(0.0);
-                                }
-                            },
-                            Types::Float(float) => {
-                                input.inputs.push(*float)
-                            },
-                            Types::Integer(int) => {
-                                input.inputs.push(*int as f32);
-                            },
-                            Types::String(str) => {
-                                let ascii = ascii_converter::string_to_decimals(str).unwrap();
-                                let mut string = String::new();
-                                for i in ascii {
-                                    string.push_str(&i.to_string());
-                                }
-                                input.inputs.push(string.parse().unwrap());
-                            }
-                        }
-                    },
-                    Err(err) => return Err(err)
-                }
-                
-            }
-            inputs.push(input);
+
+    /// Adds a row after the existing ones, with a value for every column in order
+    ///
+    /// ## Err
+    /// ### RowAlreadyExists
+    /// ### ColumnMismatch
+    /// There isn't a value for every column, or a value's type doesn't match its column
+    pub fn append_row<S: ToString>(
+        &mut self,
+        label: S,
+        values: Vec<Value>,
+    ) -> Result<(), DarjeelingError> {
+        let label = label.to_string();
+        if self.row_index.contains_key(&label) {
+            return Err(DarjeelingError::RowAlreadyExists(label));
         }
-        Ok(inputs)
-    }
-
-    pub fn frame_to_generation_inputs(&self) -> Result<Vec<Input>, DarjeelingError> {
-        let mut inputs: Vec<Input> = vec![];
-        for i in 0..self.row_labels.len() {
-            let mut input = Input::new(vec![], None);
-            for j in 0..self.col_labels.len() - 1 {
-                let wrapped = self.value_at_index(i, j);
-                match wrapped {
-                    Ok(val) => {
-                        match val {
-                            Types::Boolean(boolean) => {
-                                if *boolean {
-                                    input.inputs.push(1.0);
-                                } else {
-                                    input.inputs.push(0.0);
-                                }
-                            },
-                            Types::Float(float) => {
-                                input.inputs.push(*float)
-                            },
-                            Types::Integer(int) => {
-                                input.inputs.push(*int as f32);
-                            },
-                            Types::String(str) => {
-                                let ascii = ascii_converter::string_to_decimals(str).unwrap();
-                                let mut string = String::new();
-                                for i in ascii {
-                                    string.push_str(&i.to_string());
-                                }
-                                input.inputs.push(string.parse().unwrap());
-                            }
-                        }
-                    },
-                    Err(err) => return Err(err)
-                }
+        if values.len() != self.cols_len() {
+            return Err(DarjeelingError::ColumnMismatch(format!(
+                "there are {} columns, but {} values",
+                self.cols_len(),
+                values.len()
+            )));
+        }
+        for ((column, value), col_label) in self
+            .columns
+            .iter()
+            .zip(values.iter())
+            .zip(self.col_labels.iter())
+        {
+            if column.type_name() != value.type_name() {
+                return Err(DarjeelingError::ColumnMismatch(format!(
+                    "column {:?} is {}, but the value is {}",
+                    col_label,
+                    column.type_name(),
+                    value.type_name()
+                )));
             }
-            inputs.push(input);
         }
-        Ok(inputs)
+
+        for (column, value) in self.columns.iter_mut().zip(values) {
+            let _ = column.push(value);
+        }
+        self.row_index.insert(label.clone(), self.row_labels.len());
+        self.row_labels.push(label);
+        Ok(())
     }
 
-    fn label_index_row(&self, label: &str) -> usize {
-        (hash_str(label) % self.frame.capacity() as u64) as usize
+    /// Adds every row of another dataframe after the existing ones
+    ///
+    /// ## Err
+    /// ### RowAlreadyExists
+    /// A row label is in both dataframes
+    /// ### ColumnMismatch
+    /// The dataframes don't have the same columns, with the same types, in the same order
+    pub fn append(&mut self, other: &DataFrame) -> Result<(), DarjeelingError> {
+        if self.col_labels != other.col_labels {
+            return Err(DarjeelingError::ColumnMismatch(format!(
+                "the columns {:?} don't match {:?}",
+                other.col_labels, self.col_labels
+            )));
+        }
+        if let Some(label) = other
+            .row_labels
+            .iter()
+            .find(|label| self.row_index.contains_key(*label))
+        {
+            return Err(DarjeelingError::RowAlreadyExists(label.clone()));
+        }
+
+        let mut frame = self.clone();
+        for row in 0..other.rows_len() {
+            let values = other
+                .columns
+                .iter()
+                .map(|column| column.get(row).expect("Dataframe is ragged"))
+                .collect();
+            frame.append_row(&other.row_labels[row], values)?;
+        }
+        *self = frame;
+        Ok(())
+    }
+
+    /// A dataframe of only the given columns, in the given order
+    ///
+    /// ## Err
+    /// ### ColumnDoesNotExist
+    pub fn select(&self, columns: &[&str]) -> Result<DataFrame, DarjeelingError> {
+        let mut frame = DataFrame {
+            row_labels: self.row_labels.clone(),
+            row_index: self.row_index.clone(),
+            ..Default::default()
+        };
+        for label in columns.iter() {
+            frame.add_column(label, self.column(label)?.clone())?;
+        }
+        Ok(frame)
     }
 
-    fn label_index_col(&self, label: &str) -> usize {
-        (hash_str(label) % self.inner_capacity as u64) as usize
+    /// A dataframe of only the rows the predicate is true for
+    pub fn filter(&self, predicate: impl Fn(&Row) -> bool) -> DataFrame {
+        let rows: Vec<usize> = (0..self.rows_len())
+            .filter(|index| {
+                predicate(&Row {
+                    frame: self,
+                    index: *index,
+                })
+            })
+            .collect();
+        self.take_rows(&rows)
     }
 
-    fn rehash_dataframe(&mut self, new_capacity: usize) {
-        // This starts with self.inner_capacity being old
-        let mut new_frame: Vec<Vec<Types>> = Vec::with_capacity(self.frame.len() * 2);
-        unsafe { new_frame.set_len(self.frame.len()) }
-        println!("capacity {}", new_frame.capacity());
-        println!("inner capacity: {}", self.inner_capacity);
-        self.fix_inner_capacity();
-        let old_capacity = self.inner_capacity;
-        // Rehashes row
-        println!("old_capacity: {}, new_capacity: {}", old_capacity, new_capacity);
-        for i in 0..self.row_labels.len() { 
-            new_frame[i].reserve(new_capacity - old_capacity); 
-            unsafe { new_frame[i].set_len(self.frame[i].len()) }
+    /// Removes the given columns
+    ///
+    /// ## Err
+    /// ### ColumnDoesNotExist
+    /// Nothing is removed if any of the columns don't exist
+    pub fn drop_columns(&mut self, columns: &[&str]) -> Result<(), DarjeelingError> {
+        let mut positions = columns
+            .iter()
+            .map(|label| self.col_position(label))
+            .collect::<Result<Vec<usize>, DarjeelingError>>()?;
+        positions.sort_unstable();
+        positions.dedup();
+
+        for position in positions.into_iter().rev() {
+            self.col_labels.remove(position);
+            self.columns.remove(position);
         }
-        println!("rows: {:?}", self.row_labels);
-        println!("cols: {:?}", self.col_labels);
-        println!("frame: {:?}", self.frame);
-        // Rehashes cols
-        for row in &self.row_labels {
-            for col in &self.col_labels {
-                let old_row = self.label_index_row(row);
-                let new_row = (hash_str(row) % new_capacity as u64) as usize;
-                let old_col = self.label_index_col(col);
-                let new_col = (hash_str(col) % new_capacity as u64) as usize;
-                println!("old_col: {}, new_col: {}, old_row: {}, new_row: {}", old_col, new_col, old_row, new_row);
-                new_frame[new_row][new_col] = self.frame[old_row][old_col].clone(); // i = 2 is the problem
-            }
+        self.reindex();
+        Ok(())
+    }
+
+    /// Removes the given rows
+    ///
+    /// ## Err
+    /// ### RowDoesNotExist
+    /// Nothing is removed if any of the rows don't exist
+    pub fn drop_rows(&mut self, rows: &[&str]) -> Result<(), DarjeelingError> {
+        let mut dropped = vec![false; self.rows_len()];
+        for label in rows.iter() {
+            let row = self
+                .row_index
+                .get(*label)
+                .ok_or_else(|| DarjeelingError::RowDoesNotExist(label.to_string()))?;
+            dropped[*row] = true;
         }
-        self.inner_capacity = new_capacity;
-        println!("inner capacity: {}\nnew frame capacity: {}", self.inner_capacity, self.frame[0].capacity());
-        self.frame = new_frame;
+
+        let kept: Vec<usize> = (0..self.rows_len()).filter(|row| !dropped[*row]).collect();
+        *self = self.take_rows(&kept);
+        Ok(())
+    }
+
+    /// Converts the dataframe into inputs for categorization networks.
+    /// Every other column is a feature, in order, with booleans as one or zero.
+    /// Missing values are NaN, and need imputing before training.
+    ///
+    /// ## Params
+    /// - Label: The column of answers, numbers are written without a trailing `.0`
+    ///
+    /// ## Err
+    /// ### ColumnDoesNotExist
+    /// The label column doesn't exist
+    /// ### ColumnMismatch
+    /// A feature column is categorical, and needs to be encoded as numbers first
+    pub fn to_series(&self, label: &str) -> Result<Box<[Series]>, DarjeelingError> {
+        let label = self.col_position(label)?;
+        let features: Vec<usize> = (0..self.cols_len()).filter(|col| *col != label).collect();
+        self.check_numeric(&features)?;
+
+        Ok((0..self.rows_len())
+            .map(|row| {
+                let answer = match self.columns[label].get(row).expect("Dataframe is ragged") {
+                    Value::Float(float) => label_string(float),
+                    value => value.to_string(),
+                };
+                Series::new(self.numeric_row(row, &features), answer)
+            })
+            .collect())
+    }
+
+    /// Converts the dataframe into inputs for generation networks, every column being a feature
+    ///
+    /// ## Err
+    /// ### ColumnMismatch
+    /// A column is categorical, and needs to be encoded as numbers first
+    pub fn to_rows(&self) -> Result<Box<[Box<[f32]>]>, DarjeelingError> {
+        let features: Vec<usize> = (0..self.cols_len()).collect();
+        self.check_numeric(&features)?;
+
+        Ok((0..self.rows_len())
+            .map(|row| self.numeric_row(row, &features))
+            .collect())
     }
 
-    fn fix_inner_capacity(&mut self) {
-        for i in 0..self.frame.len() {
-            self.frame[i].reserve(self.inner_capacity);
+    fn col_position(&self, label: &str) -> Result<usize, DarjeelingError> {
+        self.col_index
+            .get(label)
+            .copied()
+            .ok_or_else(|| DarjeelingError::ColumnDoesNotExist(label.to_string()))
+    }
+
+    fn check_numeric(&self, columns: &[usize]) -> Result<(), DarjeelingError> {
+        match columns
+            .iter()
+            .find(|col| matches!(self.columns[**col], Column::Categorical(_)))
+        {
+            Some(col) => Err(DarjeelingError::ColumnMismatch(format!(
+                "column {:?} is categorical, encode it as numbers first",
+                self.col_labels[*col]
+            ))),
+            None => Ok(()),
         }
     }
-}
 
-pub fn find_longest(vec: &Vec<&str>) -> usize {
-    let mut longest_len: usize = vec[0].len();
-    for i in 1..vec.len() {
-        let length = vec[i].len();
-        if length > longest_len { longest_len = length }
+    fn numeric_row(&self, row: usize, columns: &[usize]) -> Box<[f32]> {
+        columns
+            .iter()
+            .map(|col| {
+                self.columns[*col]
+                    .get(row)
+                    .and_then(|value| value.as_f32())
+                    .expect("Column isn't numeric")
+            })
+            .collect()
     }
-    longest_len
-}
 
-pub fn find_shortest(vec: &Vec<&str>) -> usize {
-    let mut shortest_len: usize = vec[0].len();
-    for i in 1..vec.len() {
-        let length = vec[i].len();
-        if length < shortest_len { shortest_len = length; }
+    /// A dataframe of the given rows, in their order
+    fn take_rows(&self, rows: &[usize]) -> DataFrame {
+        let mut frame = DataFrame {
+            row_labels: rows
+                .iter()
+                .map(|row| self.row_labels[*row].clone())
+                .collect(),
+            col_labels: self.col_labels.clone(),
+            columns: self
+                .columns
+                .iter()
+                .map(|column| column.take(rows))
+                .collect(),
+            ..Default::default()
+        };
+        frame.reindex();
+        frame
+    }
+
+    /// Rebuilds the label lookups, after labels have been moved
+    fn reindex(&mut self) {
+        self.row_index = self
+            .row_labels
+            .iter()
+            .enumerate()
+            .map(|(i, label)| (label.clone(), i))
+            .collect();
+        self.col_index = self
+            .col_labels
+            .iter()
+            .enumerate()
+            .map(|(i, label)| (label.clone(), i))
+            .collect();
     }
-    shortest_len
 }
 
-pub fn search_remove<T>(vec: &mut Vec<T>, val: T)
-    where T: PartialEq {
-        for i in 0..vec.len() {
-            if vec[i] == val {
-                vec.remove(i);
-                break;
+impl fmt::Display for DataFrame {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let cells: Vec<Vec<String>> = (0..self.rows_len())
+            .map(|row| {
+                self.columns
+                    .iter()
+                    .map(|column| {
+                        column
+                            .get(row)
+                            .map_or(String::new(), |value| value.to_string())
+                    })
+                    .collect()
+            })
+            .collect();
+
+        let label_width = self
+            .row_labels
+            .iter()
+            .map(|label| label.len())
+            .max()
+            .unwrap_or(0);
+        let widths: Vec<usize> = self
+            .col_labels
+            .iter()
+            .enumerate()
+            .map(|(col, label)| {
+                cells
+                    .iter()
+                    .map(|row| row[col].len())
+                    .chain(std::iter::once(label.len()))
+                    .max()
+                    .unwrap_or(0)
+            })
+            .collect();
+
+        write!(f, "{:>label_width$}", "")?;
+        for (label, width) in self.col_labels.iter().zip(widths.iter()) {
+            write!(f, "  {:>width$}", label)?;
+        }
+        writeln!(f)?;
+        for (label, row) in self.row_labels.iter().zip(cells.iter()) {
+            write!(f, "{:>label_width$}", label)?;
+            for (cell, width) in row.iter().zip(widths.iter()) {
+                write!(f, "  {:>width$}", cell)?;
             }
+            writeln!(f)?;
         }
+        Ok(())
     }
-
-fn hash_str(string: &str) -> u64 {
-    let mut s = DefaultHasher::new();
-    string.hash(&mut s);
-    s.finish()
 }
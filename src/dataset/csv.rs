@@ -0,0 +1,369 @@
+use crate::{
+    dataframe::{self, DataFrame},
+    error::DarjeelingError,
+    series::Series,
+};
+use serde::{Deserialize, Serialize};
+use std::{fs, path::Path};
+
+/// A column of a file, by its header or its zero based position
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub enum Column {
+    Name(String),
+    Index(usize),
+}
+
+/// Reads delimited text files into network inputs.
+///
+/// Fields can be wrapped in quotes to contain delimiters, newlines or (doubled) quotes.
+/// Blank lines are skipped.
+/// Blank fields and the reader's missing values, like `NA`, are read as missing, see [`missing`](CsvReader::missing).
+///
+/// ## Examples
+/// ``` rust
+/// use darjeeling::dataset::csv::{Column, CsvReader};
+///
+/// let text = "a;b;label\n0;1;\"yes\"\n1;1;\"no\"\n";
+/// let data = CsvReader::new()
+///     .headers(true)
+///     .delimiter(';')
+///     .label(Column::Name("label".to_string()))
+///     .parse_series(text)
+///     .unwrap();
+/// assert_eq!(data[0].data.as_ref(), &[0.0, 1.0]);
+/// assert_eq!(data[1].answer, "no");
+///
+/// let data = CsvReader::new().parse_series("1,NA,yes\n2,,no").unwrap();
+/// assert!(data[0].data[1].is_nan() && data[1].data[1].is_nan());
+/// ```
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct CsvReader {
+    pub has_headers: bool,
+    pub delimiter: char,
+    pub quote: char,
+    /// The column holding each row's answer, the last column if None
+    pub label: Option<Column>,
+    /// Fields that mean a value is missing, besides blank ones
+    pub missing: Vec<String>,
+}
+
+impl Default for CsvReader {
+    fn default() -> CsvReader {
+        CsvReader {
+            has_headers: false,
+            delimiter: ',',
+            quote: '"',
+            label: None,
+            missing: ["NA", "N/A", "NaN", "nan", "null", "NULL"]
+                .iter()
+                .map(|missing| missing.to_string())
+                .collect(),
+        }
+    }
+}
+
+/// A record's fields, with the line it starts on
+type Record = (usize, Box<[String]>);
+
+/// The fields of a file, after the headers
+struct Table {
+    headers: Option<Box<[String]>>,
+    records: Vec<Record>,
+}
+
+impl CsvReader {
+    /// A comma delimited reader without headers, whose last column is the label
+    pub fn new() -> CsvReader {
+        CsvReader::default()
+    }
+
+    /// Whether the first record is the names of the columns
+    pub fn headers(mut self, has_headers: bool) -> CsvReader {
+        self.has_headers = has_headers;
+        self
+    }
+
+    pub fn delimiter(mut self, delimiter: char) -> CsvReader {
+        self.delimiter = delimiter;
+        self
+    }
+
+    pub fn quote(mut self, quote: char) -> CsvReader {
+        self.quote = quote;
+        self
+    }
+
+    pub fn label(mut self, label: Column) -> CsvReader {
+        self.label = Some(label);
+        self
+    }
+
+    /// Replaces the fields that mean a value is missing, blank fields always do.
+    /// Missing features are NaN in inputs, and missing in dataframes, see [`Column::infer`](dataframe::Column::infer).
+    pub fn missing<S: ToString>(mut self, missing: &[S]) -> CsvReader {
+        self.missing = missing.iter().map(|missing| missing.to_string()).collect();
+        self
+    }
+
+    /// Reads a file of labelled inputs, for categorization networks
+    ///
+    /// ## Err
+    /// ### DatasetReadFailed
+    /// The file couldn't be read, or the label column doesn't exist
+    /// ### DatasetParseFailed
+    /// A field that isn't the label isn't a number or missing, or a record has a different number of fields
+    pub fn read_series<P: AsRef<Path>>(&self, path: P) -> Result<Box<[Series]>, DarjeelingError> {
+        self.parse_series(&read(path.as_ref())?)
+    }
+
+    /// Reads a file of unlabelled inputs, for generation networks.
+    /// Every column is read, unless a label column has been chosen, in which case it's left out.
+    ///
+    /// ## Err
+    /// ### DatasetReadFailed
+    /// The file couldn't be read, or the label column doesn't exist
+    /// ### DatasetParseFailed
+    /// A field isn't a number or missing, or a record has a different number of fields
+    pub fn read_rows<P: AsRef<Path>>(&self, path: P) -> Result<Box<[Box<[f32]>]>, DarjeelingError> {
+        self.parse_rows(&read(path.as_ref())?)
+    }
+
+    /// Parses labelled inputs from text, see [`read_series`](CsvReader::read_series)
+    pub fn parse_series(&self, text: &str) -> Result<Box<[Series]>, DarjeelingError> {
+        let table = self.parse_table(text)?;
+        let label = match self.label_index(&table)? {
+            Some(label) => label,
+            None => return Ok(Box::new([])),
+        };
+
+        table
+            .records
+            .iter()
+            .map(|(line, fields)| {
+                let data = self.parse_floats(*line, fields, Some(label))?;
+                Ok(Series::new(data, fields[label].clone()))
+            })
+            .collect()
+    }
+
+    /// Parses unlabelled inputs from text, see [`read_rows`](CsvReader::read_rows)
+    pub fn parse_rows(&self, text: &str) -> Result<Box<[Box<[f32]>]>, DarjeelingError> {
+        let table = self.parse_table(text)?;
+        let label = match self.label {
+            Some(_) => self.label_index(&table)?,
+            None => None,
+        };
+
+        table
+            .records
+            .iter()
+            .map(|(line, fields)| self.parse_floats(*line, fields, label))
+            .collect()
+    }
+
+    /// Reads a file into a dataframe, every column's type is inferred with [`Column::infer`](dataframe::Column::infer).
+    /// The columns are labelled by the headers, or by their position if there aren't any.
+    ///
+    /// ## Err
+    /// ### DatasetReadFailed
+    /// The file couldn't be read
+    /// ### DatasetParseFailed
+    /// A record has a different number of fields
+    /// ### ColumnAlreadyExists
+    /// Two headers are the same
+    pub fn read_frame<P: AsRef<Path>>(&self, path: P) -> Result<DataFrame, DarjeelingError> {
+        self.parse_frame(&read(path.as_ref())?)
+    }
+
+    /// Parses a dataframe from text, see [`read_frame`](CsvReader::read_frame)
+    pub fn parse_frame(&self, text: &str) -> Result<DataFrame, DarjeelingError> {
+        let table = self.parse_table(text)?;
+        let width = table
+            .headers
+            .as_ref()
+            .map(|headers| headers.len())
+            .or_else(|| table.records.first().map(|(_, fields)| fields.len()))
+            .unwrap_or(0);
+
+        let columns = (0..width)
+            .map(|col| {
+                let label = table
+                    .headers
+                    .as_ref()
+                    .map_or(col.to_string(), |headers| headers[col].trim().to_string());
+                let values: Vec<String> = table
+                    .records
+                    .iter()
+                    .map(|(_, fields)| {
+                        if self.is_missing(&fields[col]) {
+                            String::new()
+                        } else {
+                            fields[col].clone()
+                        }
+                    })
+                    .collect();
+                (label, dataframe::Column::infer(&values))
+            })
+            .collect();
+        DataFrame::from_columns(columns)
+    }
+
+    /// The position of the label column, None if there are no columns
+    fn label_index(&self, table: &Table) -> Result<Option<usize>, DarjeelingError> {
+        let width = match table.records.first() {
+            Some((_, fields)) => fields.len(),
+            None => return Ok(None),
+        };
+
+        let index = match &self.label {
+            None => width - 1,
+            Some(Column::Index(index)) => *index,
+            Some(Column::Name(name)) => table
+                .headers
+                .as_ref()
+                .ok_or_else(|| {
+                    DarjeelingError::DatasetReadFailed(format!(
+                        "the label column {:?} can't be found by name without headers",
+                        name
+                    ))
+                })?
+                .iter()
+                .position(|header| header.trim() == name)
+                .ok_or_else(|| {
+                    DarjeelingError::DatasetReadFailed(format!(
+                        "there is no column named {:?}",
+                        name
+                    ))
+                })?,
+        };
+        if index >= width {
+            return Err(DarjeelingError::DatasetReadFailed(format!(
+                "the label column {} doesn't exist, there are only {} columns",
+                index, width
+            )));
+        }
+        Ok(Some(index))
+    }
+
+    /// Splits the text into records, and checks they all have as many fields as the first
+    fn parse_table(&self, text: &str) -> Result<Table, DarjeelingError> {
+        let mut records = self.split_records(text)?;
+        let headers = if self.has_headers && !records.is_empty() {
+            Some(records.remove(0).1)
+        } else {
+            None
+        };
+
+        let width = headers
+            .as_ref()
+            .map(|headers| headers.len())
+            .or_else(|| records.first().map(|(_, fields)| fields.len()));
+        if let Some(width) = width {
+            if let Some((line, fields)) = records.iter().find(|(_, fields)| fields.len() != width) {
+                return Err(DarjeelingError::DatasetParseFailed(
+                    *line,
+                    format!("expected {} fields, found {}", width, fields.len()),
+                ));
+            }
+        }
+        Ok(Table { headers, records })
+    }
+
+    fn is_missing(&self, field: &str) -> bool {
+        let field = field.trim();
+        field.is_empty() || self.missing.iter().any(|missing| missing == field)
+    }
+
+    /// Parses every field but the label as a number, missing fields are NaN
+    fn parse_floats(
+        &self,
+        line: usize,
+        fields: &[String],
+        label: Option<usize>,
+    ) -> Result<Box<[f32]>, DarjeelingError> {
+        fields
+            .iter()
+            .enumerate()
+            .filter(|(i, _)| Some(*i) != label)
+            .map(|(i, field)| {
+                if self.is_missing(field) {
+                    return Ok(f32::NAN);
+                }
+                field.trim().parse::<f32>().map_err(|_| {
+                    DarjeelingError::DatasetParseFailed(
+                        line,
+                        format!("field {} ({:?}) isn't a number", i, field),
+                    )
+                })
+            })
+            .collect()
+    }
+
+    fn split_records(&self, text: &str) -> Result<Vec<Record>, DarjeelingError> {
+        let mut records = vec![];
+        let mut fields: Vec<String> = vec![];
+        let mut field = String::new();
+        let mut quoted = false;
+        // Whether the current field was quoted, so an empty quoted field still counts
+        let mut was_quoted = false;
+        let mut line = 1;
+        let mut record_line = 1;
+
+        let mut chars = text.chars().peekable();
+        while let Some(c) = chars.next() {
+            if quoted {
+                if c == self.quote {
+                    if chars.peek() == Some(&self.quote) {
+                        field.push(chars.next().unwrap_or(c));
+                    } else {
+                        quoted = false;
+                    }
+                } else {
+                    if c == '\n' {
+                        line += 1;
+                    }
+                    field.push(c);
+                }
+            } else if c == self.quote && field.trim().is_empty() {
+                field.clear();
+                quoted = true;
+                was_quoted = true;
+            } else if c == self.delimiter {
+                fields.push(std::mem::take(&mut field));
+                was_quoted = false;
+            } else if c == '\n' || c == '\r' {
+                if c == '\r' && chars.peek() == Some(&'\n') {
+                    chars.next();
+                }
+                if !fields.is_empty() || !field.trim().is_empty() || was_quoted {
+                    fields.push(std::mem::take(&mut field));
+                    records.push((record_line, fields.into_boxed_slice()));
+                }
+                fields = vec![];
+                field.clear();
+                was_quoted = false;
+                line += 1;
+                record_line = line;
+            } else {
+                field.push(c);
+            }
+        }
+
+        if quoted {
+            return Err(DarjeelingError::DatasetParseFailed(
+                record_line,
+                "a quoted field is never closed".to_string(),
+            ));
+        }
+        if !fields.is_empty() || !field.trim().is_empty() || was_quoted {
+            fields.push(field);
+            records.push((record_line, fields.into_boxed_slice()));
+        }
+        Ok(records)
+    }
+}
+
+fn read(path: &Path) -> Result<String, DarjeelingError> {
+    fs::read_to_string(path)
+        .map_err(|err| DarjeelingError::DatasetReadFailed(format!("{:?}: {}", path, err)))
+}
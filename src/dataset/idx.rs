@@ -0,0 +1,158 @@
+use super::{features_of, series_from, Array};
+use crate::{error::DarjeelingError, series::Series};
+use std::{fs, path::Path};
+
+const UNSIGNED_BYTE: u8 = 0x08;
+const SIGNED_BYTE: u8 = 0x09;
+const SHORT: u8 = 0x0B;
+const INT: u8 = 0x0C;
+const FLOAT: u8 = 0x0D;
+const DOUBLE: u8 = 0x0E;
+
+/// Reads an IDX file, the format of MNIST and the datasets modelled on it
+///
+/// ## Err
+/// ### DatasetReadFailed
+/// The file couldn't be read, or isn't a valid IDX file
+pub fn read<P: AsRef<Path>>(path: P) -> Result<Array, DarjeelingError> {
+    let path = path.as_ref();
+    let bytes = fs::read(path)
+        .map_err(|err| DarjeelingError::DatasetReadFailed(format!("{:?}: {}", path, err)))?;
+    parse(&bytes)
+}
+
+/// Reads an images file and its labels file into inputs, every image is flattened
+///
+/// ## Examples
+/// ```ignore
+/// use darjeeling::dataset::idx;
+///
+/// let data = idx::read_series("train-images-idx3-ubyte", "train-labels-idx1-ubyte").unwrap();
+/// ```
+///
+/// ## Err
+/// ### DatasetReadFailed
+/// Either file couldn't be read or isn't a valid IDX file, or they don't have as many items as each other
+pub fn read_series<P: AsRef<Path>, Q: AsRef<Path>>(
+    images: P,
+    labels: Q,
+) -> Result<Box<[Series]>, DarjeelingError> {
+    series_from(&read(images)?, &read(labels)?.labels())
+}
+
+/// Parses the bytes of an IDX file
+///
+/// ## Err
+/// ### DatasetReadFailed
+/// The bytes aren't a valid IDX file
+pub fn parse(bytes: &[u8]) -> Result<Array, DarjeelingError> {
+    if bytes.len() < 4 || bytes[0] != 0 || bytes[1] != 0 {
+        return Err(invalid("it doesn't start with the IDX magic number"));
+    }
+    let data_type = bytes[2];
+    let dimensions = bytes[3] as usize;
+
+    let header_len = 4 + dimensions * 4;
+    if bytes.len() < header_len {
+        return Err(invalid("its header is cut off"));
+    }
+    let shape: Box<[usize]> = bytes[4..header_len]
+        .chunks(4)
+        .map(|dimension| {
+            u32::from_be_bytes([dimension[0], dimension[1], dimension[2], dimension[3]]) as usize
+        })
+        .collect();
+
+    let size = match data_type {
+        UNSIGNED_BYTE | SIGNED_BYTE => 1,
+        SHORT => 2,
+        INT | FLOAT => 4,
+        DOUBLE => 8,
+        _ => return Err(invalid(&format!("{:#04x} isn't a data type", data_type))),
+    };
+    let len: usize = shape.iter().product();
+    let body = &bytes[header_len..];
+    if body.len() != len * size {
+        return Err(invalid(&format!(
+            "a shape of {:?} needs {} bytes of data, but there are {}",
+            shape,
+            len * size,
+            body.len()
+        )));
+    }
+
+    let data = body
+        .chunks(size)
+        .map(|value| match data_type {
+            UNSIGNED_BYTE => value[0] as f32,
+            SIGNED_BYTE => value[0] as i8 as f32,
+            SHORT => i16::from_be_bytes([value[0], value[1]]) as f32,
+            INT => i32::from_be_bytes([value[0], value[1], value[2], value[3]]) as f32,
+            FLOAT => f32::from_be_bytes([value[0], value[1], value[2], value[3]]),
+            _ => f64::from_be_bytes([
+                value[0], value[1], value[2], value[3], value[4], value[5], value[6], value[7],
+            ]) as f32,
+        })
+        .collect();
+    Array::new(shape, data)
+}
+
+/// Writes an array as an IDX file of floats
+///
+/// ## Err
+/// ### WriteFileFailed
+/// The array has more than 255 dimensions, or the file couldn't be written
+pub fn write<P: AsRef<Path>>(path: P, array: &Array) -> Result<(), DarjeelingError> {
+    if array.shape.len() > u8::MAX as usize {
+        return Err(DarjeelingError::WriteFileFailed(format!(
+            "IDX files can't have {} dimensions",
+            array.shape.len()
+        )));
+    }
+
+    let mut bytes = vec![0, 0, FLOAT, array.shape.len() as u8];
+    array
+        .shape
+        .iter()
+        .for_each(|dimension| bytes.extend((*dimension as u32).to_be_bytes()));
+    array
+        .data
+        .iter()
+        .for_each(|value| bytes.extend(value.to_be_bytes()));
+    fs::write(path, bytes).map_err(|err| DarjeelingError::WriteFileFailed(err.to_string()))
+}
+
+/// Writes inputs as an images file of shape (inputs, features) and a labels file
+///
+/// ## Err
+/// ### WriteFileFailed
+/// An answer isn't a number, the inputs have different lengths, or either file couldn't be written
+pub fn write_series<P: AsRef<Path>, Q: AsRef<Path>>(
+    images: P,
+    labels: Q,
+    data: &[Series],
+) -> Result<(), DarjeelingError> {
+    let features =
+        features_of(data).map_err(|err| DarjeelingError::WriteFileFailed(err.to_string()))?;
+    let answers = data
+        .iter()
+        .map(|series| {
+            series.answer.parse::<f32>().map_err(|_| {
+                DarjeelingError::WriteFileFailed(format!(
+                    "IDX labels must be numbers, not {:?}",
+                    series.answer
+                ))
+            })
+        })
+        .collect::<Result<Box<[f32]>, DarjeelingError>>()?;
+
+    write(images, &features)?;
+    write(
+        labels,
+        &Array::new(vec![data.len()].into_boxed_slice(), answers)?,
+    )
+}
+
+fn invalid(reason: &str) -> DarjeelingError {
+    DarjeelingError::DatasetReadFailed(format!("not a valid IDX file, {}", reason))
+}
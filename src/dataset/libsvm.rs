@@ -0,0 +1,122 @@
+use crate::{error::DarjeelingError, series::Series};
+use std::{fmt::Write, fs, path::Path};
+
+/// Reads a sparse LIBSVM file, where each line is a label followed by one based `index:value` pairs.
+/// Features that aren't listed are zero, and anything after a `#` is a comment.
+///
+/// ## Params
+/// - Path: The file to read
+/// - Features: The number of features every input has, the largest index in the file if None
+///
+/// ## Examples
+/// ```ignore
+/// use darjeeling::dataset::libsvm;
+///
+/// let data = libsvm::read_series("a9a.txt", Some(123)).unwrap();
+/// ```
+///
+/// ## Err
+/// ### DatasetReadFailed
+/// The file couldn't be read
+/// ### DatasetParseFailed
+/// A line isn't valid, or has an index larger than the number of features
+pub fn read_series<P: AsRef<Path>>(
+    path: P,
+    features: Option<usize>,
+) -> Result<Box<[Series]>, DarjeelingError> {
+    let path = path.as_ref();
+    let text = fs::read_to_string(path)
+        .map_err(|err| DarjeelingError::DatasetReadFailed(format!("{:?}: {}", path, err)))?;
+    parse_series(&text, features)
+}
+
+/// A line's number, label and index:value pairs
+type SparseRow = (usize, String, Vec<(usize, f32)>);
+
+/// Parses LIBSVM text, see [`read_series`]
+pub fn parse_series(text: &str, features: Option<usize>) -> Result<Box<[Series]>, DarjeelingError> {
+    let mut rows: Vec<SparseRow> = vec![];
+    for (i, line) in text.lines().enumerate() {
+        let line_number = i + 1;
+        let line = line.split('#').next().unwrap_or("").trim();
+        if line.is_empty() {
+            continue;
+        }
+
+        let mut tokens = line.split_whitespace();
+        let label = tokens.next().unwrap_or("").to_string();
+        let pairs = tokens
+            .map(|token| parse_pair(line_number, token))
+            .collect::<Result<Vec<(usize, f32)>, DarjeelingError>>()?;
+        rows.push((line_number, label, pairs));
+    }
+
+    let largest = rows
+        .iter()
+        .flat_map(|(_, _, pairs)| pairs.iter().map(|(index, _)| *index))
+        .max()
+        .unwrap_or(0);
+    let width = features.unwrap_or(largest);
+
+    rows.into_iter()
+        .map(|(line, label, pairs)| {
+            let mut data = vec![0.0; width];
+            for (index, value) in pairs {
+                if index > width {
+                    return Err(DarjeelingError::DatasetParseFailed(
+                        line,
+                        format!("index {} is past the {} features", index, width),
+                    ));
+                }
+                data[index - 1] = value;
+            }
+            Ok(Series::new(data, label))
+        })
+        .collect()
+}
+
+/// Writes inputs as a LIBSVM file, leaving out features that are zero
+///
+/// ## Err
+/// ### WriteFileFailed
+/// An answer contains whitespace, or the file couldn't be written
+pub fn write_series<P: AsRef<Path>>(path: P, data: &[Series]) -> Result<(), DarjeelingError> {
+    let mut text = String::new();
+    for series in data.iter() {
+        if series.answer.is_empty() || series.answer.contains(char::is_whitespace) {
+            return Err(DarjeelingError::WriteFileFailed(format!(
+                "LIBSVM labels can't be empty or contain whitespace, {:?} does",
+                series.answer
+            )));
+        }
+
+        text.push_str(&series.answer);
+        series
+            .data
+            .iter()
+            .enumerate()
+            .filter(|(_, value)| **value != 0.0)
+            .for_each(|(i, value)| {
+                let _ = write!(text, " {}:{}", i + 1, value);
+            });
+        text.push('\n');
+    }
+    fs::write(path, text).map_err(|err| DarjeelingError::WriteFileFailed(err.to_string()))
+}
+
+fn parse_pair(line: usize, token: &str) -> Result<(usize, f32), DarjeelingError> {
+    let invalid = || {
+        DarjeelingError::DatasetParseFailed(
+            line,
+            format!("{:?} isn't a one based index:value pair", token),
+        )
+    };
+
+    let (index, value) = token.split_once(':').ok_or_else(invalid)?;
+    let index: usize = index.parse().map_err(|_| invalid())?;
+    let value: f32 = value.parse().map_err(|_| invalid())?;
+    if index == 0 {
+        return Err(invalid());
+    }
+    Ok((index, value))
+}
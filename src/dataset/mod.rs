@@ -0,0 +1,103 @@
+pub mod csv;
+pub mod idx;
+pub mod libsvm;
+pub mod npy;
+
+use crate::{error::DarjeelingError, series::Series};
+use serde::{Deserialize, Serialize};
+
+/// A dense n-dimensional array read from a binary file, stored in row major order
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct Array {
+    pub shape: Box<[usize]>,
+    pub data: Box<[f32]>,
+}
+
+impl Array {
+    /// ## Err
+    /// ### DatasetReadFailed
+    /// The shape doesn't have as many elements as the data
+    pub fn new(shape: Box<[usize]>, data: Box<[f32]>) -> Result<Array, DarjeelingError> {
+        let len: usize = shape.iter().product();
+        if len != data.len() {
+            return Err(DarjeelingError::DatasetReadFailed(format!(
+                "an array of shape {:?} needs {} elements, but has {}",
+                shape,
+                len,
+                data.len()
+            )));
+        }
+        Ok(Array { shape, data })
+    }
+
+    /// Every item along the first dimension, with the rest of the dimensions flattened.
+    /// Items are empty if another dimension is 0.
+    pub fn rows(&self) -> Box<[Box<[f32]>]> {
+        let count = self.shape.first().copied().unwrap_or(1);
+        if count == 0 {
+            return Box::new([]);
+        }
+        let width = self.data.len() / count;
+        if width == 0 {
+            return vec![Box::new([]) as Box<[f32]>; count].into_boxed_slice();
+        }
+        self.data.chunks(width).map(|row| row.into()).collect()
+    }
+
+    /// Every element as a category, whole numbers are written without a decimal point
+    pub fn labels(&self) -> Box<[String]> {
+        self.data.iter().map(|label| label_string(*label)).collect()
+    }
+}
+
+/// Pairs every row of the features with its label
+///
+/// ## Err
+/// ### DatasetReadFailed
+/// There aren't as many labels as rows
+pub fn series_from(features: &Array, labels: &[String]) -> Result<Box<[Series]>, DarjeelingError> {
+    let rows = features.rows();
+    if rows.len() != labels.len() {
+        return Err(DarjeelingError::DatasetReadFailed(format!(
+            "there are {} rows, but {} labels",
+            rows.len(),
+            labels.len()
+        )));
+    }
+
+    Ok(rows
+        .into_vec()
+        .into_iter()
+        .zip(labels.iter())
+        .map(|(row, label)| Series::new(row, label))
+        .collect())
+}
+
+/// The features of every input, as an array of shape (inputs, features)
+///
+/// ## Err
+/// ### DatasetReadFailed
+/// The inputs have different lengths
+pub fn features_of(data: &[Series]) -> Result<Array, DarjeelingError> {
+    let width = data.first().map_or(0, |series| series.data.len());
+    if data.iter().any(|series| series.data.len() != width) {
+        return Err(DarjeelingError::DatasetReadFailed(
+            "every input needs the same number of features".to_string(),
+        ));
+    }
+
+    Array::new(
+        vec![data.len(), width].into_boxed_slice(),
+        data.iter()
+            .flat_map(|series| series.data.iter().copied())
+            .collect(),
+    )
+}
+
+pub(crate) fn label_string(label: f32) -> String {
+    if label.fract() == 0.0 && label.abs() < 1e15 {
+        format!("{}", label as i64)
+    } else {
+        label.to_string()
+    }
+}
@@ -0,0 +1,334 @@
+use super::{features_of, series_from, Array};
+use crate::{error::DarjeelingError, series::Series};
+use std::{fs, path::Path};
+
+const MAGIC: &[u8] = b"\x93NUMPY";
+
+/// The element type of an array, from its `descr`
+struct Descr {
+    big_endian: bool,
+    kind: char,
+    size: usize,
+}
+
+/// Reads a numeric `.npy` file, as saved by `numpy.save`
+///
+/// ## Err
+/// ### DatasetReadFailed
+/// The file couldn't be read, isn't a valid `.npy` file, or doesn't hold numbers or booleans
+pub fn read<P: AsRef<Path>>(path: P) -> Result<Array, DarjeelingError> {
+    parse(&read_bytes(path.as_ref())?)
+}
+
+/// Reads the labels of a dataset from a one dimensional `.npy` file of numbers or unicode strings
+///
+/// ## Err
+/// ### DatasetReadFailed
+/// The file couldn't be read, or isn't a valid `.npy` file
+pub fn read_labels<P: AsRef<Path>>(path: P) -> Result<Box<[String]>, DarjeelingError> {
+    parse_labels(&read_bytes(path.as_ref())?)
+}
+
+/// Reads a features file and its labels file into inputs, every item along the first dimension being an input
+///
+/// ## Examples
+/// ```ignore
+/// use darjeeling::dataset::npy;
+///
+/// // numpy.save("x.npy", x); numpy.save("y.npy", y)
+/// let data = npy::read_series("x.npy", "y.npy").unwrap();
+/// ```
+///
+/// ## Err
+/// ### DatasetReadFailed
+/// Either file couldn't be read or isn't valid, or they don't have as many items as each other
+pub fn read_series<P: AsRef<Path>, Q: AsRef<Path>>(
+    features: P,
+    labels: Q,
+) -> Result<Box<[Series]>, DarjeelingError> {
+    series_from(&read(features)?, &read_labels(labels)?)
+}
+
+/// Parses the bytes of a numeric `.npy` file
+pub fn parse(bytes: &[u8]) -> Result<Array, DarjeelingError> {
+    let (descr, shape, body) = parse_header(bytes)?;
+    if descr.kind == 'U' {
+        return Err(invalid("it holds strings, read it with read_labels"));
+    }
+
+    let data = body
+        .chunks(descr.size)
+        .map(|value| {
+            let mut bytes = [0; 8];
+            bytes[..descr.size].copy_from_slice(value);
+            if descr.big_endian {
+                bytes[..descr.size].reverse();
+            }
+            match (descr.kind, descr.size) {
+                ('f', 4) => f32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]),
+                ('f', _) => f64::from_le_bytes(bytes) as f32,
+                ('i', 1) => bytes[0] as i8 as f32,
+                ('i', 2) => i16::from_le_bytes([bytes[0], bytes[1]]) as f32,
+                ('i', 4) => i32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]) as f32,
+                ('i', _) => i64::from_le_bytes(bytes) as f32,
+                // Unsigned integers and booleans, the unused bytes are zero
+                _ => u64::from_le_bytes(bytes) as f32,
+            }
+        })
+        .collect::<Box<[f32]>>();
+    Array::new(shape, data)
+}
+
+/// Parses the bytes of a one dimensional `.npy` file of numbers or unicode strings
+pub fn parse_labels(bytes: &[u8]) -> Result<Box<[String]>, DarjeelingError> {
+    let (descr, shape, body) = parse_header(bytes)?;
+    if shape.len() != 1 {
+        return Err(invalid(&format!(
+            "labels need one dimension, not a shape of {:?}",
+            shape
+        )));
+    }
+    if descr.kind != 'U' {
+        return Ok(parse(bytes)?.labels());
+    }
+
+    body.chunks(descr.size)
+        .map(|label| {
+            label
+                .chunks(4)
+                .map(|c| {
+                    let c = [c[0], c[1], c[2], c[3]];
+                    if descr.big_endian {
+                        u32::from_be_bytes(c)
+                    } else {
+                        u32::from_le_bytes(c)
+                    }
+                })
+                // Strings shorter than the array's width are padded with nulls
+                .take_while(|c| *c != 0)
+                .map(|c| char::from_u32(c).ok_or_else(|| invalid("a label isn't valid unicode")))
+                .collect()
+        })
+        .collect()
+}
+
+/// Writes an array as a `.npy` file of little endian floats
+///
+/// ## Err
+/// ### WriteFileFailed
+/// The file couldn't be written
+pub fn write<P: AsRef<Path>>(path: P, array: &Array) -> Result<(), DarjeelingError> {
+    let mut bytes = header("<f4", &array.shape);
+    array
+        .data
+        .iter()
+        .for_each(|value| bytes.extend(value.to_le_bytes()));
+    fs::write(path, bytes).map_err(|err| DarjeelingError::WriteFileFailed(err.to_string()))
+}
+
+/// Writes labels as a one dimensional `.npy` file of unicode strings
+///
+/// ## Err
+/// ### WriteFileFailed
+/// The file couldn't be written
+pub fn write_labels<P: AsRef<Path>>(path: P, labels: &[String]) -> Result<(), DarjeelingError> {
+    let width = labels
+        .iter()
+        .map(|label| label.chars().count())
+        .max()
+        .unwrap_or(0)
+        .max(1);
+
+    let mut bytes = header(&format!("<U{}", width), &[labels.len()]);
+    for label in labels.iter() {
+        let chars: Vec<char> = label.chars().collect();
+        (0..width).for_each(|i| {
+            bytes.extend(chars.get(i).map_or(0, |c| *c as u32).to_le_bytes());
+        });
+    }
+    fs::write(path, bytes).map_err(|err| DarjeelingError::WriteFileFailed(err.to_string()))
+}
+
+/// Writes inputs as a features file of shape (inputs, features) and a labels file
+///
+/// ## Err
+/// ### WriteFileFailed
+/// The inputs have different lengths, or either file couldn't be written
+pub fn write_series<P: AsRef<Path>, Q: AsRef<Path>>(
+    features: P,
+    labels: Q,
+    data: &[Series],
+) -> Result<(), DarjeelingError> {
+    let array =
+        features_of(data).map_err(|err| DarjeelingError::WriteFileFailed(err.to_string()))?;
+    let answers: Box<[String]> = data.iter().map(|series| series.answer.clone()).collect();
+
+    write(features, &array)?;
+    write_labels(labels, &answers)
+}
+
+fn read_bytes(path: &Path) -> Result<Vec<u8>, DarjeelingError> {
+    fs::read(path).map_err(|err| DarjeelingError::DatasetReadFailed(format!("{:?}: {}", path, err)))
+}
+
+/// A file's element type, its shape and its data in row major order
+type Parts = (Descr, Box<[usize]>, Vec<u8>);
+
+fn parse_header(bytes: &[u8]) -> Result<Parts, DarjeelingError> {
+    if bytes.len() < 10 || &bytes[..6] != MAGIC {
+        return Err(invalid("it doesn't start with the .npy magic string"));
+    }
+    let (header_len, header_start) = match bytes[6] {
+        1 => (u16::from_le_bytes([bytes[8], bytes[9]]) as usize, 10),
+        2 | 3 if bytes.len() >= 12 => (
+            u32::from_le_bytes([bytes[8], bytes[9], bytes[10], bytes[11]]) as usize,
+            12,
+        ),
+        version => return Err(invalid(&format!("version {} isn't supported", version))),
+    };
+    let body_start = header_start + header_len;
+    if bytes.len() < body_start {
+        return Err(invalid("its header is cut off"));
+    }
+    let header = std::str::from_utf8(&bytes[header_start..body_start])
+        .map_err(|_| invalid("its header isn't text"))?;
+
+    let descr =
+        parse_descr(header_value(header, "descr")?.trim_matches(|c| c == '\'' || c == '"'))?;
+    let fortran_order = header_value(header, "fortran_order")? == "True";
+    let shape: Box<[usize]> = header_value(header, "shape")?
+        .trim_matches(|c| c == '(' || c == ')')
+        .split(',')
+        .map(str::trim)
+        .filter(|dimension| !dimension.is_empty())
+        .map(|dimension| {
+            dimension
+                .parse()
+                .map_err(|_| invalid(&format!("{:?} isn't a dimension", dimension)))
+        })
+        .collect::<Result<_, _>>()?;
+
+    let len: usize = shape.iter().product();
+    let body = &bytes[body_start..];
+    if body.len() != len * descr.size {
+        return Err(invalid(&format!(
+            "a shape of {:?} needs {} bytes of data, but there are {}",
+            shape,
+            len * descr.size,
+            body.len()
+        )));
+    }
+
+    let body = if fortran_order && shape.len() > 1 {
+        to_row_major(body, &shape, descr.size)
+    } else {
+        body.to_vec()
+    };
+    Ok((descr, shape, body))
+}
+
+/// The raw value of a key in the header's python dictionary
+fn header_value<'a>(header: &'a str, key: &str) -> Result<&'a str, DarjeelingError> {
+    let start = ["'", "\""]
+        .iter()
+        .find_map(|quote| header.find(&format!("{}{}{}", quote, key, quote)))
+        .ok_or_else(|| invalid(&format!("its header has no {}", key)))?;
+    let rest = &header[start + key.len() + 2..];
+    let rest = rest.trim_start().trim_start_matches(':').trim_start();
+
+    let end = if rest.starts_with('(') {
+        rest.find(')').map(|end| end + 1)
+    } else {
+        rest.find([',', '}'])
+    }
+    .ok_or_else(|| invalid(&format!("its header's {} isn't closed", key)))?;
+    Ok(rest[..end].trim())
+}
+
+fn parse_descr(descr: &str) -> Result<Descr, DarjeelingError> {
+    let mut chars = descr.chars();
+    let big_endian = match chars.next() {
+        Some('>') => true,
+        Some('<') | Some('|') | Some('=') => false,
+        _ => return Err(invalid(&format!("{:?} isn't a supported type", descr))),
+    };
+    let kind = chars.next().unwrap_or(' ');
+    let size: usize = chars
+        .as_str()
+        .parse()
+        .map_err(|_| invalid(&format!("{:?} isn't a supported type", descr)))?;
+
+    let supported = match kind {
+        'f' => size == 4 || size == 8,
+        'i' | 'u' => [1, 2, 4, 8].contains(&size),
+        'b' => size == 1,
+        'U' => size > 0,
+        _ => false,
+    };
+    if !supported {
+        return Err(invalid(&format!("{:?} isn't a supported type", descr)));
+    }
+
+    Ok(Descr {
+        big_endian,
+        kind,
+        // Unicode strings are sized in characters of four bytes each
+        size: if kind == 'U' { size * 4 } else { size },
+    })
+}
+
+/// Reorders column major elements into row major order
+fn to_row_major(body: &[u8], shape: &[usize], size: usize) -> Vec<u8> {
+    let len: usize = shape.iter().product();
+    let mut row_major = Vec::with_capacity(body.len());
+    for i in 0..len {
+        // The index of each dimension, from the row major position
+        let mut rest = i;
+        let mut offset = 0;
+        let mut stride = 1;
+        let mut indices = vec![0; shape.len()];
+        for (dimension, index) in shape.iter().zip(indices.iter_mut()).rev() {
+            *index = rest % dimension;
+            rest /= dimension;
+        }
+        for (dimension, index) in shape.iter().zip(indices.iter()) {
+            offset += index * stride;
+            stride *= dimension;
+        }
+        row_major.extend_from_slice(&body[offset * size..(offset + 1) * size]);
+    }
+    row_major
+}
+
+/// A version 1.0 header, padded so the data is aligned to 64 bytes
+fn header(descr: &str, shape: &[usize]) -> Vec<u8> {
+    let shape = match shape {
+        [dimension] => format!("({},)", dimension),
+        _ => format!(
+            "({})",
+            shape
+                .iter()
+                .map(|dimension| dimension.to_string())
+                .collect::<Vec<String>>()
+                .join(", ")
+        ),
+    };
+    let mut dict = format!(
+        "{{'descr': '{}', 'fortran_order': False, 'shape': {}, }}",
+        descr, shape
+    );
+    while (MAGIC.len() + 4 + dict.len() + 1) % 64 != 0 {
+        dict.push(' ');
+    }
+    dict.push('\n');
+
+    let mut bytes = MAGIC.to_vec();
+    bytes.extend([1, 0]);
+    bytes.extend((dict.len() as u16).to_le_bytes());
+    bytes.extend(dict.bytes());
+    bytes
+}
+
+fn invalid(reason: &str) -> DarjeelingError {
+    DarjeelingError::DatasetReadFailed(format!("not a valid .npy file, {}", reason))
+}
@@ -17,11 +17,31 @@ pub enum DarjeelingError {
     RemoveModelFailed(String),
     ActivationFunctionNotRead(String),
     InvalidNodeValueRead(String),
+    InvalidLayerSpec(String),
+    ConfigReadFailed(String),
+    CategoryDoesNotExist(String),
+    InvalidMetricInput(String),
+    SerializationFailed(String),
+    WriteFileFailed(String),
+    InvalidFolds(String),
+    InvalidSearch(String),
+    DatasetReadFailed(String),
+    DatasetParseFailed(usize, String),
+    ColumnMismatch(String),
+    TransformFailed(String),
+    ModelMissingCategories,
+    NanEncountered(String),
+    InvalidSplit(String),
+    InvalidSampling(String),
+    InvalidThresholds(String),
+    InvalidLatent(String),
+    InvalidCorruption(String),
+    UnsupportedConfig(String),
 
     UnknownError(String),
 }
 
-impl<'a> fmt::Display for DarjeelingError {
+impl fmt::Display for DarjeelingError {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match self {
             DarjeelingError::ColumnDoesNotExist(column) => write!(f, 
@@ -36,9 +56,9 @@ impl<'a> fmt::Display for DarjeelingError {
                 "There is no point at row: {:?}, column: {:?}", 
                 row, column
             ),
-            DarjeelingError::SaveModelFailed(model_name) => write!(f,
-                "Unable to read model {:?}, \n Hint: Double check the model_name \n Error Message: {}",
-                model_name.split(";").collect::<Vec<&str>>()[0], model_name.split(";").collect::<Vec<&str>>()[1]
+            DarjeelingError::SaveModelFailed(err) => write!(f,
+                "Unable to write model, \n Hint: Double check the save path \n Error Message: {}",
+                err
             ),
             DarjeelingError::LoadModelFailed(model_name) => write!(f,
                 "Unable to write model {:?}, \n Hint: This is probably because the random name already exists, try saving it again",
@@ -76,6 +96,85 @@ impl<'a> fmt::Display for DarjeelingError {
                 "Attemtped to add a row labeled: {}, that already exists",
                 label
             ),
+            DarjeelingError::InvalidLayerSpec(err) => write!(f,
+                "The network's layers couldn't be built: {}",
+                err
+            ),
+            DarjeelingError::ConfigReadFailed(err) => write!(f,
+                "Unable to read or write the training config. Error message: {}",
+                err
+            ),
+            DarjeelingError::CategoryDoesNotExist(category) => write!(f,
+                "{:?} isn't one of the network's categories",
+                category
+            ),
+            DarjeelingError::InvalidMetricInput(err) => write!(f,
+                "Metrics couldn't be calculated: {}",
+                err
+            ),
+            DarjeelingError::SerializationFailed(err) => write!(f,
+                "Unable to serialize. Error message: {}",
+                err
+            ),
+            DarjeelingError::WriteFileFailed(err) => write!(f,
+                "Unable to write file. Error message: {}",
+                err
+            ),
+            DarjeelingError::InvalidFolds(err) => write!(f,
+                "The data couldn't be split into folds: {}",
+                err
+            ),
+            DarjeelingError::InvalidSearch(err) => write!(f,
+                "The hyperparameter search couldn't be run: {}",
+                err
+            ),
+            DarjeelingError::DatasetReadFailed(err) => write!(f,
+                "Unable to read the dataset. Error message: {}",
+                err
+            ),
+            DarjeelingError::DatasetParseFailed(line, err) => write!(f,
+                "Unable to parse the dataset at line {}: {}",
+                line, err
+            ),
+            DarjeelingError::ColumnMismatch(err) => write!(f,
+                "The dataframe's columns don't match: {}",
+                err
+            ),
+            DarjeelingError::TransformFailed(err) => write!(f,
+                "Transforming the data failed: {}",
+                err
+            ),
+            DarjeelingError::ModelMissingCategories => write!(f,
+                "The model's answer nodes have no categories, train it first"
+            ),
+            DarjeelingError::NanEncountered(err) => write!(f,
+                "Encountered NaN: {}",
+                err
+            ),
+            DarjeelingError::InvalidSplit(err) => write!(f,
+                "The data can't be split: {}",
+                err
+            ),
+            DarjeelingError::InvalidSampling(err) => write!(f,
+                "The data can't be resampled: {}",
+                err
+            ),
+            DarjeelingError::InvalidThresholds(err) => write!(f,
+                "The thresholds don't fit the network: {}",
+                err
+            ),
+            DarjeelingError::InvalidLatent(err) => write!(f,
+                "The latent vectors can't be used: {}",
+                err
+            ),
+            DarjeelingError::InvalidCorruption(err) => write!(f,
+                "The inputs can't be corrupted: {}",
+                err
+            ),
+            DarjeelingError::UnsupportedConfig(err) => write!(f,
+                "The config sets something the training doesn't use: {}",
+                err
+            ),
             DarjeelingError::UnknownError(error) => write!(f,
                 "Non-Darjeeling error encountered: \n {:?}",
                 error
@@ -84,3 +183,5 @@ impl<'a> fmt::Display for DarjeelingError {
         }
     }
 }
+
+impl Error for DarjeelingError {}
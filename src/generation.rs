@@ -1,16 +1,143 @@
 use crate::{
-    activation::ActivationFunction, categorize::CatNetwork, dbg_println, error::DarjeelingError,
-    neural_network::NeuralNetwork, node::Node, series::Series, utils::RandomIter, DEBUG,
+    activation::ActivationFunction,
+    builder::NetworkBuilder,
+    categorize::CatNetwork,
+    checkpoint::TrainState,
+    config::{ConfigField, TrainConfig},
+    dbg_println,
+    error::DarjeelingError,
+    layer::{self, LayerSpec, NodeArray},
+    metrics::{category_index, classifier_accuracy, GenerationReport},
+    neural_network::Model,
+    node::Node,
+    optimizer::OptimizerState,
+    preprocessing::{check_missing, pipeline::Pipeline, Transform},
+    series::Series,
+    split, trainer, DEBUG,
 };
-use rand::Rng;
+use rand::{seq::index, Rng, SeedableRng};
+use rand_distr::{Distribution, Normal};
+use rand_pcg::Pcg64;
 use serde::{Deserialize, Serialize};
-use std::fs;
+use std::borrow::Cow;
+
+/// The distribution the latent vectors a generator turns into data are drawn from
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+pub enum Latent {
+    /// Every value is equally likely to be anywhere from `low` up to `high`
+    Uniform { low: f32, high: f32 },
+    /// Values fall on a bell curve around `mean`
+    Normal { mean: f32, std_dev: f32 },
+}
+
+impl Default for Latent {
+    fn default() -> Latent {
+        Latent::Uniform {
+            low: -1.0,
+            high: 1.0,
+        }
+    }
+}
+
+impl Latent {
+    /// Draws a latent vector of the given width
+    ///
+    /// ## Err
+    /// ### InvalidLatent
+    /// The distribution's parameters don't describe a distribution, see [`check`](Latent::check)
+    pub fn sample(&self, width: usize, rng: &mut impl Rng) -> Result<Box<[f32]>, DarjeelingError> {
+        self.check()?;
+        Ok(match *self {
+            Latent::Uniform { low, high } => (0..width).map(|_| rng.gen_range(low..high)).collect(),
+            Latent::Normal { mean, std_dev } => {
+                let normal = Normal::new(mean, std_dev)
+                    .map_err(|err| DarjeelingError::InvalidLatent(err.to_string()))?;
+                (0..width).map(|_| normal.sample(rng)).collect()
+            }
+        })
+    }
+
+    /// Checks that the uniform range isn't empty, and the normal standard deviation isn't negative
+    ///
+    /// ## Err
+    /// ### InvalidLatent
+    /// The distribution's parameters aren't finite, or don't describe a distribution
+    pub fn check(&self) -> Result<(), DarjeelingError> {
+        let valid = match *self {
+            Latent::Uniform { low, high } => low.is_finite() && high.is_finite() && low < high,
+            Latent::Normal { mean, std_dev } => {
+                mean.is_finite() && std_dev.is_finite() && std_dev >= 0.0
+            }
+        };
+        if valid {
+            Ok(())
+        } else {
+            Err(DarjeelingError::InvalidLatent(format!(
+                "{:?} isn't a distribution",
+                self
+            )))
+        }
+    }
+}
+
+/// Compares rows the generator generates to the real training rows at the end of every epoch of training,
+/// with a [`GenerationReport`] that's printed and kept in [`GenNetwork::reports`].
+///
+/// The rows are generated from the same latent vectors every epoch, so the reports only change as the generator does.
+///
+/// ## Examples
+/// ```ignore
+/// let classifier = CatNetwork::read_model("digits.darj".to_string()).unwrap();
+/// net.set_monitor(Monitor::new(200).classifier(classifier));
+/// net.train_conditional(&digits, categories, &config, &distinguisher, &distinguishing_config).unwrap();
+/// let last = net.reports().last().unwrap();
+/// ```
+#[derive(Debug, Clone)]
+pub struct Monitor {
+    samples: usize,
+    classifier: Option<CatNetwork>,
+}
+
+impl Monitor {
+    /// Generates the given number of rows every epoch, and compares them to as many training rows drawn at random.
+    /// More rows make the metrics more reliable, but the maximum mean discrepancy takes quadratically longer.
+    /// How many training rows there are doesn't make the reports any slower.
+    pub fn new(samples: usize) -> Monitor {
+        Monitor {
+            samples,
+            classifier: None,
+        }
+    }
+
+    /// Also reports how often a trained classifier puts the generated rows in the category they were generated for,
+    /// see [`classifier_accuracy`]. Only networks trained with [`train_conditional`](GenNetwork::train_conditional)
+    /// can be monitored with a classifier, which needs to have been trained on their categories.
+    pub fn classifier(mut self, classifier: CatNetwork) -> Monitor {
+        self.classifier = Some(classifier);
+        self
+    }
+}
 
 /// The generation Neural Network struct
 #[derive(Debug, Serialize, Deserialize)]
 pub struct GenNetwork {
     node_array: Box<[Box<[Node]>]>,
+    layers: Box<[LayerSpec]>,
     activation_function: Option<ActivationFunction>,
+    /// The config the network was last trained with
+    config: Option<TrainConfig>,
+    /// Every input goes through it before it reaches the network
+    pipeline: Option<Pipeline>,
+    /// The distribution the generator's inputs are drawn from, during training and sampling
+    latent: Latent,
+    /// The categories one-hot encoded after the latent vector, if the network was trained conditionally
+    categories: Option<Box<[String]>>,
+    /// Reports on the generated rows every epoch of training, it isn't saved with the model
+    #[serde(skip)]
+    monitor: Option<Monitor>,
+    /// The report of every epoch of the last training run, if the network had a monitor
+    #[serde(skip)]
+    reports: Vec<GenerationReport>,
 }
 #[warn(clippy::unwrap_in_result)]
 impl GenNetwork {
@@ -24,6 +151,8 @@ impl GenNetwork {
     /// - Answer: The number of answer nodes, or possible categories
     /// - Hidden Layers: The number of different hidden layers
     ///
+    /// Every hidden layer has the same width, use a [`NetworkBuilder`] for anything else.
+    ///
     /// ## Examples
     /// ``` rust
     /// use darjeeling::{
@@ -31,11 +160,11 @@ impl GenNetwork {
     ///     generation::GenNetwork
     /// };
     ///
-    /// let inputs: i32 = 10;
-    /// let hidden: i32 = 40;
-    /// let answer: i32 = 2;
-    /// let hidden_layers: i32 = 1;
-    /// let mut net = GenNetwork::new(inputs, hidden, answer, hidden_layers, ActivationFunction::Sigmoid);
+    /// let inputs: usize = 10;
+    /// let hidden: usize = 40;
+    /// let answer: usize = 2;
+    /// let hidden_layers: usize = 1;
+    /// let mut net = GenNetwork::new(inputs, hidden, answer, hidden_layers, Some(ActivationFunction::Sigmoid));
     /// ```
     pub fn new(
         input_nodes: usize,
@@ -44,444 +173,775 @@ impl GenNetwork {
         hidden_layers: usize,
         activation_function: Option<ActivationFunction>,
     ) -> GenNetwork {
-        // links point backwards to previous layer
-        let mut rng = rand::thread_rng();
-
-        let mut node_array: Vec<Box<[Node]>> = vec![];
-
-        let input_row: Box<[Node]> = (0..input_nodes)
-            .map(|_| Node::new(vec![].into_boxed_slice(), 1.0))
-            .collect::<Box<[Node]>>();
-        node_array.push(input_row);
-
-        // links point backwards, so the first layer has a different number of them
-        let first_hidden_layer = (0..hidden_nodes)
-            .into_iter()
-            .map(|_| {
-                Node::new(
-                    vec![rng.gen_range(-0.5..0.5); input_nodes].into_boxed_slice(),
-                    rng.gen_range(-0.5..0.5),
-                )
+        let mut builder = (0..hidden_layers.max(1))
+            .fold(NetworkBuilder::new(input_nodes), |builder, _| {
+                builder.hidden(hidden_nodes)
             })
-            .collect();
-        node_array.push(first_hidden_layer);
-
-        // includes the answer node
-        for _ in 1..hidden_layers {
-            let hidden_vec: Box<[Node]> = (0..hidden_nodes)
-                .into_iter()
-                .map(|_| 
-                    Node::new(
-                        vec![rng.gen_range(-0.5..0.5); hidden_nodes].into_boxed_slice(),
-                        rng.gen_range(-0.5..0.5),
-                    )
-                )
-                .collect();
-            node_array.push(hidden_vec);
+            .hidden(answer_nodes);
+        if let Some(activation_function) = activation_function {
+            builder = builder.activation(activation_function);
         }
 
-        let answer_layer = (0..answer_nodes)
-            .into_iter()
-            .map(|_| Node::new(
-                vec![rng.gen_range(-0.5..0.5); hidden_nodes].into_boxed_slice(),
-                rng.gen_range(-0.5..0.5))).collect();
-
-        node_array.push(answer_layer);
+        builder
+            .build_gen()
+            .expect("Every layer of the network needs at least one node")
+    }
 
+    pub(crate) fn from_parts(
+        node_array: Box<[Box<[Node]>]>,
+        layers: Box<[LayerSpec]>,
+        activation_function: Option<ActivationFunction>,
+    ) -> GenNetwork {
         GenNetwork {
-            node_array: node_array.into_boxed_slice(),
+            node_array,
+            layers,
             activation_function,
+            config: None,
+            pipeline: None,
+            latent: Latent::default(),
+            categories: None,
+            monitor: None,
+            reports: vec![],
         }
     }
 
-    /// Trains a neural model to generate new data formatted as inputs, based on the given data
+    /// The config the network was last trained with, saved models keep it
+    pub fn config(&self) -> Option<&TrainConfig> {
+        self.config.as_ref()
+    }
+
+    /// Sets the pipeline every input goes through before it reaches the network.
+    /// An unfitted pipeline is fitted on the training data the next time the network is trained,
+    /// and it's saved with the model. Pipelines that encode categorical columns can't be used,
+    /// since there are no dataframes to fit them on.
+    pub fn set_pipeline(&mut self, pipeline: Pipeline) {
+        self.pipeline = Some(pipeline);
+    }
+
+    /// The pipeline every input goes through, saved models keep it
+    pub fn pipeline(&self) -> Option<&Pipeline> {
+        self.pipeline.as_ref()
+    }
+
+    /// The distribution latent vectors are drawn from, uniform between -1 and 1 unless it's been set
+    pub fn latent(&self) -> Latent {
+        self.latent
+    }
+
+    /// Sets the distribution latent vectors are drawn from, during training and sampling.
+    /// It's saved with the model, so a trained generator keeps sampling from the distribution it learned from.
+    ///
+    /// ## Err
+    /// ### InvalidLatent
+    /// The distribution's parameters don't describe a distribution
+    pub fn set_latent(&mut self, latent: Latent) -> Result<(), DarjeelingError> {
+        latent.check()?;
+        self.latent = latent;
+        Ok(())
+    }
+
+    /// Trains the network as the generator of a generative adversarial network, so it turns latent vectors drawn from its [`Latent`] distribution into data like the given data.
+    ///
+    /// Every batch, the distinguishing model is first trained to tell real inputs from generated ones,
+    /// then the generator is trained to fool it, with the gradient of the distinguishing model's verdict
+    /// carried back through the distinguishing model and into the generator.
+    /// The distinguishing model only exists for the length of the training run.
+    /// A network with a [`Monitor`] compares its generated rows to the data at the end of every epoch.
     ///
     /// ## Params
-    /// - Data: List of inputs to be trained on
-    /// - Learning Rate: The modifier that is applied to link weights as they're adjusted.
-    /// Try fiddling with this one, but -1.5 - 1.5 is recommended to start.
-    /// - Name: The model name
-    /// - Max Cycles: The maximum number of epochs the training will run for.
-    /// - Distinguishing Learning Rate: The learning rate for the distinguishing model.
-    /// - Distinguishing Hidden Neurons: The number of hidden neurons in each layer of the distinguishing model.
-    /// - Distinguising Hidden Layers: The number of hidden layers in the distinguishing model.
-    /// - Distinguishing Activation: The activation function of the distinguishing model.
-    /// - Distinguishing Target Error Percent: The error percentage at which the distinguishing models will stop training.
+    /// - Data: List of inputs to be trained on, as wide as this network's answer layer once they've been through the pipeline
+    /// - Config: The hyperparameters of the generator, see [`TrainConfig`].
+    ///   Its batch size is the number of real and generated inputs in each batch.
+    ///   The model is written to the config's save path once training finishes, if it has one.
+    /// - Distinguisher: The layers of the distinguishing model.
+    ///   Its input layer must be as wide as this network's answer layer, and its answer layer must have one node,
+    ///   which always uses the sigmoid function and is the probability that the input is real.
+    /// - Distinguishing Config: The learning rate, schedule and optimizer of the distinguishing model.
+    ///   Its epochs are the number of times it's trained on each batch before the generator is, usually one.
     ///
     /// ## Returns
-    /// The fallible name of the model that this neural network trained
+    /// The fallible save path the model was written to, none if the config has no save path
     ///
     /// ## Err
     /// ### WriteModelFailed
     /// There was a problem when saving the model to a file
     ///
-    /// ### ModelNameAlreadyExists
-    /// The random model name chosen already exists
-    /// Change the name or retrain
+    /// ### UnsupportedConfig
+    /// The config has early stopping, a target accuracy, checkpoints, class weights or a loss other than the default,
+    /// or the distinguishing config sets anything but its learning rate, schedule, optimizer and epochs
     ///
-    /// ### RemoveModelFailed
-    /// Every time a new distinguishing model is written to the project folder, the previous one has to be removed.
-    /// This removal failed,
+    /// ### InvalidLayerSpec
+    /// The distinguisher's layers or the data don't fit this network
     ///
-    /// ### DistinguishingModel
-    /// The distinguishing model training failed.
+    /// ### NanEncountered
+    /// An input has a missing (NaN) value, or the training diverged
     ///
-    /// ### UnknownError
-    /// Not sure what happened, but something failed
+    /// ### DistinguishingModel
+    /// The distinguishing model has no activation function
     ///
-    /// Make an issue on the [darjeeling](https://github.com/Ewie21/darjeeling) GitHub page
-    /// Or contact me at elocolburn@comcast.net
+    /// ### ModelMissingCategories
+    /// The network's monitor has a classifier
     ///
-    /// ## TODO: Refactor to pass around the neural net, not the model name
+    /// ### InvalidMetricInput
+    /// The network has a monitor, and a generated row has a NaN or infinite value
     ///
     /// ## Examples
     /// ```ignore
     /// use darjeeling::{
-    ///     generation::GenNetwork,
     ///     activation::ActivationFunction,
-    ///     input::Input,
+    ///     builder::NetworkBuilder,
+    ///     config::TrainConfig,
+    ///     generation::GenNetwork,
     ///     // This file may not be avaliable
     ///     // Everything found here will be hyper-specific to your project.
-    ///     tests::{categories_str_format, file}
+    ///     tests::gen_data_file
     /// };
     ///
     /// // A file with data
     /// // To make sure the networked is properly trained, make sure it follows some sort of pattern
-    /// // This is just sample data, for accurate results, around 3800 datapoints is needed
     /// // 1 2 3 4 5 6 7 8
     /// // 3 2 5 4 7 6 1 8
     /// // 0 2 5 4 3 6 1 8
     /// // 7 2 3 4 9 6 1 8
-    /// // You also need to write the file input function
-    /// // Automatic file reading and formatting function coming soon
-    /// let mut data: Vec<Input> = file();
-    /// let mut net = GenNetwork::new(2, 2, 2, 1, ActivationFunction::Sigmoid);
-    /// let model_name: String = net.learn(&mut data, 0.5, "gen", 100, 0.5, 10, 1, ActivationFunction::Sigmoid, 99.0).unwrap();
-    /// let new_data: Vec<Input> = net.test(data).unwrap();
+    /// let data: Box<[Box<[f32]>]> = gen_data_file();
+    /// // Turns 4 numbers of noise into 8 numbers of data
+    /// let mut net = GenNetwork::new(4, 16, 8, 1, Some(ActivationFunction::Sigmoid));
+    /// let distinguisher = NetworkBuilder::new(8)
+    ///     .activation(ActivationFunction::Sigmoid)
+    ///     .hidden(10)
+    ///     .hidden(1);
+    /// let config = TrainConfig { batch_size: 16, save_path: Some("gen.darj".to_string()), ..Default::default() };
+    /// let distinguishing_config = TrainConfig { epochs: 1, ..Default::default() };
+    /// let model_name = net.train(&data, &config, &distinguisher, &distinguishing_config).unwrap();
     /// ```
     pub fn train(
-        // Frankly this whole function is disgusting and needs to be burned; I concur from the future
         &mut self,
-        data: &Box<[Box<[f32]>]>,
-        learning_rate: f32,
-        name: &str,
-        max_cycles: usize,
-        distinguising_learning_rate: f32,
-        distinguising_hidden_neurons: usize,
-        distinguising_hidden_layers: usize,
-        distinguising_activation: ActivationFunction,
-        distinguishing_target_err_percent: f32,
-    ) -> Result<String, DarjeelingError> {
-        let mut epochs: f32 = 0.0;
-        let mut distinguishing_model = CatNetwork::new(
-            self.node_array.last().expect("Network has no layers").len(),
-            distinguising_hidden_neurons,
-            2,
-            distinguising_hidden_layers,
-            Some(distinguising_activation),
-        );
-        let activation_function = self.activation_function.unwrap();
-
-        let mut outputs: Vec<Box<[f32]>> = vec![];
-        for _ in 0..max_cycles {
-            let data_iter = RandomIter::new(data);
-
-            // Train generation network
-            for line in data_iter {
-                dbg_println!("Training Checkpoint One Passed");
-                self.push_downstream(line, activation_function);
-
-                let answer_layer = self.node_array.last().expect("Network has no layers");
-                outputs.push(
-                    answer_layer
-                        .iter()
-                        .map(|node| {
-                            node.cached_output
-                                .expect("Answer layer node has no cached output")
-                        })
-                        .collect::<Box<[f32]>>(),
-                );
-            }
-
-            let series_data: Box<[Series]> = data
-                .into_iter()
-                .map(|line| Series::new(line.clone(), ""))
-                .collect();
-
-            let _mse: f32 = match distinguishing_model.train(
-                &series_data,
-                vec!["real".to_string(), "generated".to_string()].into_boxed_slice(),
-                distinguising_learning_rate,
-                &("distinguishing".to_owned() + &name),
-                distinguishing_target_err_percent,
-                false,
-            ) {
-                Ok((_name, _err_percent, errmse)) => errmse,
-                Err(error) => return Err(error),
-            };
-
-            self.backpropogate(learning_rate, activation_function);
-            epochs += 1.0;
-            println!("Epoch: {:?}", epochs);
-        }
-
-        Ok(self.write_model(&name)?)
+        data: &[Box<[f32]>],
+        config: &TrainConfig,
+        distinguisher: &NetworkBuilder,
+        distinguishing_config: &TrainConfig,
+    ) -> Result<Option<String>, DarjeelingError> {
+        check_unused(config, distinguishing_config)?;
+        self.categories = None;
+        self.train_from(data, &[], config, distinguisher, distinguishing_config)
     }
 
-    pub fn test(&mut self, data: &Box<[Box<[f32]>]>) -> Result<Box<[Box<[f32]>]>, DarjeelingError> {
-        let data_iter = RandomIter::new(data);
-        let activation_function = self.activation_function.unwrap();
-
-        Ok(data_iter
-            .map(|line| {
-                self.push_downstream(line, activation_function);
-                self.node_array
-                    .last()
-                    .expect("Network has no layers")
-                    .iter()
-                    .map(|node| {
-                        node.cached_output
-                            .expect("Answer layer node does not have cached output")
-                    })
-                    .collect()
-            })
-            .collect())
+    /// Trains the network as the generator of a conditional generative adversarial network,
+    /// so it generates data like the inputs of whichever category it's asked for, see [`sample_conditional`](GenNetwork::sample_conditional).
+    ///
+    /// The category is one-hot encoded and appended to the generator's latent vector and to the distinguishing model's input,
+    /// so the generator's input layer has a node for each category after its latent nodes,
+    /// and the distinguishing model's input layer has one for each category after a node for each value of the data.
+    /// Otherwise training is the same as [`train`](GenNetwork::train).
+    ///
+    /// ## Params
+    /// - Data: List of inputs to be trained on, each with its category as its answer
+    /// - Categories: List of Strings, each denoting a category the network can be asked to generate
+    /// - Config: The hyperparameters of the generator
+    /// - Distinguisher: The layers of the distinguishing model
+    /// - Distinguishing Config: The hyperparameters of the distinguishing model
+    ///
+    /// ## Returns
+    /// The fallible save path the model was written to, none if the config has no save path
+    ///
+    /// ## Err
+    /// The errors of [`train`](GenNetwork::train), and
+    /// ### CategoryDoesNotExist
+    /// An input's answer isn't one of the categories
+    ///
+    /// ## Examples
+    /// ```ignore
+    /// let categories: Box<[String]> = (0..10).map(|digit| digit.to_string()).collect();
+    /// // 8 latent nodes and 10 category nodes turn into 64 pixels
+    /// let mut net = GenNetwork::new(18, 64, 64, 1, Some(ActivationFunction::Sigmoid));
+    /// let distinguisher = NetworkBuilder::new(74)
+    ///     .activation(ActivationFunction::Sigmoid)
+    ///     .hidden(32)
+    ///     .hidden(1);
+    /// net.train_conditional(&digits, categories, &config, &distinguisher, &distinguishing_config).unwrap();
+    /// let sevens = net.sample_conditional("7", 10, &mut rand::thread_rng()).unwrap();
+    /// ```
+    pub fn train_conditional(
+        &mut self,
+        data: &[Series],
+        categories: Box<[String]>,
+        config: &TrainConfig,
+        distinguisher: &NetworkBuilder,
+        distinguishing_config: &TrainConfig,
+    ) -> Result<Option<String>, DarjeelingError> {
+        check_unused(config, distinguishing_config)?;
+        let labels: Box<[usize]> = data
+            .iter()
+            .map(|series| category_index(&categories, &series.answer))
+            .collect::<Result<_, _>>()?;
+        let rows: Box<[Box<[f32]>]> = data.iter().map(|series| series.data.clone()).collect();
+        self.categories = Some(categories);
+        self.train_from(&rows, &labels, config, distinguisher, distinguishing_config)
     }
 
-    /// Passes in data to the sensors, pushs data 'downstream' through the network
-    fn push_downstream(&mut self, data: &Box<[f32]>, activation_function: ActivationFunction) {
-        // Pass data to the input layer
-        self.node_array.first_mut().expect("Neural Network has no layers")
-            .iter_mut().enumerate().for_each(|(i, node)| {
-                node.cached_output = Some(data[i]);
-            });
-
-        // Push forward hidden and output layers
-        for layer_i in 1..self.node_array.len() {
-            // Clone the cached outputs from the previous layer
-            let prev_cached_outputs: Box<[f32]> = self.node_array[layer_i - 1]
-                .iter()
-                .map(|node| {
-                    node.cached_output
-                        .expect("Previous nodes do not have cached outputs")
-                })
-                .collect();
+    /// Sets the monitor that reports on the generated rows at the end of every epoch of training
+    pub fn set_monitor(&mut self, monitor: Monitor) {
+        self.monitor = Some(monitor);
+    }
 
-            let layer = self.node_array[layer_i].iter_mut();
-
-            layer.for_each(|node| {
-                prev_cached_outputs
-                    .iter()
-                    .enumerate()
-                    .for_each(|(prev_node_i, prev_output)| {
-                        assert_eq!(prev_cached_outputs.len(), node.link_vals.len());
-                        node.link_vals[prev_node_i] = *prev_output;
-                        node.cached_output = Some(node.output(activation_function));
-                    });
-            });
-        }
+    /// The report of every epoch of the last training run, empty if the network had no monitor
+    pub fn reports(&self) -> &[GenerationReport] {
+        &self.reports
     }
 
-    /// Analyses the chosen answer node's result.
-    /// Also increments sum and count
-    fn self_analysis(
-        &self,
-        epochs: &mut Option<f32>,
-        sum: &mut f32,
-        count: &mut f32,
-        series: &Series,
-        mse: &mut f32,
-    ) -> (String, Option<f32>) {
-        dbg_println!("answer {}", self.node_array.len() - 1);
-        dbg_println!("largest index {}", self.largest_node());
-        dbg_println!("{:?}", self);
-
-        let answer_layer = self.node_array.last().expect("Network has no layer");
-        let brightest_node: &Node = &answer_layer[self.largest_node()];
-        let brightness: f32 = brightest_node.cached_output.unwrap();
-
-        dbg_println!(
-            "Category: {:?} \nBrightness: {:?}",
-            brightest_node.category.as_ref().unwrap(),
-            brightness
-        );
+    /// The categories the network was trained to generate, if it was trained conditionally
+    pub fn categories(&self) -> Option<&[String]> {
+        self.categories.as_deref()
+    }
 
-        if brightest_node.category.clone().unwrap() == series.answer {
-            dbg_println!("Sum++");
-            *sum += 1.0;
+    /// Runs the adversarial training loop, conditioning every input on its category if the network has categories
+    fn train_from(
+        &mut self,
+        data: &[Box<[f32]>],
+        labels: &[usize],
+        config: &TrainConfig,
+        distinguisher: &NetworkBuilder,
+        distinguishing_config: &TrainConfig,
+    ) -> Result<Option<String>, DarjeelingError> {
+        let activation_function = match self.activation_function {
+            Some(s) => s,
+            None => return Err(DarjeelingError::ModelMissingActivationFunction),
+        };
+        let answer_nodes = self.node_array.last().expect("Network has no layers").len();
+        let category_count = self.one_hot(None).len();
+        if distinguisher.input_nodes() != answer_nodes + category_count
+            || distinguisher.layers().last().map(|layer| layer.width) != Some(1)
+        {
+            return Err(DarjeelingError::InvalidLayerSpec(format!(
+                "the distinguishing model needs {} input nodes and 1 answer node",
+                answer_nodes + category_count
+            )));
+        }
+        if category_count == 0
+            && self
+                .monitor
+                .as_ref()
+                .map_or(false, |monitor| monitor.classifier.is_some())
+        {
+            return Err(DarjeelingError::ModelMissingCategories);
+        }
+        if self.latent_width() == 0 {
+            return Err(DarjeelingError::InvalidLayerSpec(format!(
+                "the input layer needs a latent node as well as a node for each of the {} categories",
+                category_count
+            )));
+        }
+        if let Some(pipeline) = self
+            .pipeline
+            .as_mut()
+            .filter(|pipeline| !pipeline.is_fitted())
+        {
+            pipeline.fit_rows(&data.iter().map(|row| row.as_ref()).collect::<Vec<&[f32]>>())?;
+        }
+        let raw = data;
+        let data = self.preprocess(data)?;
+        let data: &[Box<[f32]>] = &data;
+        check_missing(&data.iter().map(|row| row.as_ref()).collect::<Vec<&[f32]>>())?;
+        if let Some(row) = data.iter().find(|row| row.len() != answer_nodes) {
+            return Err(DarjeelingError::InvalidLayerSpec(format!(
+                "the answer layer has {} nodes, but an input has {} values",
+                answer_nodes,
+                row.len()
+            )));
         }
 
-        *count += 1.0;
+        let mut distinguishing_model = Discriminator::new(distinguisher)?;
+        let mut state = TrainState::new(config.seed);
+        let batch_size = config.batch_size.max(1);
+        self.reports = vec![];
+
+        for epoch in 0..config.epochs {
+            let learning_rate = config.learning_rate_at(epoch);
+            let distinguishing_learning_rate = distinguishing_config.learning_rate_at(epoch);
+            let mut distinguishing_loss = 0.0;
+            let mut generating_loss = 0.0;
+
+            let mut order: Vec<usize> = (0..data.len()).collect();
+            split::shuffle(&mut order, &mut state.rng);
+            for batch in order.chunks(batch_size) {
+                // Teach the distinguishing model to tell real inputs from generated ones,
+                // generating an input of the same category as each real one
+                for _ in 0..distinguishing_config.epochs.max(1) {
+                    for i in batch {
+                        let category = labels.get(*i).copied();
+                        let real = self.conditioned(&data[*i], category);
+                        distinguishing_loss +=
+                            distinguishing_model.learn(&real, 1.0, &mut state.rng);
+                        let generated =
+                            self.generate(activation_function, false, category, &mut state.rng)?;
+                        distinguishing_loss += distinguishing_model.learn(
+                            &self.conditioned(&generated, category),
+                            0.0,
+                            &mut state.rng,
+                        );
+                    }
+                    distinguishing_model.step(
+                        distinguishing_config,
+                        distinguishing_learning_rate,
+                        batch.len() * 2,
+                    );
+                }
 
-        match epochs {
-            Some(epochs) => {
-                // This won't happen during testing
-                if *epochs % 10.0 != 0.0 || *epochs == 0.0 {
-                    return (brightest_node.category.clone().unwrap(), None);
+                // Teach the generator to make inputs the distinguishing model thinks are real
+                for i in batch {
+                    let category = labels.get(*i).copied();
+                    let generated =
+                        self.generate(activation_function, true, category, &mut state.rng)?;
+                    let (loss, input_grads) = distinguishing_model
+                        .fool(&self.conditioned(&generated, category), &mut state.rng);
+                    generating_loss += loss;
+                    // The category's part of the gradient has nothing to flow back into
+                    self.backpropogate(&input_grads[..answer_nodes], activation_function);
                 }
-                println!("\n-------------------------\n");
-                println!("Epoch: {:?}", epochs);
-                println!(
-                    "Category: {:?} \nBrightness: {:?}",
-                    brightest_node.category.as_ref().unwrap(),
-                    brightness
+                state.optimizer_state.step(
+                    config.optimizer,
+                    &mut self.node_array,
+                    learning_rate,
+                    batch.len(),
                 );
-                if DEBUG {
-                    let dimest_node: &Node =
-                        &answer_layer[answer_layer.len() - self.largest_node() - 1];
-                    println!(
-                        "Chosen category: {:?} \nDimest Brightness: {:?}",
-                        dimest_node.category.as_ref().unwrap(),
-                        dimest_node.cached_output.unwrap()
-                    );
-                }
-                (brightest_node.category.clone().unwrap(), None)
             }
-            None => (
-                brightest_node.category.clone().unwrap(),
-                Some(CatNetwork::calculate_err_for_generation_model(
-                    mse,
-                    brightest_node,
-                )),
-            ),
+
+            generating_loss /= data.len().max(1) as f32;
+            // Every pass scores each real input and a generated one
+            distinguishing_loss /=
+                (2 * data.len() * distinguishing_config.epochs.max(1)).max(1) as f32;
+            if generating_loss.is_nan() || distinguishing_loss.is_nan() {
+                return Err(DarjeelingError::NanEncountered(format!(
+                    "the loss of epoch {} is NaN, try a lower learning rate",
+                    epoch + 1
+                )));
+            }
+            state.epoch += 1;
+            println!("Epoch: {:?}", state.epoch);
+            dbg_println!(
+                "Distinguishing Loss: {:?} Generating Loss: {:?}",
+                distinguishing_loss,
+                generating_loss
+            );
+
+            if let Some(mut monitor) = self.monitor.take() {
+                let report = self.report(&mut monitor, raw, labels);
+                self.monitor = Some(monitor);
+                let report = report?;
+                println!("{}", report);
+                self.reports.push(report);
+            }
         }
-    }
 
-    fn calculate_err_for_generation_model(mse: &mut f32, node: &Node) -> f32 {
-        *mse += f32::powi(
-            node.correct_answer.unwrap() - node.cached_output.unwrap(),
-            2,
-        );
-        *mse
+        self.config = Some(config.clone());
+        trainer::save(self, config)
     }
 
-    /// Finds the index and the brightest node in an array and returns it
-    fn largest_node(&self) -> usize {
-        let mut largest_index = 0;
-        let answer_layer = self.node_array.last().expect("Network has no layers");
-        for (i, node) in answer_layer.iter().enumerate() {
-            if node.cached_output > answer_layer[largest_index].cached_output {
-                largest_index = i;
-            }
+    /// Generates an output from every latent vector, each as wide as the input layer.
+    /// The latent vectors of a network trained conditionally end with their one-hot category.
+    /// Outputs as wide as the pipeline's output are mapped back with
+    /// [`Pipeline::inverse_transform_row`], so they're on the same scale as the raw training data.
+    ///
+    /// ## Err
+    /// ### ModelMissingActivationFunction
+    /// The network has no activation function
+    /// ### InvalidLatent
+    /// A latent vector isn't as wide as the input layer
+    /// ### TransformFailed
+    /// An output couldn't be mapped back through the pipeline
+    pub fn test(&mut self, latent: &[Box<[f32]>]) -> Result<Box<[Box<[f32]>]>, DarjeelingError> {
+        let mut rng = rand::thread_rng();
+        let activation_function = match self.activation_function {
+            Some(s) => s,
+            None => return Err(DarjeelingError::ModelMissingActivationFunction),
+        };
+        let width = self.node_array[0].len();
+        if let Some(line) = latent.iter().find(|line| line.len() != width) {
+            return Err(DarjeelingError::InvalidLatent(format!(
+                "the input layer has {} nodes, but a latent vector has {} values",
+                width,
+                line.len()
+            )));
         }
 
-        largest_index
-    }
-    /// Goes back through the network adjusting the weights of the all the neurons based on their error signal
-    fn backpropogate(&mut self, learning_rate: f32, activation_function: ActivationFunction) {
-        let hidden_layers = self.node_array.len() - 2;
-
-        self.node_array
-            .last_mut()
-            .expect("Network has no layers")
-            .iter_mut()
-            .for_each(|node| {
-                node.compute_answer_err_sig(activation_function);
-
-                dbg_println!("Answer Node(Post Error Calc): {:?}", node);
-            });
-
-        self.adjust_hidden_weights(learning_rate, hidden_layers);
-
-        self.node_array
-            .last_mut()
-            .expect("Network has no layers")
-            .iter_mut()
-            .for_each(|node| node.adjust_weights(learning_rate));
-    }
-
-    /// Adjusts the weights of all the hidden neurons in a network
-    fn adjust_hidden_weights(&mut self, learning_rate: f32, hidden_layers: usize) {
-        for layer in 1..=hidden_layers {
-            for node in 0..self.node_array[layer].len() {
-                self.node_array[layer][node].err_sig = Some(0.0);
-                // link weights, err sigs
-                let mut next_layer: Box<[(f32, f32)]> = self.node_array[layer + 1]
-                    .iter()
-                    .map(|next_node| {
-                        (
-                            next_node.link_weights[node],
-                            next_node.err_sig.unwrap_or(0.00),
-                        )
-                    })
-                    .collect::<Box<[(f32, f32)]>>();
-
-                next_layer.iter_mut().for_each(|(next_weight, err_sig)| {
-                    // This changes based on the activation function
-                    self.node_array[layer][node].err_sig =
-                        Some(*err_sig + (*err_sig * *next_weight));
-
-                    dbg_println!("next err sig {:?}", err_sig);
-                    dbg_println!("next weight {:?}", next_weight);
-                });
-
-                let hidden_result = self.node_array[layer][node].cached_output.unwrap();
-                let multiplied_value = self.node_array[layer][node].err_sig.unwrap()
-                    * (hidden_result)
-                    * (1.0 - hidden_result);
-
-                dbg_println!("New hidden errsig multiply: {:?}", multiplied_value);
-
-                self.node_array[layer][node].err_sig = Some(multiplied_value);
-
-                dbg_println!("\nLayer: {:?}", layer);
-                dbg_println!("Node: {:?}", node);
-
-                self.node_array[layer][node].adjust_weights(learning_rate);
-            }
-        }
+        latent
+            .iter()
+            .map(|line| {
+                self.push_downstream(line, activation_function, false, &mut rng);
+                let output = layer::answer_outputs(&self.node_array);
+                match &self.pipeline {
+                    Some(pipeline) if pipeline.is_fitted() => {
+                        pipeline.inverse_transform_row(&output)
+                    }
+                    _ => Ok(output),
+                }
+            })
+            .collect()
     }
 
-    /// Serializes a trained model so it can be used later
+    /// Generates new data from latent vectors drawn from the network's [`Latent`] distribution.
+    /// A network trained conditionally generates each row from a random category.
+    ///
+    /// ## Params
+    /// - N: The number of rows to generate
+    /// - Rng: Draws the latent vectors, seed it to generate the same rows again
     ///
     /// ## Returns
-    /// The name of the model
+    /// The generated rows, on the same scale as the raw training data
     ///
     /// ## Err
-    /// ### WriteModelFailed:
-    /// Wraps the models name
-    /// ### UnknownError:
-    /// Wraps error
-    ///  
-    pub fn write_model(&mut self, name: &str) -> Result<String, DarjeelingError> {
-        let bin = match bincode::serialize(self) {
-            Ok(v) => v,
-            Err(err) => return Err(DarjeelingError::SaveModelFailed(err.to_string())),
-        };
-
-        let mut rng = rand::thread_rng();
-        let num = rng.gen_range(0..i32::MAX);
+    /// The errors of [`test`](GenNetwork::test)
+    ///
+    /// ## Examples
+    /// ```ignore
+    /// use rand::SeedableRng;
+    /// use rand_pcg::Pcg64;
+    ///
+    /// let mut net = GenNetwork::read_model("gen.darj".to_string()).unwrap();
+    /// let rows = net.sample(100, &mut Pcg64::seed_from_u64(42)).unwrap();
+    /// ```
+    pub fn sample(
+        &mut self,
+        n: usize,
+        rng: &mut impl Rng,
+    ) -> Result<Box<[Box<[f32]>]>, DarjeelingError> {
+        let category_count = self.one_hot(None).len();
+        let latent = (0..n)
+            .map(|_| {
+                let category = (category_count > 0).then(|| rng.gen_range(0..category_count));
+                self.latent
+                    .sample(self.latent_width(), rng)
+                    .map(|latent| self.conditioned(&latent, category))
+            })
+            .collect::<Result<Box<[Box<[f32]>]>, DarjeelingError>>()?;
+        self.test(&latent)
+    }
 
-        let model_name = format!("{}_{}.darj", name, num);
-        match fs::write(name, bin) {
-            Ok(_) => Ok(model_name),
-            Err(err) => Err(DarjeelingError::SaveModelFailed(err.to_string())),
-        }
+    /// Generates new data of a single category with a network trained by [`train_conditional`](GenNetwork::train_conditional)
+    ///
+    /// ## Params
+    /// - Category: The category to generate, one of the categories the network was trained with
+    /// - N: The number of rows to generate
+    /// - Rng: Draws the latent vectors, seed it to generate the same rows again
+    ///
+    /// ## Err
+    /// The errors of [`test`](GenNetwork::test), and
+    /// ### ModelMissingCategories
+    /// The network wasn't trained conditionally
+    /// ### CategoryDoesNotExist
+    /// The category isn't one of the network's categories
+    pub fn sample_conditional(
+        &mut self,
+        category: &str,
+        n: usize,
+        rng: &mut impl Rng,
+    ) -> Result<Box<[Box<[f32]>]>, DarjeelingError> {
+        let categories = self
+            .categories
+            .as_ref()
+            .ok_or(DarjeelingError::ModelMissingCategories)?;
+        let category = Some(category_index(categories, category)?);
+        let latent = (0..n)
+            .map(|_| {
+                self.latent
+                    .sample(self.latent_width(), rng)
+                    .map(|latent| self.conditioned(&latent, category))
+            })
+            .collect::<Result<Box<[Box<[f32]>]>, DarjeelingError>>()?;
+        self.test(&latent)
     }
 
-    /// Reads a file containing a serizalized Categorization Network
+    /// Generates the data along the straight line between two latent vectors, to explore what the generator has learned
     ///
     /// ## Params
-    /// - Model Name: The name(or more helpfully the path) of the model to be read
+    /// - A: The latent vector the line starts at
+    /// - B: The latent vector the line ends at
+    /// - Steps: The number of evenly spaced points on the line, including both ends
     ///
     /// ## Returns
-    /// A neural network read from a serialized .darj file
+    /// The data generated from each point, starting with A's and ending with B's
     ///
     /// ## Err
-    /// If the file cannnot be read, or if the file does not contain a valid serialized Neural Network
-    pub fn read_model(model_name: String) -> Result<GenNetwork, DarjeelingError> {
-        let read_bin = match fs::read(&model_name) {
-            Ok(bin) => bin,
-            Err(err) => return Err(DarjeelingError::LoadModelFailed(err.to_string())),
-        };
+    /// The errors of [`test`](GenNetwork::test), and
+    /// ### InvalidLatent
+    /// There are fewer than two steps
+    pub fn interpolate(
+        &mut self,
+        a: &[f32],
+        b: &[f32],
+        steps: usize,
+    ) -> Result<Box<[Box<[f32]>]>, DarjeelingError> {
+        if steps < 2 {
+            return Err(DarjeelingError::InvalidLatent(format!(
+                "an interpolation needs at least 2 steps for its ends, not {}",
+                steps
+            )));
+        }
+        if a.len() != b.len() {
+            return Err(DarjeelingError::InvalidLatent(format!(
+                "the latent vectors have {} and {} values",
+                a.len(),
+                b.len()
+            )));
+        }
+
+        let latent: Box<[Box<[f32]>]> = (0..steps)
+            .map(|step| {
+                let t = step as f32 / (steps - 1) as f32;
+                a.iter()
+                    .zip(b.iter())
+                    .map(|(from, to)| from + t * (to - from))
+                    .collect()
+            })
+            .collect();
+        self.test(&latent)
+    }
 
-        match bincode::deserialize(&read_bin) {
-            Ok(net) => Ok(net),
-            Err(err) => Err(DarjeelingError::LoadModelFailed(err.to_string())),
+    /// Runs inputs through the pipeline, if the network has one
+    fn preprocess<'a>(
+        &self,
+        data: &'a [Box<[f32]>],
+    ) -> Result<Cow<'a, [Box<[f32]>]>, DarjeelingError> {
+        match &self.pipeline {
+            Some(pipeline) => Ok(Cow::Owned(pipeline.transform_rows(data)?.into_vec())),
+            None => Ok(Cow::Borrowed(data)),
         }
     }
 
+    /// Compares rows generated from the monitor's latent vectors to as many raw real rows, drawn at random.
+    /// A network trained conditionally generates rows of each category as often as the drawn real rows have it.
+    pub(crate) fn report(
+        &mut self,
+        monitor: &mut Monitor,
+        real: &[Box<[f32]>],
+        labels: &[usize],
+    ) -> Result<GenerationReport, DarjeelingError> {
+        let mut rng = Pcg64::seed_from_u64(0);
+        // The same real rows are drawn every epoch, and how long drawing them takes doesn't depend on how many there are
+        let drawn = index::sample(&mut rng, real.len(), monitor.samples.min(real.len())).into_vec();
+        let real: Box<[Box<[f32]>]> = drawn.iter().map(|i| real[*i].clone()).collect();
+        let categories: Box<[Option<usize>]> = (0..monitor.samples)
+            .map(|i| match drawn.len() {
+                0 => None,
+                len => labels.get(drawn[i % len]).copied(),
+            })
+            .collect();
+        let latent = categories
+            .iter()
+            .map(|category| {
+                self.latent
+                    .sample(self.latent_width(), &mut rng)
+                    .map(|latent| self.conditioned(&latent, *category))
+            })
+            .collect::<Result<Box<[Box<[f32]>]>, DarjeelingError>>()?;
+        let generated = self.test(&latent)?;
+
+        let mut report = GenerationReport::new(&real, &generated)?;
+        if let (Some(classifier), Some(names)) = (monitor.classifier.as_mut(), &self.categories) {
+            let series: Box<[Series]> = generated
+                .iter()
+                .zip(categories.iter())
+                .filter_map(|(row, category)| {
+                    category.map(|category| Series::new(row.clone(), &names[category]))
+                })
+                .collect();
+            report.classifier_accuracy = Some(classifier_accuracy(classifier, &series)?);
+        }
+        Ok(report)
+    }
+
+    /// Generates an output from a latent vector drawn from the network's distribution, conditioned on the category
+    fn generate(
+        &mut self,
+        activation_function: ActivationFunction,
+        training: bool,
+        category: Option<usize>,
+        rng: &mut impl Rng,
+    ) -> Result<Box<[f32]>, DarjeelingError> {
+        let noise = self.latent.sample(self.latent_width(), rng)?;
+        let noise = self.conditioned(&noise, category);
+        self.push_downstream(&noise, activation_function, training, rng);
+        Ok(layer::answer_outputs(&self.node_array))
+    }
+
+    /// The number of input nodes the latent vector fills, the rest are the one-hot category
+    fn latent_width(&self) -> usize {
+        self.node_array[0]
+            .len()
+            .saturating_sub(self.one_hot(None).len())
+    }
+
+    /// A one for the category and a zero for every other one, empty if the network has no categories
+    fn one_hot(&self, category: Option<usize>) -> Box<[f32]> {
+        let count = self
+            .categories
+            .as_ref()
+            .map_or(0, |categories| categories.len());
+        (0..count)
+            .map(|i| if Some(i) == category { 1.0 } else { 0.0 })
+            .collect()
+    }
+
+    /// The values with the one-hot category appended
+    fn conditioned(&self, values: &[f32], category: Option<usize>) -> Box<[f32]> {
+        values
+            .iter()
+            .copied()
+            .chain(self.one_hot(category).iter().copied())
+            .collect()
+    }
+
+    /// Passes in data to the sensors, pushs data 'downstream' through the network
+    fn push_downstream(
+        &mut self,
+        data: &[f32],
+        activation_function: ActivationFunction,
+        training: bool,
+        rng: &mut impl Rng,
+    ) {
+        layer::push_downstream(
+            &mut self.node_array,
+            &self.layers,
+            activation_function,
+            data,
+            training,
+            rng,
+        );
+    }
+
+    /// Goes back through the network computing the error signal of all the neurons,
+    /// from the gradient the distinguishing model gave each answer node's output
+    fn backpropogate(&mut self, output_grads: &[f32], activation_function: ActivationFunction) {
+        layer::backpropogate(
+            &mut self.node_array,
+            &self.layers,
+            activation_function,
+            output_grads,
+        );
+        layer::accumulate_grads(&mut self.node_array, &self.layers);
+    }
+
     pub fn set_activation_func(&mut self, new_activation_function: ActivationFunction) {
         self.activation_function = Some(new_activation_function);
     }
 }
+
+impl Model for GenNetwork {}
+
+/// Checks that neither config sets anything adversarial training would ignore
+fn check_unused(
+    config: &TrainConfig,
+    distinguishing_config: &TrainConfig,
+) -> Result<(), DarjeelingError> {
+    let unused = [
+        ConfigField::EarlyStopping,
+        ConfigField::TargetAccuracy,
+        ConfigField::Checkpoint,
+        ConfigField::ClassWeights,
+        ConfigField::Loss,
+    ];
+    config.check_unused("generative adversarial training", &unused)?;
+    distinguishing_config.check_unused(
+        "the distinguishing model",
+        &[&unused[..], &[ConfigField::Seed, ConfigField::SavePath]].concat(),
+    )
+}
+
+/// Keeps the distinguishing model's loss finite when it's completely sure and wrong
+const PROBABILITY_EPSILON: f32 = 1e-7;
+
+/// The distinguishing model of a generative adversarial network, kept in memory for the length of a training run.
+/// Its single answer node is the probability that its input is real.
+struct Discriminator {
+    node_array: NodeArray,
+    layers: Box<[LayerSpec]>,
+    activation_function: ActivationFunction,
+    optimizer_state: OptimizerState,
+}
+
+impl Discriminator {
+    fn new(builder: &NetworkBuilder) -> Result<Discriminator, DarjeelingError> {
+        let activation_function = builder.activation_function().ok_or_else(|| {
+            DarjeelingError::DisinguishingModelError(
+                "the distinguishing model has no activation function".to_string(),
+            )
+        })?;
+        let (node_array, mut layers) = builder.build_parts()?;
+        if let Some(answer) = layers.last_mut() {
+            answer.activation = Some(ActivationFunction::Sigmoid);
+        }
+
+        Ok(Discriminator {
+            node_array,
+            layers,
+            activation_function,
+            optimizer_state: OptimizerState::new(),
+        })
+    }
+
+    /// The probability that the input is real
+    fn score(&mut self, data: &[f32], training: bool, rng: &mut impl Rng) -> f32 {
+        layer::push_downstream(
+            &mut self.node_array,
+            &self.layers,
+            self.activation_function,
+            data,
+            training,
+            rng,
+        );
+        layer::answer_outputs(&self.node_array)[0]
+    }
+
+    /// Scores the input and carries the binary cross-entropy against the target back through the model
+    ///
+    /// ## Returns
+    /// The loss, and its derivative with respect to each value of the input
+    fn backpropogate(
+        &mut self,
+        data: &[f32],
+        target: f32,
+        training: bool,
+        rng: &mut impl Rng,
+    ) -> (f32, Box<[f32]>) {
+        let score = self.score(data, training, rng);
+        let clamped = score.clamp(PROBABILITY_EPSILON, 1.0 - PROBABILITY_EPSILON);
+        let loss = -(target * clamped.ln() + (1.0 - target) * (1.0 - clamped).ln());
+
+        // The derivative of the loss with respect to the answer node's weighted input,
+        // the sigmoid's derivative cancels out so it's just the score minus the target
+        let input_grads = layer::backpropogate_weighted(
+            &mut self.node_array,
+            &self.layers,
+            self.activation_function,
+            &[score - target],
+        );
+        (loss, input_grads)
+    }
+
+    /// Adds the gradients of learning that the input is real (1) or generated (0) to the current batch
+    ///
+    /// ## Returns
+    /// The loss
+    fn learn(&mut self, data: &[f32], target: f32, rng: &mut impl Rng) -> f32 {
+        let (loss, _) = self.backpropogate(data, target, true, rng);
+        layer::accumulate_grads(&mut self.node_array, &self.layers);
+        loss
+    }
+
+    /// How wrong the model is to think a generated input isn't real, without changing the model
+    ///
+    /// ## Returns
+    /// The generator's loss, and its derivative with respect to each value of the generated input
+    fn fool(&mut self, generated: &[f32], rng: &mut impl Rng) -> (f32, Box<[f32]>) {
+        self.backpropogate(generated, 1.0, false, rng)
+    }
+
+    fn step(&mut self, config: &TrainConfig, learning_rate: f32, batch_len: usize) {
+        self.optimizer_state.step(
+            config.optimizer,
+            &mut self.node_array,
+            learning_rate,
+            batch_len,
+        );
+    }
+}
@@ -0,0 +1,675 @@
+use crate::{
+    activation::ActivationFunction, dbg_println, error::DarjeelingError, node::Node, DEBUG,
+};
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+pub(crate) type NodeArray = Box<[Box<[Node]>]>;
+
+/// Keeps layer normalization from dividing by zero when every input is the same
+const NORM_EPSILON: f32 = 1e-5;
+
+/// How a layer's weighted inputs are rescaled before the activation function is applied
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Default)]
+pub enum Normalization {
+    #[default]
+    None,
+    /// Rescales the weighted inputs of the layer to a mean of zero and a variance of one
+    Layer,
+}
+
+/// How a convolution or pooling window moves along each channel of the layer before
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+pub struct Window {
+    /// The number of values the window covers
+    pub size: usize,
+    /// How far the window moves between outputs
+    pub stride: usize,
+    /// The number of zeros added to both ends of every channel
+    pub padding: usize,
+    /// The gap between the values the window covers, one covers neighbouring values
+    pub dilation: usize,
+}
+
+impl Window {
+    /// The number of outputs along a channel of the given length,
+    /// none if the window doesn't fit the padded channel
+    pub fn output_len(&self, input_len: usize) -> Option<usize> {
+        let span = self.dilation * (self.size.max(1) - 1) + 1;
+        (input_len + 2 * self.padding)
+            .checked_sub(span)
+            .map(|room| room / self.stride.max(1) + 1)
+    }
+}
+
+/// What a layer computes from the outputs of the layer before it.
+///
+/// The outputs of a layer are its channels one after the other, so a layer of width 64 with 8 channels
+/// is 8 sequences of 8 values. Dense layers have a single channel.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Default)]
+pub enum LayerKind {
+    /// Every node is linked to every output of the layer before
+    #[default]
+    Dense,
+    /// Slides each filter's kernel along every channel of the layer before, so each filter is an output channel.
+    /// Every position of a filter shares its weights
+    Conv1D { filters: usize, window: Window },
+    /// The largest value in the window of each channel, it has no weights, activation function or normalization
+    MaxPool1D(Window),
+    /// The mean of the values in the window of each channel, leaving out the padding.
+    /// It has no weights, activation function or normalization
+    AvgPool1D(Window),
+}
+
+impl LayerKind {
+    fn window(&self) -> Option<Window> {
+        match *self {
+            LayerKind::Dense => None,
+            LayerKind::Conv1D { window, .. }
+            | LayerKind::MaxPool1D(window)
+            | LayerKind::AvgPool1D(window) => Some(window),
+        }
+    }
+
+    fn window_mut(&mut self) -> Option<&mut Window> {
+        match self {
+            LayerKind::Dense => None,
+            LayerKind::Conv1D { window, .. }
+            | LayerKind::MaxPool1D(window)
+            | LayerKind::AvgPool1D(window) => Some(window),
+        }
+    }
+
+    fn is_pooling(&self) -> bool {
+        matches!(self, LayerKind::MaxPool1D(_) | LayerKind::AvgPool1D(_))
+    }
+}
+
+/// Describes a single layer of a network
+///
+/// Layers are built with [`LayerSpec::new`] and then refined with the chaining methods:
+/// ``` rust
+/// use darjeeling::{
+///     activation::ActivationFunction,
+///     layer::{LayerSpec, Normalization}
+/// };
+///
+/// let layer = LayerSpec::new(128)
+///     .activation(ActivationFunction::Tanh)
+///     .dropout(0.2)
+///     .normalization(Normalization::Layer);
+/// ```
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+pub struct LayerSpec {
+    /// The number of nodes in the layer, worked out by the builder for convolution and pooling layers
+    pub width: usize,
+    /// The activation function of the layer, or None to use the network's activation function
+    pub activation: Option<ActivationFunction>,
+    /// The probability that a node's output is zeroed during training
+    pub dropout: f32,
+    pub normalization: Normalization,
+    pub kind: LayerKind,
+    /// The number of channels the layer's outputs are split into, see [`LayerKind`]
+    pub channels: usize,
+}
+
+impl LayerSpec {
+    /// Creates a layer of the given width that uses the network's activation function,
+    /// without dropout or normalization
+    pub fn new(width: usize) -> LayerSpec {
+        LayerSpec {
+            width,
+            activation: None,
+            dropout: 0.0,
+            normalization: Normalization::None,
+            kind: LayerKind::Dense,
+            channels: 1,
+        }
+    }
+
+    /// Creates a 1D convolution layer with a stride and dilation of one and no padding.
+    /// Its width is worked out by the [`NetworkBuilder`](crate::builder::NetworkBuilder) from the layer before.
+    ///
+    /// ## Examples
+    /// ``` rust
+    /// use darjeeling::{activation::ActivationFunction, builder::NetworkBuilder, layer::LayerSpec};
+    ///
+    /// // The 64 pixels of a digit are 8 rows of 8
+    /// let net = NetworkBuilder::new(64)
+    ///     .input_channels(8)
+    ///     .activation(ActivationFunction::Sigmoid)
+    ///     .layer(LayerSpec::conv1d(16, 3).padding(1))
+    ///     .layer(LayerSpec::max_pool1d(2))
+    ///     .hidden(10)
+    ///     .build_cat()
+    ///     .unwrap();
+    /// ```
+    pub fn conv1d(filters: usize, kernel: usize) -> LayerSpec {
+        LayerSpec {
+            kind: LayerKind::Conv1D {
+                filters,
+                window: Window {
+                    size: kernel,
+                    stride: 1,
+                    padding: 0,
+                    dilation: 1,
+                },
+            },
+            channels: filters,
+            ..LayerSpec::new(0)
+        }
+    }
+
+    /// Creates a 1D max pooling layer whose stride is its size, so the windows don't overlap
+    pub fn max_pool1d(size: usize) -> LayerSpec {
+        LayerSpec {
+            kind: LayerKind::MaxPool1D(LayerSpec::pool_window(size)),
+            ..LayerSpec::new(0)
+        }
+    }
+
+    /// Creates a 1D average pooling layer whose stride is its size, so the windows don't overlap
+    pub fn avg_pool1d(size: usize) -> LayerSpec {
+        LayerSpec {
+            kind: LayerKind::AvgPool1D(LayerSpec::pool_window(size)),
+            ..LayerSpec::new(0)
+        }
+    }
+
+    fn pool_window(size: usize) -> Window {
+        Window {
+            size,
+            stride: size,
+            padding: 0,
+            dilation: 1,
+        }
+    }
+
+    /// Sets how far a convolution or pooling window moves between outputs, dense layers ignore it
+    pub fn stride(mut self, stride: usize) -> LayerSpec {
+        if let Some(window) = self.kind.window_mut() {
+            window.stride = stride;
+        }
+        self
+    }
+
+    /// Sets the number of zeros added to both ends of every channel, dense layers ignore it
+    pub fn padding(mut self, padding: usize) -> LayerSpec {
+        if let Some(window) = self.kind.window_mut() {
+            window.padding = padding;
+        }
+        self
+    }
+
+    /// Sets the gap between the values a convolution or pooling window covers, dense layers ignore it
+    pub fn dilation(mut self, dilation: usize) -> LayerSpec {
+        if let Some(window) = self.kind.window_mut() {
+            window.dilation = dilation;
+        }
+        self
+    }
+
+    pub fn activation(mut self, activation: ActivationFunction) -> LayerSpec {
+        self.activation = Some(activation);
+        self
+    }
+
+    pub fn dropout(mut self, dropout: f32) -> LayerSpec {
+        self.dropout = dropout;
+        self
+    }
+
+    pub fn normalization(mut self, normalization: Normalization) -> LayerSpec {
+        self.normalization = normalization;
+        self
+    }
+}
+
+/// Creates the nodes of a network from its layer specs.
+/// The first spec describes the input layer, whose nodes have no links.
+/// Link and bias weights are initialized randomly between -.5 and .5,
+/// every position of a convolution filter starts with the same weights and pooling layers have none
+pub(crate) fn build_node_array(layers: &[LayerSpec], rng: &mut impl Rng) -> NodeArray {
+    // links point backwards to previous layer
+    layers
+        .iter()
+        .enumerate()
+        .map(|(i, layer)| {
+            let mut random_node = |links: usize| {
+                let link_weights = (0..links).map(|_| rng.gen_range(-0.5..0.5)).collect();
+                Node::new(link_weights, rng.gen_range(-0.5..0.5))
+            };
+            match layer.kind {
+                _ if i == 0 => (0..layer.width)
+                    .map(|_| Node::new(Box::new([]), 0.0))
+                    .collect(),
+                LayerKind::Dense => (0..layer.width)
+                    .map(|_| random_node(layers[i - 1].width))
+                    .collect(),
+                LayerKind::Conv1D { filters, window } => {
+                    let positions = layer.width / filters.max(1);
+                    (0..filters)
+                        .flat_map(|_| {
+                            let filter = random_node(layers[i - 1].channels * window.size);
+                            (0..positions).map(move |_| filter.clone())
+                        })
+                        .collect()
+                }
+                LayerKind::MaxPool1D(_) | LayerKind::AvgPool1D(_) => (0..layer.width)
+                    .map(|_| Node::new(Box::new([]), 0.0))
+                    .collect(),
+            }
+        })
+        .collect()
+}
+
+/// The index in the previous layer of every value a convolution or pooling node's window covers,
+/// none where the window covers padding.
+/// A convolution window covers every channel, a pooling window only covers the node's own channel.
+fn window_inputs(layer: &LayerSpec, prev: &LayerSpec, node: usize) -> Box<[Option<usize>]> {
+    let window = layer.kind.window().expect("Dense layers have no windows");
+    let positions = layer.width / layer.channels.max(1);
+    let (channel, position) = (node / positions.max(1), node % positions.max(1));
+    let input_len = prev.width / prev.channels.max(1);
+    let channels = match layer.kind {
+        LayerKind::Conv1D { .. } => 0..prev.channels,
+        _ => channel..channel + 1,
+    };
+
+    channels
+        .flat_map(|channel| {
+            (0..window.size).map(move |k| {
+                (position * window.stride + k * window.dilation)
+                    .checked_sub(window.padding)
+                    .filter(|i| *i < input_len)
+                    .map(|i| channel * input_len + i)
+            })
+        })
+        .collect()
+}
+
+/// Passes in data to the sensors, pushs data 'downstream' through the network.
+/// Dropout is only applied while training.
+pub(crate) fn push_downstream(
+    node_array: &mut [Box<[Node]>],
+    layers: &[LayerSpec],
+    activation_function: ActivationFunction,
+    data: &[f32],
+    training: bool,
+    rng: &mut impl Rng,
+) {
+    // Pass data to the input layer
+    node_array
+        .first_mut()
+        .expect("Neural Network has no layers")
+        .iter_mut()
+        .enumerate()
+        .for_each(|(i, node)| {
+            node.cached_input = Some(data[i]);
+            node.cached_output = Some(data[i]);
+        });
+    apply_dropout(&mut node_array[0], &layers[0], training, rng);
+
+    // Push forward hidden and output layers
+    for layer_i in 1..node_array.len() {
+        // Clone the cached outputs from the previous layer
+        let prev_cached_outputs: Box<[f32]> = node_array[layer_i - 1]
+            .iter()
+            .map(|prev_node| {
+                prev_node
+                    .cached_output
+                    .expect("Previous nodes do not have cached outputs")
+            })
+            .collect();
+
+        let layer = &layers[layer_i];
+        let activation = layer.activation.unwrap_or(activation_function);
+
+        match layer.kind {
+            LayerKind::Dense => node_array[layer_i].iter_mut().for_each(|node| {
+                node.link_vals.copy_from_slice(&prev_cached_outputs);
+                node.cached_input = Some(node.input());
+            }),
+            LayerKind::Conv1D { .. } => {
+                for (i, node) in node_array[layer_i].iter_mut().enumerate() {
+                    let inputs = window_inputs(layer, &layers[layer_i - 1], i);
+                    node.link_vals
+                        .iter_mut()
+                        .zip(inputs.iter())
+                        .for_each(|(link_val, input)| {
+                            *link_val = input.map_or(0.0, |input| prev_cached_outputs[input])
+                        });
+                    node.cached_input = Some(node.input());
+                }
+            }
+            LayerKind::MaxPool1D(_) | LayerKind::AvgPool1D(_) => {
+                for (i, node) in node_array[layer_i].iter_mut().enumerate() {
+                    let values: Vec<f32> = window_inputs(layer, &layers[layer_i - 1], i)
+                        .iter()
+                        .flatten()
+                        .map(|input| prev_cached_outputs[*input])
+                        .collect();
+                    let pooled = match layer.kind {
+                        LayerKind::MaxPool1D(_) => values.iter().copied().reduce(f32::max),
+                        _ => Some(values.iter().sum::<f32>() / values.len().max(1) as f32),
+                    };
+                    node.cached_input = Some(pooled.unwrap_or(0.0));
+                    node.cached_output = node.cached_input;
+                }
+            }
+        }
+
+        if !layer.kind.is_pooling() {
+            let normalized = normalized_inputs(&node_array[layer_i], layer.normalization);
+            node_array[layer_i]
+                .iter_mut()
+                .zip(normalized.iter())
+                .for_each(|(node, input)| node.cached_output = Some(activation.apply(*input)));
+        }
+
+        apply_dropout(&mut node_array[layer_i], layer, training, rng);
+    }
+}
+
+/// Goes back through the network, computing every node's error signal from the derivative of the loss
+/// with respect to each answer node's output.
+/// Weights are not changed, see [`accumulate_grads`].
+///
+/// ## Returns
+/// The derivative of the loss with respect to each value given to the input layer
+pub(crate) fn backpropogate(
+    node_array: &mut [Box<[Node]>],
+    layers: &[LayerSpec],
+    activation_function: ActivationFunction,
+    output_grads: &[f32],
+) -> Box<[f32]> {
+    backpropogate_from(node_array, layers, activation_function, output_grads, false)
+}
+
+/// Like [`backpropogate`], but from the derivative of the loss with respect to each answer node's (normalized) weighted input.
+/// The derivative of a loss like binary cross-entropy after a sigmoid cancels out the sigmoid's derivative,
+/// so leaving both out keeps an answer node that's saturated and wrong learning, instead of its gradient vanishing.
+pub(crate) fn backpropogate_weighted(
+    node_array: &mut [Box<[Node]>],
+    layers: &[LayerSpec],
+    activation_function: ActivationFunction,
+    answer_grads: &[f32],
+) -> Box<[f32]> {
+    backpropogate_from(node_array, layers, activation_function, answer_grads, true)
+}
+
+fn backpropogate_from(
+    node_array: &mut [Box<[Node]>],
+    layers: &[LayerSpec],
+    activation_function: ActivationFunction,
+    answer_grads: &[f32],
+    weighted: bool,
+) -> Box<[f32]> {
+    let mut grads: Box<[f32]> = answer_grads.into();
+
+    for layer_i in (1..node_array.len()).rev() {
+        let layer = &layers[layer_i];
+        let activation = layer.activation.unwrap_or(activation_function);
+        let skip_activation = weighted && layer_i == node_array.len() - 1;
+
+        // The derivative of the loss with respect to each node's (normalized) weighted input
+        let input_grads: Box<[f32]> = if layer.kind.is_pooling() {
+            node_array[layer_i]
+                .iter()
+                .zip(grads.iter())
+                .map(|(node, grad)| grad * node.dropout_mask.unwrap_or(1.0))
+                .collect()
+        } else {
+            let normalized = normalized_inputs(&node_array[layer_i], layer.normalization);
+            let input_grads: Box<[f32]> = node_array[layer_i]
+                .iter()
+                .zip(normalized.iter())
+                .zip(grads.iter())
+                .map(|((node, input), grad)| {
+                    let grad = grad * node.dropout_mask.unwrap_or(1.0);
+                    if skip_activation {
+                        grad
+                    } else {
+                        grad * activation.derivative(activation.apply(*input))
+                    }
+                })
+                .collect();
+
+            if layer.normalization == Normalization::Layer {
+                layer_norm_grads(&node_array[layer_i], &normalized, &input_grads)
+            } else {
+                input_grads
+            }
+        };
+
+        let prev_len = node_array[layer_i - 1].len();
+        grads = match layer.kind {
+            LayerKind::Dense => (0..prev_len)
+                .map(|prev_node_i| {
+                    node_array[layer_i]
+                        .iter()
+                        .zip(input_grads.iter())
+                        .map(|(node, grad)| node.link_weights[prev_node_i] * grad)
+                        .sum()
+                })
+                .collect(),
+            _ => {
+                let mut prev_grads = vec![0.0; prev_len];
+                for (i, (node, grad)) in node_array[layer_i]
+                    .iter()
+                    .zip(input_grads.iter())
+                    .enumerate()
+                {
+                    let inputs = window_inputs(layer, &layers[layer_i - 1], i);
+                    match layer.kind {
+                        LayerKind::Conv1D { .. } => inputs
+                            .iter()
+                            .zip(node.link_weights.iter())
+                            .for_each(|(input, weight)| {
+                                if let Some(input) = input {
+                                    prev_grads[*input] += weight * grad;
+                                }
+                            }),
+                        LayerKind::MaxPool1D(_) => {
+                            // Only the largest value in the window affected the output
+                            let largest =
+                                inputs.iter().flatten().copied().reduce(|largest, input| {
+                                    let output =
+                                        |i: usize| node_array[layer_i - 1][i].cached_output;
+                                    if output(input) > output(largest) {
+                                        input
+                                    } else {
+                                        largest
+                                    }
+                                });
+                            if let Some(largest) = largest {
+                                prev_grads[largest] += grad;
+                            }
+                        }
+                        _ => {
+                            let count = inputs.iter().flatten().count().max(1) as f32;
+                            inputs
+                                .iter()
+                                .flatten()
+                                .for_each(|input| prev_grads[*input] += grad / count);
+                        }
+                    }
+                }
+                prev_grads.into_boxed_slice()
+            }
+        };
+
+        // The error signal points in the direction that reduces the loss
+        node_array[layer_i]
+            .iter_mut()
+            .zip(input_grads.iter())
+            .for_each(|(node, grad)| {
+                node.err_sig = Some(-grad);
+                dbg_println!("Err Signal: {:?}", node.err_sig);
+            });
+    }
+
+    node_array[0]
+        .iter()
+        .zip(grads.iter())
+        .map(|(node, grad)| grad * node.dropout_mask.unwrap_or(1.0))
+        .collect()
+}
+
+/// Adds the gradients implied by every non-input node's error signal to the gradients of the current batch.
+/// Every position of a convolution filter gets the filter's summed gradients, so they keep sharing their weights.
+pub(crate) fn accumulate_grads(node_array: &mut [Box<[Node]>], layers: &[LayerSpec]) {
+    for (layer, spec) in node_array.iter_mut().zip(layers.iter()).skip(1) {
+        match spec.kind {
+            LayerKind::Dense => layer.iter_mut().for_each(|node| node.accumulate_grads()),
+            LayerKind::Conv1D { filters, .. } => {
+                let positions = layer.len() / filters.max(1);
+                for filter in layer.chunks_mut(positions.max(1)) {
+                    let mut link_grads = vec![0.0; filter[0].link_weights.len()];
+                    let mut b_grad = 0.0;
+                    for node in filter.iter() {
+                        let err_sig = node.err_sig.expect("Node has no error signal");
+                        b_grad -= err_sig;
+                        link_grads
+                            .iter_mut()
+                            .zip(node.link_vals.iter())
+                            .for_each(|(grad, link_val)| *grad -= err_sig * link_val);
+                    }
+                    for node in filter.iter_mut() {
+                        if node.link_grads.len() != node.link_weights.len() {
+                            node.link_grads = vec![0.0; node.link_weights.len()].into_boxed_slice();
+                        }
+                        node.b_grad += b_grad;
+                        node.link_grads
+                            .iter_mut()
+                            .zip(link_grads.iter())
+                            .for_each(|(grad, filter_grad)| *grad += filter_grad);
+                    }
+                }
+            }
+            // Pooling layers have no weights
+            LayerKind::MaxPool1D(_) | LayerKind::AvgPool1D(_) => {}
+        }
+    }
+}
+
+/// The outputs of the last layer of the network
+pub(crate) fn answer_outputs(node_array: &[Box<[Node]>]) -> Box<[f32]> {
+    node_array
+        .last()
+        .expect("Network has no layers")
+        .iter()
+        .map(|node| {
+            node.cached_output
+                .expect("Answer layer node has no cached output")
+        })
+        .collect()
+}
+
+/// Checks an input is as wide as the network's input layer, a narrower one would panic and a wider one would be cut short
+///
+/// ## Err
+/// ### InvalidLayerSpec
+/// The input isn't as wide as the input layer
+pub(crate) fn check_input(node_array: &[Box<[Node]>], data: &[f32]) -> Result<(), DarjeelingError> {
+    let width = node_array.first().map_or(0, |layer| layer.len());
+    if data.len() != width {
+        return Err(DarjeelingError::InvalidLayerSpec(format!(
+            "the input layer has {} nodes, but an input has {} values",
+            width,
+            data.len()
+        )));
+    }
+    Ok(())
+}
+
+/// The answer layer's outputs for an input that's already been through the network's pipeline, without dropout
+///
+/// ## Err
+/// ### ModelMissingActivationFunction
+/// The network has no activation function
+/// ### InvalidLayerSpec
+/// The input isn't as wide as the input layer
+pub(crate) fn outputs(
+    node_array: &mut [Box<[Node]>],
+    layers: &[LayerSpec],
+    activation_function: Option<ActivationFunction>,
+    data: &[f32],
+) -> Result<Box<[f32]>, DarjeelingError> {
+    let activation_function =
+        activation_function.ok_or(DarjeelingError::ModelMissingActivationFunction)?;
+    check_input(node_array, data)?;
+
+    push_downstream(
+        node_array,
+        layers,
+        activation_function,
+        data,
+        false,
+        &mut rand::thread_rng(),
+    );
+    Ok(answer_outputs(node_array))
+}
+
+fn apply_dropout(layer: &mut [Node], spec: &LayerSpec, training: bool, rng: &mut impl Rng) {
+    if !training || spec.dropout <= 0.0 {
+        layer.iter_mut().for_each(|node| node.dropout_mask = None);
+        return;
+    }
+
+    // Inverted dropout: kept outputs are scaled up so nothing changes outside of training
+    let keep = 1.0 - spec.dropout;
+    layer.iter_mut().for_each(|node| {
+        let mask = if rng.gen::<f32>() < keep {
+            1.0 / keep
+        } else {
+            0.0
+        };
+        node.dropout_mask = Some(mask);
+        node.cached_output = node.cached_output.map(|output| output * mask);
+    });
+}
+
+fn mean_and_std(values: &[f32]) -> (f32, f32) {
+    let mean = values.iter().sum::<f32>() / values.len() as f32;
+    let variance = values.iter().map(|v| (v - mean).powi(2)).sum::<f32>() / values.len() as f32;
+    (mean, (variance + NORM_EPSILON).sqrt())
+}
+
+fn normalized_inputs(layer: &[Node], normalization: Normalization) -> Box<[f32]> {
+    let inputs: Box<[f32]> = layer
+        .iter()
+        .map(|node| node.cached_input.expect("Node has no cached input"))
+        .collect();
+
+    match normalization {
+        Normalization::None => inputs,
+        Normalization::Layer => {
+            let (mean, std) = mean_and_std(&inputs);
+            inputs.iter().map(|input| (input - mean) / std).collect()
+        }
+    }
+}
+
+/// Carries the gradients of the normalized inputs back through the layer normalization
+fn layer_norm_grads(layer: &[Node], normalized: &[f32], grads: &[f32]) -> Box<[f32]> {
+    let inputs: Box<[f32]> = layer
+        .iter()
+        .map(|node| node.cached_input.expect("Node has no cached input"))
+        .collect();
+    let (_, std) = mean_and_std(&inputs);
+    let n = grads.len() as f32;
+    let grad_mean = grads.iter().sum::<f32>() / n;
+    let grad_dot_mean = grads
+        .iter()
+        .zip(normalized.iter())
+        .map(|(grad, norm)| grad * norm)
+        .sum::<f32>()
+        / n;
+
+    grads
+        .iter()
+        .zip(normalized.iter())
+        .map(|(grad, norm)| (grad - grad_mean - norm * grad_dot_mean) / std)
+        .collect()
+}
@@ -1,12 +1,30 @@
 pub static DEBUG: bool = false;
 
 pub mod activation;
+pub mod autoencoder;
+pub mod builder;
 pub mod categorize;
+pub mod checkpoint;
+pub mod config;
+pub mod dataframe;
+pub mod dataset;
 pub mod error;
 pub mod generation;
+pub mod layer;
+pub mod metrics;
+pub mod multilabel;
 pub mod neural_network;
 pub mod node;
+pub mod optimizer;
+pub mod preprocessing;
+pub mod regression;
+pub mod sampling;
+pub mod search;
 pub mod series;
+pub mod split;
 #[cfg(test)]
 pub mod tests;
+mod trainer;
 mod utils;
+pub mod validation;
+pub mod variational;
@@ -0,0 +1,1175 @@
+use crate::{categorize::CatNetwork, error::DarjeelingError, series::Series};
+use serde::{Deserialize, Serialize};
+use std::{fmt, fs};
+
+/// Keeps the log loss finite when a network gives the correct category no score at all
+const PROBABILITY_EPSILON: f32 = 1e-7;
+
+/// How well a network did on a single category
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct ClassMetrics {
+    pub category: String,
+    /// The fraction of inputs assigned this category that actually belong to it
+    pub precision: f32,
+    /// The fraction of inputs belonging to this category that were assigned it
+    pub recall: f32,
+    pub f1: f32,
+    /// The fraction of all inputs correctly assigned to either this category or any other one
+    pub accuracy: f32,
+    /// The number of inputs that actually belong to this category
+    pub support: usize,
+}
+
+/// Precision, recall and f1 averaged over every category
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+pub struct AverageMetrics {
+    pub precision: f32,
+    pub recall: f32,
+    pub f1: f32,
+}
+
+/// The results of evaluating a categorization network against labelled data
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct ClassificationReport {
+    pub categories: Box<[String]>,
+    /// Counts of each actual category (rows) being assigned each category (columns)
+    pub confusion_matrix: Box<[Box<[usize]>]>,
+    pub classes: Box<[ClassMetrics]>,
+    /// The fraction of all inputs that were assigned their actual category
+    pub accuracy: f32,
+    /// Every category counts equally
+    pub macro_avg: AverageMetrics,
+    /// Every category counts as much as its support
+    pub weighted_avg: AverageMetrics,
+    /// The mean negative log of the probability given to each input's actual category
+    pub log_loss: f32,
+}
+
+impl ClassificationReport {
+    /// Builds a report from the scores of a network's answer layer
+    ///
+    /// ## Params
+    /// - Categories: The category of each answer node, in order
+    /// - Answers: The actual category of each input
+    /// - Scores: The outputs of the answer layer for each input.
+    ///   The highest scoring category is the one assigned, and the scores are normalized to sum to one for the log loss.
+    ///
+    /// ## Err
+    /// ### CategoryDoesNotExist
+    /// An answer isn't one of the categories
+    /// ### InvalidMetricInput
+    /// There aren't as many answers as scores, or the scores aren't as wide as the categories
+    pub fn new(
+        categories: &[String],
+        answers: &[String],
+        scores: &[Box<[f32]>],
+    ) -> Result<ClassificationReport, DarjeelingError> {
+        if answers.len() != scores.len() {
+            return Err(DarjeelingError::InvalidMetricInput(format!(
+                "there are {} answers, but {} sets of scores",
+                answers.len(),
+                scores.len()
+            )));
+        }
+        if let Some(score) = scores.iter().find(|score| score.len() != categories.len()) {
+            return Err(DarjeelingError::InvalidMetricInput(format!(
+                "there are {} categories, but {} scores for an input",
+                categories.len(),
+                score.len()
+            )));
+        }
+
+        let actual: Box<[usize]> = answers
+            .iter()
+            .map(|answer| category_index(categories, answer))
+            .collect::<Result<_, _>>()?;
+
+        let mut confusion_matrix: Box<[Box<[usize]>]> = categories
+            .iter()
+            .map(|_| vec![0; categories.len()].into_boxed_slice())
+            .collect();
+        let mut log_loss = 0.0;
+        for (actual, score) in actual.iter().zip(scores.iter()) {
+            confusion_matrix[*actual][largest_index(score)] += 1;
+            log_loss -= probabilities(score)[*actual].max(PROBABILITY_EPSILON).ln();
+        }
+
+        let total = actual.len();
+        let classes: Box<[ClassMetrics]> = categories
+            .iter()
+            .enumerate()
+            .map(|(i, category)| {
+                let true_positives = confusion_matrix[i][i];
+                let support: usize = confusion_matrix[i].iter().sum();
+                let predicted: usize = confusion_matrix.iter().map(|row| row[i]).sum();
+                let true_negatives = total + true_positives - support - predicted;
+
+                let precision = ratio(true_positives, predicted);
+                let recall = ratio(true_positives, support);
+                ClassMetrics {
+                    category: category.clone(),
+                    precision,
+                    recall,
+                    f1: f1(precision, recall),
+                    accuracy: ratio(true_positives + true_negatives, total),
+                    support,
+                }
+            })
+            .collect();
+
+        let correct: usize = (0..categories.len()).map(|i| confusion_matrix[i][i]).sum();
+        Ok(ClassificationReport {
+            categories: categories.into(),
+            macro_avg: average(&classes, |_| 1.0),
+            weighted_avg: average(&classes, |class| class.support as f32),
+            confusion_matrix,
+            classes,
+            accuracy: ratio(correct, total),
+            log_loss: if total == 0 {
+                0.0
+            } else {
+                log_loss / total as f32
+            },
+        })
+    }
+
+    pub fn to_json(&self) -> Result<String, DarjeelingError> {
+        serde_json::to_string_pretty(self)
+            .map_err(|err| DarjeelingError::SerializationFailed(err.to_string()))
+    }
+}
+
+impl fmt::Display for ClassificationReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let width = self
+            .categories
+            .iter()
+            .map(|category| category.len())
+            .chain(std::iter::once("weighted avg".len()))
+            .max()
+            .unwrap_or(0);
+        let support: usize = self.classes.iter().map(|class| class.support).sum();
+
+        writeln!(
+            f,
+            "{:>width$} {:>10} {:>10} {:>10} {:>10} {:>10}",
+            "", "precision", "recall", "f1-score", "accuracy", "support"
+        )?;
+        for class in self.classes.iter() {
+            writeln!(
+                f,
+                "{:>width$} {:>10.4} {:>10.4} {:>10.4} {:>10.4} {:>10}",
+                class.category,
+                class.precision,
+                class.recall,
+                class.f1,
+                class.accuracy,
+                class.support
+            )?;
+        }
+        writeln!(f)?;
+        writeln!(
+            f,
+            "{:>width$} {:>10} {:>10} {:>10.4} {:>10} {:>10}",
+            "accuracy", "", "", self.accuracy, "", support
+        )?;
+        for (name, avg) in [
+            ("macro avg", self.macro_avg),
+            ("weighted avg", self.weighted_avg),
+        ] {
+            writeln!(
+                f,
+                "{:>width$} {:>10.4} {:>10.4} {:>10.4} {:>10} {:>10}",
+                name, avg.precision, avg.recall, avg.f1, "", support
+            )?;
+        }
+        writeln!(f, "{:>width$} {:>10.4}", "log loss", self.log_loss)?;
+
+        writeln!(f)?;
+        writeln!(
+            f,
+            "Confusion matrix (rows are actual, columns are assigned)"
+        )?;
+        let cell = self
+            .categories
+            .iter()
+            .map(|category| category.len())
+            .chain(
+                self.confusion_matrix
+                    .iter()
+                    .flat_map(|row| row.iter())
+                    .map(|count| count.to_string().len()),
+            )
+            .max()
+            .unwrap_or(0);
+        write!(f, "{:>width$}", "")?;
+        for category in self.categories.iter() {
+            write!(f, " {:>cell$}", category)?;
+        }
+        writeln!(f)?;
+        for (category, row) in self.categories.iter().zip(self.confusion_matrix.iter()) {
+            write!(f, "{:>width$}", category)?;
+            for count in row.iter() {
+                write!(f, " {:>cell$}", count)?;
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
+/// The results of evaluating a multi-label network against labelled data
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct MultiLabelReport {
+    pub labels: Box<[String]>,
+    /// The metrics of each label on its own, its accuracy being the fraction of inputs it was correctly given or left off
+    pub classes: Box<[ClassMetrics]>,
+    /// The fraction of every label of every input that was wrongly given or left off
+    pub hamming_loss: f32,
+    /// The fraction of inputs that were given exactly their labels
+    pub subset_accuracy: f32,
+    /// Every label of every input counts equally
+    pub micro_avg: AverageMetrics,
+    /// Every label counts equally
+    pub macro_avg: AverageMetrics,
+}
+
+impl MultiLabelReport {
+    /// Builds a report from the labels a network gave each input
+    ///
+    /// ## Params
+    /// - Labels: The label of each answer node, in order
+    /// - Answers: The actual labels of each input
+    /// - Predictions: The labels the network gave each input
+    ///
+    /// ## Err
+    /// ### CategoryDoesNotExist
+    /// An answer or prediction isn't one of the labels
+    /// ### InvalidMetricInput
+    /// There aren't as many answers as predictions
+    ///
+    /// ## Examples
+    /// ``` rust
+    /// use darjeeling::metrics::MultiLabelReport;
+    ///
+    /// let labels = vec!["a".to_string(), "b".to_string()];
+    /// let answers = vec![vec!["a".to_string()].into(), vec!["a".to_string(), "b".to_string()].into()];
+    /// let predictions = vec![vec!["a".to_string()].into(), vec!["b".to_string()].into()];
+    /// let report = MultiLabelReport::new(&labels, &answers, &predictions).unwrap();
+    /// assert_eq!(report.hamming_loss, 0.25);
+    /// assert_eq!(report.subset_accuracy, 0.5);
+    /// ```
+    pub fn new(
+        labels: &[String],
+        answers: &[Box<[String]>],
+        predictions: &[Box<[String]>],
+    ) -> Result<MultiLabelReport, DarjeelingError> {
+        if answers.len() != predictions.len() {
+            return Err(DarjeelingError::InvalidMetricInput(format!(
+                "there are {} answers, but {} predictions",
+                answers.len(),
+                predictions.len()
+            )));
+        }
+        let actual = label_matrix(labels, answers)?;
+        let predicted = label_matrix(labels, predictions)?;
+
+        let total = answers.len();
+        // True positives, false positives and false negatives of each label
+        let counts: Vec<(usize, usize, usize)> = (0..labels.len())
+            .map(|i| {
+                actual.iter().zip(predicted.iter()).fold(
+                    (0, 0, 0),
+                    |(tp, fp, fn_), (actual, predicted)| match (actual[i], predicted[i]) {
+                        (true, true) => (tp + 1, fp, fn_),
+                        (false, true) => (tp, fp + 1, fn_),
+                        (true, false) => (tp, fp, fn_ + 1),
+                        (false, false) => (tp, fp, fn_),
+                    },
+                )
+            })
+            .collect();
+
+        let classes: Box<[ClassMetrics]> = labels
+            .iter()
+            .zip(counts.iter())
+            .map(|(label, (tp, fp, fn_))| {
+                let precision = ratio(*tp, tp + fp);
+                let recall = ratio(*tp, tp + fn_);
+                ClassMetrics {
+                    category: label.clone(),
+                    precision,
+                    recall,
+                    f1: f1(precision, recall),
+                    accuracy: ratio(total - fp - fn_, total),
+                    support: tp + fn_,
+                }
+            })
+            .collect();
+
+        let (tp, fp, fn_) = counts.iter().fold((0, 0, 0), |sum, count| {
+            (sum.0 + count.0, sum.1 + count.1, sum.2 + count.2)
+        });
+        let micro_precision = ratio(tp, tp + fp);
+        let micro_recall = ratio(tp, tp + fn_);
+        let exact = actual
+            .iter()
+            .zip(predicted.iter())
+            .filter(|(actual, predicted)| actual == predicted)
+            .count();
+
+        Ok(MultiLabelReport {
+            labels: labels.into(),
+            macro_avg: average(&classes, |_| 1.0),
+            micro_avg: AverageMetrics {
+                precision: micro_precision,
+                recall: micro_recall,
+                f1: f1(micro_precision, micro_recall),
+            },
+            classes,
+            hamming_loss: ratio(fp + fn_, total * labels.len()),
+            subset_accuracy: ratio(exact, total),
+        })
+    }
+
+    pub fn to_json(&self) -> Result<String, DarjeelingError> {
+        serde_json::to_string_pretty(self)
+            .map_err(|err| DarjeelingError::SerializationFailed(err.to_string()))
+    }
+}
+
+impl fmt::Display for MultiLabelReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let width = self
+            .labels
+            .iter()
+            .map(|label| label.len())
+            .chain(std::iter::once("subset accuracy".len()))
+            .max()
+            .unwrap_or(0);
+        let support: usize = self.classes.iter().map(|class| class.support).sum();
+
+        writeln!(
+            f,
+            "{:>width$} {:>10} {:>10} {:>10} {:>10} {:>10}",
+            "", "precision", "recall", "f1-score", "accuracy", "support"
+        )?;
+        for class in self.classes.iter() {
+            writeln!(
+                f,
+                "{:>width$} {:>10.4} {:>10.4} {:>10.4} {:>10.4} {:>10}",
+                class.category,
+                class.precision,
+                class.recall,
+                class.f1,
+                class.accuracy,
+                class.support
+            )?;
+        }
+        writeln!(f)?;
+        for (name, avg) in [("micro avg", self.micro_avg), ("macro avg", self.macro_avg)] {
+            writeln!(
+                f,
+                "{:>width$} {:>10.4} {:>10.4} {:>10.4} {:>10} {:>10}",
+                name, avg.precision, avg.recall, avg.f1, "", support
+            )?;
+        }
+        writeln!(f, "{:>width$} {:>10.4}", "hamming loss", self.hamming_loss)?;
+        writeln!(
+            f,
+            "{:>width$} {:>10.4}",
+            "subset accuracy", self.subset_accuracy
+        )
+    }
+}
+
+/// How well a regression network predicted a single target
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+pub struct RegressionMetrics {
+    /// The coefficient of determination, the fraction of the target's variance the predictions explain
+    pub r2: f32,
+    /// The root mean squared error
+    pub rmse: f32,
+    /// The mean absolute error
+    pub mae: f32,
+}
+
+/// The results of evaluating a regression network against labelled data
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct RegressionReport {
+    /// The metrics of each target, in the order of the answer nodes
+    pub targets: Box<[RegressionMetrics]>,
+    /// Every target's R² counts equally
+    pub r2: f32,
+    /// The root mean squared error of every target of every input
+    pub rmse: f32,
+    /// The mean absolute error of every target of every input
+    pub mae: f32,
+}
+
+impl RegressionReport {
+    /// Builds a report from a network's predictions
+    ///
+    /// ## Params
+    /// - Targets: The actual values of each input
+    /// - Predictions: The values the network predicted for each input
+    ///
+    /// ## Err
+    /// ### InvalidMetricInput
+    /// There are no inputs, there aren't as many targets as predictions, or they have different widths
+    ///
+    /// ## Examples
+    /// ``` rust
+    /// use darjeeling::metrics::RegressionReport;
+    ///
+    /// let targets = vec![vec![1.0].into(), vec![3.0].into()];
+    /// let predictions = vec![vec![2.0].into(), vec![3.0].into()];
+    /// let report = RegressionReport::new(&targets, &predictions).unwrap();
+    /// assert_eq!((report.r2, report.mae), (0.5, 0.5));
+    /// ```
+    pub fn new(
+        targets: &[Box<[f32]>],
+        predictions: &[Box<[f32]>],
+    ) -> Result<RegressionReport, DarjeelingError> {
+        if targets.len() != predictions.len() || targets.is_empty() {
+            return Err(DarjeelingError::InvalidMetricInput(format!(
+                "there are {} targets and {} predictions, there needs to be the same number of at least one",
+                targets.len(),
+                predictions.len()
+            )));
+        }
+        let width = targets[0].len();
+        if let Some((target, prediction)) = targets
+            .iter()
+            .zip(predictions.iter())
+            .find(|(target, prediction)| target.len() != width || prediction.len() != width)
+        {
+            return Err(DarjeelingError::InvalidMetricInput(format!(
+                "an input has {} targets and {} predictions, but the first has {} targets",
+                target.len(),
+                prediction.len(),
+                width
+            )));
+        }
+
+        let count = targets.len() as f32;
+        let metrics: Box<[RegressionMetrics]> = (0..width)
+            .map(|i| {
+                let mean = targets.iter().map(|target| target[i]).sum::<f32>() / count;
+                let (squared, absolute, variance) = targets.iter().zip(predictions.iter()).fold(
+                    (0.0, 0.0, 0.0),
+                    |(squared, absolute, variance), (target, prediction)| {
+                        let error = prediction[i] - target[i];
+                        (
+                            squared + error.powi(2),
+                            absolute + error.abs(),
+                            variance + (target[i] - mean).powi(2),
+                        )
+                    },
+                );
+                RegressionMetrics {
+                    r2: r2(squared, variance),
+                    rmse: (squared / count).sqrt(),
+                    mae: absolute / count,
+                }
+            })
+            .collect();
+
+        let values = count * width as f32;
+        let squared: f32 = metrics
+            .iter()
+            .map(|metric| metric.rmse.powi(2) * count)
+            .sum();
+        let absolute: f32 = metrics.iter().map(|metric| metric.mae * count).sum();
+        Ok(RegressionReport {
+            r2: metrics.iter().map(|metric| metric.r2).sum::<f32>() / width.max(1) as f32,
+            rmse: (squared / values).sqrt(),
+            mae: absolute / values,
+            targets: metrics,
+        })
+    }
+
+    pub fn to_json(&self) -> Result<String, DarjeelingError> {
+        serde_json::to_string_pretty(self)
+            .map_err(|err| DarjeelingError::SerializationFailed(err.to_string()))
+    }
+}
+
+impl fmt::Display for RegressionReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "{:>10} {:>10} {:>10} {:>10}", "", "r2", "rmse", "mae")?;
+        for (i, target) in self.targets.iter().enumerate() {
+            writeln!(
+                f,
+                "{:>10} {:>10.4} {:>10.4} {:>10.4}",
+                format!("target {}", i),
+                target.r2,
+                target.rmse,
+                target.mae
+            )?;
+        }
+        writeln!(
+            f,
+            "{:>10} {:>10.4} {:>10.4} {:>10.4}",
+            "overall", self.r2, self.rmse, self.mae
+        )
+    }
+}
+
+/// How far generated data is from the real data on a single feature
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+pub struct FeatureDrift {
+    /// The generated mean minus the real mean
+    pub mean_drift: f32,
+    /// The generated variance minus the real variance
+    pub variance_drift: f32,
+    /// The two-sample Kolmogorov-Smirnov statistic, the largest gap between the two cumulative distributions,
+    /// from zero when they match to one when they don't overlap
+    pub ks: f32,
+}
+
+/// The results of comparing rows a generative network generated to real rows
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct GenerationReport {
+    /// The drift of each feature, in order
+    pub features: Box<[FeatureDrift]>,
+    /// The mean size of every feature's mean drift
+    pub mean_drift: f32,
+    /// The mean size of every feature's variance drift
+    pub variance_drift: f32,
+    /// The mean Kolmogorov-Smirnov statistic of every feature
+    pub ks: f32,
+    /// The squared maximum mean discrepancy between whole rows, with a gaussian kernel
+    /// whose bandwidth is the median distance between rows. Zero when the rows come from the same distribution
+    pub mmd: f32,
+    /// The fraction of generated rows a classifier puts in the category they were generated for,
+    /// see [`classifier_accuracy`]
+    pub classifier_accuracy: Option<f32>,
+}
+
+impl GenerationReport {
+    /// Builds a report from real and generated rows, which don't need to be as many as each other.
+    /// The maximum mean discrepancy compares every pair of rows, so it takes quadratically longer with more rows
+    ///
+    /// ## Err
+    /// ### InvalidMetricInput
+    /// There are no real or no generated rows, they have different widths, or a value is NaN or infinite
+    ///
+    /// ## Examples
+    /// ``` rust
+    /// use darjeeling::metrics::GenerationReport;
+    ///
+    /// let real = vec![vec![0.0].into(), vec![1.0].into()];
+    /// let generated = vec![vec![1.0].into(), vec![2.0].into()];
+    /// let report = GenerationReport::new(&real, &generated).unwrap();
+    /// assert_eq!((report.mean_drift, report.ks), (1.0, 0.5));
+    /// ```
+    pub fn new(
+        real: &[Box<[f32]>],
+        generated: &[Box<[f32]>],
+    ) -> Result<GenerationReport, DarjeelingError> {
+        if real.is_empty() || generated.is_empty() {
+            return Err(DarjeelingError::InvalidMetricInput(format!(
+                "there are {} real and {} generated rows, there needs to be at least one of each",
+                real.len(),
+                generated.len()
+            )));
+        }
+        let width = real[0].len();
+        if let Some(row) = real
+            .iter()
+            .chain(generated.iter())
+            .find(|row| row.len() != width)
+        {
+            return Err(DarjeelingError::InvalidMetricInput(format!(
+                "a row has {} values, but the first real row has {}",
+                row.len(),
+                width
+            )));
+        }
+        if real
+            .iter()
+            .chain(generated.iter())
+            .any(|row| row.iter().any(|value| !value.is_finite()))
+        {
+            return Err(DarjeelingError::InvalidMetricInput(
+                "a row has a NaN or infinite value".to_string(),
+            ));
+        }
+
+        let features: Box<[FeatureDrift]> = (0..width)
+            .map(|i| {
+                let real: Box<[f32]> = real.iter().map(|row| row[i]).collect();
+                let generated: Box<[f32]> = generated.iter().map(|row| row[i]).collect();
+                let (real_mean, real_variance) = mean_and_variance(&real);
+                let (generated_mean, generated_variance) = mean_and_variance(&generated);
+                FeatureDrift {
+                    mean_drift: generated_mean - real_mean,
+                    variance_drift: generated_variance - real_variance,
+                    ks: ks_statistic(&real, &generated),
+                }
+            })
+            .collect();
+
+        let mean_of = |metric: fn(&FeatureDrift) -> f32| {
+            features.iter().map(metric).sum::<f32>() / width.max(1) as f32
+        };
+        Ok(GenerationReport {
+            mean_drift: mean_of(|feature| feature.mean_drift.abs()),
+            variance_drift: mean_of(|feature| feature.variance_drift.abs()),
+            ks: mean_of(|feature| feature.ks),
+            mmd: mmd(real, generated),
+            classifier_accuracy: None,
+            features,
+        })
+    }
+
+    pub fn to_json(&self) -> Result<String, DarjeelingError> {
+        serde_json::to_string_pretty(self)
+            .map_err(|err| DarjeelingError::SerializationFailed(err.to_string()))
+    }
+}
+
+impl fmt::Display for GenerationReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "{:>12} {:>10} {:>10} {:>10}",
+            "", "mean drift", "var drift", "ks"
+        )?;
+        for (i, feature) in self.features.iter().enumerate() {
+            writeln!(
+                f,
+                "{:>12} {:>10.4} {:>10.4} {:>10.4}",
+                format!("feature {}", i),
+                feature.mean_drift,
+                feature.variance_drift,
+                feature.ks
+            )?;
+        }
+        writeln!(
+            f,
+            "{:>12} {:>10.4} {:>10.4} {:>10.4}",
+            "overall", self.mean_drift, self.variance_drift, self.ks
+        )?;
+        write!(f, "{:>12} {:>10.4}", "mmd", self.mmd)?;
+        if let Some(accuracy) = self.classifier_accuracy {
+            write!(f, "\n{:>12} {:>10.4}", "classifier", accuracy)?;
+        }
+        Ok(())
+    }
+}
+
+/// The fraction of generated rows a trained classifier puts in the category they were generated for.
+/// The classifier is only used to predict, so it stays frozen.
+///
+/// ## Params
+/// - Classifier: A network trained on real rows of the same categories
+/// - Generated: The generated rows, each with the category it was generated for as its answer
+///
+/// ## Err
+/// The errors of [`CatNetwork::predict`], and
+/// ### InvalidMetricInput
+/// There are no generated rows
+pub fn classifier_accuracy(
+    classifier: &mut CatNetwork,
+    generated: &[Series],
+) -> Result<f32, DarjeelingError> {
+    if generated.is_empty() {
+        return Err(DarjeelingError::InvalidMetricInput(
+            "there are no generated rows to classify".to_string(),
+        ));
+    }
+    let mut correct = 0;
+    for series in generated {
+        if classifier.predict(&series.data)? == series.answer {
+            correct += 1;
+        }
+    }
+    Ok(ratio(correct, generated.len()))
+}
+
+/// A receiver operating characteristic curve, how the true and false positive rates trade off as the threshold falls.
+///
+/// The first point has an infinite threshold, where nothing is a positive.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct RocCurve {
+    /// Scores at or above the threshold count as positives
+    pub thresholds: Box<[f32]>,
+    pub false_positive_rates: Box<[f32]>,
+    pub true_positive_rates: Box<[f32]>,
+    /// The area under the curve
+    pub auc: f32,
+}
+
+impl RocCurve {
+    /// Builds the curve of a binary classifier
+    ///
+    /// ## Params
+    /// - Labels: Whether each input is actually a positive
+    /// - Scores: The classifier's score for each input, higher scores being more likely positives
+    ///
+    /// ## Err
+    /// ### InvalidMetricInput
+    /// There aren't as many labels as scores, or there isn't at least one positive and one negative
+    pub fn new(labels: &[bool], scores: &[f32]) -> Result<RocCurve, DarjeelingError> {
+        let (positives, negatives) = class_counts(labels, scores)?;
+
+        let mut thresholds = vec![f32::INFINITY];
+        let mut false_positive_rates = vec![0.0];
+        let mut true_positive_rates = vec![0.0];
+        for (threshold, true_positives, false_positives) in sweep(labels, scores) {
+            thresholds.push(threshold);
+            false_positive_rates.push(false_positives as f32 / negatives as f32);
+            true_positive_rates.push(true_positives as f32 / positives as f32);
+        }
+
+        let auc = (1..thresholds.len())
+            .map(|i| {
+                (false_positive_rates[i] - false_positive_rates[i - 1])
+                    * (true_positive_rates[i] + true_positive_rates[i - 1])
+                    / 2.0
+            })
+            .sum();
+        Ok(RocCurve {
+            thresholds: thresholds.into_boxed_slice(),
+            false_positive_rates: false_positive_rates.into_boxed_slice(),
+            true_positive_rates: true_positive_rates.into_boxed_slice(),
+            auc,
+        })
+    }
+
+    /// Builds the curve of one category against every other one, from the scores of a network's answer layer
+    ///
+    /// ## Params
+    /// - Categories: The category of each answer node, in order
+    /// - Answers: The actual category of each input
+    /// - Scores: The outputs of the answer layer for each input
+    /// - Category: The category counted as positive
+    ///
+    /// ## Err
+    /// ### CategoryDoesNotExist
+    /// The category or an answer isn't one of the categories
+    /// ### InvalidMetricInput
+    /// The category is every answer or none of them, or the scores don't match the answers
+    pub fn one_vs_rest(
+        categories: &[String],
+        answers: &[String],
+        scores: &[Box<[f32]>],
+        category: &str,
+    ) -> Result<RocCurve, DarjeelingError> {
+        let (labels, scores) = one_vs_rest(categories, answers, scores, category)?;
+        RocCurve::new(&labels, &scores)
+    }
+
+    /// The curve as csv, with a row for each point
+    pub fn to_csv(&self) -> String {
+        curve_csv(
+            ["threshold", "false_positive_rate", "true_positive_rate"],
+            &self.thresholds,
+            &self.false_positive_rates,
+            &self.true_positive_rates,
+        )
+    }
+
+    /// Writes the curve as csv to the given path
+    ///
+    /// ## Err
+    /// ### WriteFileFailed
+    /// The file couldn't be written
+    pub fn write_csv(&self, path: &str) -> Result<(), DarjeelingError> {
+        fs::write(path, self.to_csv())
+            .map_err(|err| DarjeelingError::WriteFileFailed(err.to_string()))
+    }
+}
+
+/// A precision-recall curve, how precision trades off against recall as the threshold falls.
+///
+/// The first point has an infinite threshold, where nothing is a positive and the precision is taken to be one.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct PrCurve {
+    /// Scores at or above the threshold count as positives
+    pub thresholds: Box<[f32]>,
+    pub recalls: Box<[f32]>,
+    pub precisions: Box<[f32]>,
+    /// The mean of the precisions at each threshold, weighted by how much the recall rose
+    pub average_precision: f32,
+}
+
+impl PrCurve {
+    /// Builds the curve of a binary classifier
+    ///
+    /// ## Params
+    /// - Labels: Whether each input is actually a positive
+    /// - Scores: The classifier's score for each input, higher scores being more likely positives
+    ///
+    /// ## Err
+    /// ### InvalidMetricInput
+    /// There aren't as many labels as scores, or there isn't at least one positive and one negative
+    pub fn new(labels: &[bool], scores: &[f32]) -> Result<PrCurve, DarjeelingError> {
+        let (positives, _) = class_counts(labels, scores)?;
+
+        let mut thresholds = vec![f32::INFINITY];
+        let mut recalls = vec![0.0];
+        let mut precisions = vec![1.0];
+        for (threshold, true_positives, false_positives) in sweep(labels, scores) {
+            thresholds.push(threshold);
+            recalls.push(true_positives as f32 / positives as f32);
+            precisions.push(true_positives as f32 / (true_positives + false_positives) as f32);
+        }
+
+        let average_precision = (1..thresholds.len())
+            .map(|i| (recalls[i] - recalls[i - 1]) * precisions[i])
+            .sum();
+        Ok(PrCurve {
+            thresholds: thresholds.into_boxed_slice(),
+            recalls: recalls.into_boxed_slice(),
+            precisions: precisions.into_boxed_slice(),
+            average_precision,
+        })
+    }
+
+    /// Builds the curve of one category against every other one, see [`RocCurve::one_vs_rest`]
+    pub fn one_vs_rest(
+        categories: &[String],
+        answers: &[String],
+        scores: &[Box<[f32]>],
+        category: &str,
+    ) -> Result<PrCurve, DarjeelingError> {
+        let (labels, scores) = one_vs_rest(categories, answers, scores, category)?;
+        PrCurve::new(&labels, &scores)
+    }
+
+    /// The curve as csv, with a row for each point
+    pub fn to_csv(&self) -> String {
+        curve_csv(
+            ["threshold", "recall", "precision"],
+            &self.thresholds,
+            &self.recalls,
+            &self.precisions,
+        )
+    }
+
+    /// Writes the curve as csv to the given path
+    ///
+    /// ## Err
+    /// ### WriteFileFailed
+    /// The file couldn't be written
+    pub fn write_csv(&self, path: &str) -> Result<(), DarjeelingError> {
+        fs::write(path, self.to_csv())
+            .map_err(|err| DarjeelingError::WriteFileFailed(err.to_string()))
+    }
+}
+
+/// The one-vs-rest area under the roc curve of every category, averaged with each category counting equally.
+/// Categories that are every answer or none of them have no curve and are left out.
+///
+/// ## Params
+/// - Categories: The category of each answer node, in order
+/// - Answers: The actual category of each input
+/// - Scores: The outputs of the answer layer for each input
+///
+/// ## Err
+/// ### CategoryDoesNotExist
+/// An answer isn't one of the categories
+/// ### InvalidMetricInput
+/// No category has a curve, or the scores don't match the answers
+pub fn macro_auc(
+    categories: &[String],
+    answers: &[String],
+    scores: &[Box<[f32]>],
+) -> Result<f32, DarjeelingError> {
+    let mut aucs = vec![];
+    for category in categories.iter() {
+        let (labels, scores) = one_vs_rest(categories, answers, scores, category)?;
+        if labels.iter().any(|label| *label) && labels.iter().any(|label| !*label) {
+            aucs.push(RocCurve::new(&labels, &scores)?.auc);
+        }
+    }
+
+    if aucs.is_empty() {
+        return Err(DarjeelingError::InvalidMetricInput(
+            "no category has both positive and negative answers".to_string(),
+        ));
+    }
+    Ok(aucs.iter().sum::<f32>() / aucs.len() as f32)
+}
+
+/// The number of positive and negative labels
+fn class_counts(labels: &[bool], scores: &[f32]) -> Result<(usize, usize), DarjeelingError> {
+    if labels.len() != scores.len() {
+        return Err(DarjeelingError::InvalidMetricInput(format!(
+            "there are {} labels, but {} scores",
+            labels.len(),
+            scores.len()
+        )));
+    }
+
+    let positives = labels.iter().filter(|label| **label).count();
+    let negatives = labels.len() - positives;
+    if positives == 0 || negatives == 0 {
+        return Err(DarjeelingError::InvalidMetricInput(
+            "a curve needs at least one positive and one negative label".to_string(),
+        ));
+    }
+    Ok((positives, negatives))
+}
+
+/// Lowers the threshold through every distinct score, highest first.
+/// Yields each threshold with the true and false positives at or above it.
+fn sweep(labels: &[bool], scores: &[f32]) -> Vec<(f32, usize, usize)> {
+    let mut order: Vec<usize> = (0..scores.len()).collect();
+    order.sort_by(|a, b| scores[*b].total_cmp(&scores[*a]));
+
+    let mut points = vec![];
+    let mut true_positives = 0;
+    let mut false_positives = 0;
+    for (i, index) in order.iter().enumerate() {
+        if labels[*index] {
+            true_positives += 1;
+        } else {
+            false_positives += 1;
+        }
+        // Tied scores share a threshold, so only the last of them makes a point
+        if order
+            .get(i + 1)
+            .map_or(true, |next| scores[*next] != scores[*index])
+        {
+            points.push((scores[*index], true_positives, false_positives));
+        }
+    }
+    points
+}
+
+/// Whether each input has each label
+fn label_matrix(
+    labels: &[String],
+    answers: &[Box<[String]>],
+) -> Result<Box<[Box<[bool]>]>, DarjeelingError> {
+    answers
+        .iter()
+        .map(|answers| {
+            let mut has = vec![false; labels.len()].into_boxed_slice();
+            for answer in answers.iter() {
+                has[category_index(labels, answer)?] = true;
+            }
+            Ok(has)
+        })
+        .collect()
+}
+
+/// The threshold that gives a binary classifier the best f1 score,
+/// halfway between the lowest score it counts as a positive and the next lower score
+pub(crate) fn best_threshold(labels: &[bool], scores: &[f32]) -> Option<f32> {
+    let positives = labels.iter().filter(|label| **label).count();
+    if positives == 0 || labels.len() != scores.len() {
+        return None;
+    }
+
+    let points = sweep(labels, scores);
+    let best = (0..points.len()).max_by(|a, b| {
+        let score = |i: usize| {
+            let (_, true_positives, false_positives) = points[i];
+            f1(
+                ratio(true_positives, true_positives + false_positives),
+                ratio(true_positives, positives),
+            )
+        };
+        // Ties go to the higher threshold
+        score(*a).total_cmp(&score(*b)).then(b.cmp(a))
+    })?;
+    Some(match points.get(best + 1) {
+        Some((lower, _, _)) => (points[best].0 + lower) / 2.0,
+        None => points[best].0,
+    })
+}
+
+/// Whether each input is a positive, and its score
+type BinaryScores = (Box<[bool]>, Box<[f32]>);
+
+/// Whether each answer is the category, and the category's score for each input
+fn one_vs_rest(
+    categories: &[String],
+    answers: &[String],
+    scores: &[Box<[f32]>],
+    category: &str,
+) -> Result<BinaryScores, DarjeelingError> {
+    let index = category_index(categories, category)?;
+    if answers.len() != scores.len() {
+        return Err(DarjeelingError::InvalidMetricInput(format!(
+            "there are {} answers, but {} sets of scores",
+            answers.len(),
+            scores.len()
+        )));
+    }
+
+    let labels = answers
+        .iter()
+        .map(|answer| category_index(categories, answer).map(|answer| answer == index))
+        .collect::<Result<_, _>>()?;
+    let scores = scores
+        .iter()
+        .map(|score| {
+            score.get(index).copied().ok_or_else(|| {
+                DarjeelingError::InvalidMetricInput(format!(
+                    "there are {} categories, but {} scores for an input",
+                    categories.len(),
+                    score.len()
+                ))
+            })
+        })
+        .collect::<Result<_, _>>()?;
+    Ok((labels, scores))
+}
+
+fn curve_csv(header: [&str; 3], thresholds: &[f32], xs: &[f32], ys: &[f32]) -> String {
+    let mut csv = header.join(",");
+    csv.push('\n');
+    for ((threshold, x), y) in thresholds.iter().zip(xs.iter()).zip(ys.iter()) {
+        csv.push_str(&format!("{},{},{}\n", threshold, x, y));
+    }
+    csv
+}
+
+/// The index of the given category
+pub(crate) fn category_index(
+    categories: &[String],
+    answer: &str,
+) -> Result<usize, DarjeelingError> {
+    categories
+        .iter()
+        .position(|category| category == answer)
+        .ok_or_else(|| DarjeelingError::CategoryDoesNotExist(answer.to_string()))
+}
+
+/// The index of the highest score
+pub(crate) fn largest_index(scores: &[f32]) -> usize {
+    let mut largest_index = 0;
+    for (i, score) in scores.iter().enumerate() {
+        if *score > scores[largest_index] {
+            largest_index = i;
+        }
+    }
+    largest_index
+}
+
+/// Normalizes answer layer scores to sum to one, negative scores count as zero
+pub(crate) fn probabilities(scores: &[f32]) -> Box<[f32]> {
+    let sum: f32 = scores.iter().map(|score| score.max(0.0)).sum();
+    if sum <= 0.0 {
+        return scores.iter().map(|_| 1.0 / scores.len() as f32).collect();
+    }
+    scores.iter().map(|score| score.max(0.0) / sum).collect()
+}
+
+fn ratio(numerator: usize, denominator: usize) -> f32 {
+    if denominator == 0 {
+        0.0
+    } else {
+        numerator as f32 / denominator as f32
+    }
+}
+
+/// The coefficient of determination from the squared errors and the target's variance, both summed.
+/// A target that never changes gives one if it's predicted perfectly and zero otherwise.
+fn r2(squared_error: f32, variance: f32) -> f32 {
+    if variance == 0.0 {
+        if squared_error == 0.0 {
+            1.0
+        } else {
+            0.0
+        }
+    } else {
+        1.0 - squared_error / variance
+    }
+}
+
+/// The mean and the population variance of the values
+fn mean_and_variance(values: &[f32]) -> (f32, f32) {
+    let count = values.len().max(1) as f32;
+    let mean = values.iter().sum::<f32>() / count;
+    let variance = values
+        .iter()
+        .map(|value| (value - mean).powi(2))
+        .sum::<f32>()
+        / count;
+    (mean, variance)
+}
+
+/// The largest gap between the cumulative distributions of two samples
+fn ks_statistic(a: &[f32], b: &[f32]) -> f32 {
+    let mut a = a.to_vec();
+    let mut b = b.to_vec();
+    a.sort_by(|x, y| x.total_cmp(y));
+    b.sort_by(|x, y| x.total_cmp(y));
+
+    let (mut i, mut j) = (0, 0);
+    let mut statistic: f32 = 0.0;
+    while i < a.len() && j < b.len() {
+        // Step past every copy of the smallest remaining value in both samples
+        let value = a[i].min(b[j]);
+        while i < a.len() && a[i] <= value {
+            i += 1;
+        }
+        while j < b.len() && b[j] <= value {
+            j += 1;
+        }
+        statistic = statistic.max((ratio(i, a.len()) - ratio(j, b.len())).abs());
+    }
+    statistic
+}
+
+/// The biased estimate of the squared maximum mean discrepancy, with a gaussian kernel
+/// whose bandwidth is the median distance between all the rows
+fn mmd(a: &[Box<[f32]>], b: &[Box<[f32]>]) -> f32 {
+    let squared_distance = |x: &[f32], y: &[f32]| -> f32 {
+        x.iter().zip(y.iter()).map(|(x, y)| (x - y).powi(2)).sum()
+    };
+    let rows: Vec<&[f32]> = a.iter().chain(b.iter()).map(|row| row.as_ref()).collect();
+    let mut distances: Vec<f32> = (0..rows.len())
+        .flat_map(|i| (i + 1..rows.len()).map(move |j| (i, j)))
+        .map(|(i, j)| squared_distance(rows[i], rows[j]))
+        .collect();
+    distances.sort_by(|x, y| x.total_cmp(y));
+    let bandwidth = match distances.get(distances.len() / 2) {
+        Some(median) if *median > 0.0 => *median,
+        _ => 1.0,
+    };
+
+    let mean_kernel = |x: &[Box<[f32]>], y: &[Box<[f32]>]| -> f32 {
+        x.iter()
+            .flat_map(|x| y.iter().map(move |y| (x, y)))
+            .map(|(x, y)| (-squared_distance(x, y) / (2.0 * bandwidth)).exp())
+            .sum::<f32>()
+            / (x.len() * y.len()) as f32
+    };
+    (mean_kernel(a, a) + mean_kernel(b, b) - 2.0 * mean_kernel(a, b)).max(0.0)
+}
+
+fn f1(precision: f32, recall: f32) -> f32 {
+    if precision + recall == 0.0 {
+        0.0
+    } else {
+        2.0 * precision * recall / (precision + recall)
+    }
+}
+
+fn average(classes: &[ClassMetrics], weight: impl Fn(&ClassMetrics) -> f32) -> AverageMetrics {
+    let total_weight: f32 = classes.iter().map(&weight).sum();
+    let weighted = |metric: fn(&ClassMetrics) -> f32| {
+        if total_weight == 0.0 {
+            return 0.0;
+        }
+        classes
+            .iter()
+            .map(|class| metric(class) * weight(class))
+            .sum::<f32>()
+            / total_weight
+    };
+
+    AverageMetrics {
+        precision: weighted(|class| class.precision),
+        recall: weighted(|class| class.recall),
+        f1: weighted(|class| class.f1),
+    }
+}
@@ -0,0 +1,532 @@
+use crate::{
+    activation::ActivationFunction,
+    builder::NetworkBuilder,
+    checkpoint::{Checkpoint, TrainState},
+    config::{ConfigField, TrainConfig},
+    error::DarjeelingError,
+    layer::{self, LayerSpec, NodeArray},
+    metrics::{self, MultiLabelReport},
+    neural_network::Model,
+    node::Node,
+    preprocessing::{check_missing, pipeline::Pipeline, Transform},
+    series::MultiLabelSeries,
+    trainer::{self, Gradient, Supervised},
+};
+use serde::{Deserialize, Serialize};
+use std::time::Instant;
+
+/// Keeps the binary cross-entropy finite when an answer node is completely sure and wrong
+const PROBABILITY_EPSILON: f32 = 1e-7;
+
+/// The threshold of every label until thresholds are set or fitted
+const DEFAULT_THRESHOLD: f32 = 0.5;
+
+/// The multi-label Neural Network struct, which gives each input any number of labels.
+///
+/// Every answer node is an independent sigmoid output, the probability that the input has its label,
+/// trained with binary cross-entropy. An input is given every label whose output reaches the label's threshold.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct MultiLabelNetwork {
+    node_array: Box<[Box<[Node]>]>,
+    layers: Box<[LayerSpec]>,
+    activation_function: Option<ActivationFunction>,
+    /// The config the network was last trained with
+    config: Option<TrainConfig>,
+    /// Every input goes through it before it reaches the network
+    pipeline: Option<Pipeline>,
+    /// The labels of the answer nodes, in order
+    labels: Option<Box<[String]>>,
+    /// The output each answer node needs to reach for its label to be given, in order
+    thresholds: Option<Box<[f32]>>,
+}
+
+impl MultiLabelNetwork {
+    /// Constructor function for a multi-label neural network
+    /// Fills a Neural Network's node_array with empty nodes.
+    /// Initializes random starting link and bias weights between -.5 and .5
+    ///
+    /// ## Params
+    /// - Inputs: The number of sensors in the input layer
+    /// - Hidden: The number of hidden nodes per hidden layer
+    /// - Answer: The number of answer nodes, or possible labels
+    /// - Hidden Layers: The number of different hidden layers
+    /// - Activation Function: Which activation function is used by the hidden layers, the answer layer always uses the sigmoid function
+    ///
+    /// Every hidden layer has the same width, use a [`NetworkBuilder`] for anything else.
+    ///
+    /// ## Examples
+    /// ``` rust
+    /// use darjeeling::{activation::ActivationFunction, multilabel::MultiLabelNetwork};
+    ///
+    /// let mut net = MultiLabelNetwork::new(10, 40, 4, 1, Some(ActivationFunction::Tanh));
+    /// ```
+    pub fn new(
+        input_nodes: usize,
+        hidden_nodes: usize,
+        answer_nodes: usize,
+        hidden_layers: usize,
+        activation_function: Option<ActivationFunction>,
+    ) -> MultiLabelNetwork {
+        let mut builder = (0..hidden_layers.max(1))
+            .fold(NetworkBuilder::new(input_nodes), |builder, _| {
+                builder.hidden(hidden_nodes)
+            })
+            .hidden(answer_nodes);
+        if let Some(activation_function) = activation_function {
+            builder = builder.activation(activation_function);
+        }
+
+        builder
+            .build_multi_label()
+            .expect("Every layer of the network needs at least one node")
+    }
+
+    pub(crate) fn from_parts(
+        node_array: Box<[Box<[Node]>]>,
+        mut layers: Box<[LayerSpec]>,
+        activation_function: Option<ActivationFunction>,
+    ) -> MultiLabelNetwork {
+        if let Some(answer) = layers.last_mut() {
+            answer.activation = Some(ActivationFunction::Sigmoid);
+        }
+
+        MultiLabelNetwork {
+            node_array,
+            layers,
+            activation_function,
+            config: None,
+            pipeline: None,
+            labels: None,
+            thresholds: None,
+        }
+    }
+
+    /// The config the network was last trained with, saved models keep it
+    pub fn config(&self) -> Option<&TrainConfig> {
+        self.config.as_ref()
+    }
+
+    /// Sets the pipeline every input goes through before it reaches the network.
+    /// An unfitted pipeline is fitted on the training data the next time the network is trained,
+    /// and it's saved with the model. Pipelines that encode categorical columns can't be used,
+    /// since there are no dataframes to fit them on.
+    pub fn set_pipeline(&mut self, pipeline: Pipeline) {
+        self.pipeline = Some(pipeline);
+    }
+
+    /// The pipeline every input goes through, saved models keep it
+    pub fn pipeline(&self) -> Option<&Pipeline> {
+        self.pipeline.as_ref()
+    }
+
+    /// The labels of the answer nodes, in order, once the network has been trained
+    pub fn labels(&self) -> Option<&[String]> {
+        self.labels.as_deref()
+    }
+
+    /// The threshold of each label, in order. Every label's threshold is 0.5 until they're set or fitted.
+    pub fn thresholds(&self) -> Box<[f32]> {
+        match &self.thresholds {
+            Some(thresholds) => thresholds.clone(),
+            None => vec![DEFAULT_THRESHOLD; self.answer_width()].into_boxed_slice(),
+        }
+    }
+
+    /// Sets the output each answer node needs to reach for its label to be given
+    ///
+    /// ## Err
+    /// ### InvalidThresholds
+    /// There isn't one threshold per answer node
+    pub fn set_thresholds(&mut self, thresholds: &[f32]) -> Result<(), DarjeelingError> {
+        if thresholds.len() != self.answer_width() {
+            return Err(DarjeelingError::InvalidThresholds(format!(
+                "there are {} thresholds, but {} answer nodes",
+                thresholds.len(),
+                self.answer_width()
+            )));
+        }
+        self.thresholds = Some(thresholds.into());
+        Ok(())
+    }
+
+    /// Sets the threshold of each label to the one that gives it the best f1 score on labelled data,
+    /// which should be a validation set rather than the training data.
+    /// Labels that no input has keep their threshold.
+    ///
+    /// ## Returns
+    /// The new thresholds, in the order of the labels
+    ///
+    /// ## Err
+    /// The errors of [`scores`](MultiLabelNetwork::scores), and
+    /// ### ModelMissingCategories
+    /// The network hasn't been trained, so its answer nodes have no labels
+    pub fn fit_thresholds(
+        &mut self,
+        data: &[MultiLabelSeries],
+    ) -> Result<Box<[f32]>, DarjeelingError> {
+        let labels = self
+            .labels
+            .clone()
+            .ok_or(DarjeelingError::ModelMissingCategories)?;
+        let scores = self.scores(data)?;
+        let mut thresholds = self.thresholds();
+
+        for (i, label) in labels.iter().enumerate() {
+            let has: Box<[bool]> = data.iter().map(|series| series.has(label)).collect();
+            let label_scores: Box<[f32]> = scores.iter().map(|score| score[i]).collect();
+            if let Some(threshold) = metrics::best_threshold(&has, &label_scores) {
+                thresholds[i] = threshold;
+            }
+        }
+        self.thresholds = Some(thresholds.clone());
+        Ok(thresholds)
+    }
+
+    /// Trains the neural network model to give each input its labels
+    ///
+    /// ## Params
+    /// - Data: List of inputs, with every one of their labels
+    /// - Labels: List of Strings, each denoting the label of an answer node.
+    ///   The number of answer nodes should be the same as the number of labels
+    /// - Config: The hyperparameters of the training, see [`TrainConfig`].
+    ///   The model is written to the config's save path once training finishes, if it has one,
+    ///   and checkpoints that [`resume_from`](MultiLabelNetwork::resume_from) continues are written to its checkpoint path.
+    ///   Its target accuracy is compared to the subset accuracy.
+    ///
+    /// ## Returns
+    /// The fallible:
+    /// - save path the model was written to, none if the config has no save path
+    /// - the percentage of inputs given exactly their labels on the last epoch
+    /// - the mean binary cross-entropy of the last epoch
+    ///
+    /// ## Err
+    /// - ### ModelMissingActivationFunction
+    /// The network has no activation function to train with
+    /// - ### InvalidLayerSpec
+    /// The answer layer doesn't have one node per label, or an input isn't as wide as the input layer
+    /// - ### CategoryDoesNotExist
+    /// An input has a label that isn't one of the labels
+    /// - ### NanEncountered
+    /// An input has a missing (NaN) feature, or the training diverged
+    /// - ### WriteModelFailed
+    /// There was a problem when saving the model to a file
+    /// - ### UnsupportedConfig
+    /// The config has class weights, or a loss other than the default, since the binary cross-entropy is always minimized
+    ///
+    /// ## Examples
+    /// ```ignore
+    /// let labels: Box<[String]> = vec!["rust".to_string(), "tutorial".to_string()].into();
+    /// let data = vec![MultiLabelSeries::new(features, &["rust", "tutorial"])];
+    /// let mut net = MultiLabelNetwork::new(features.len(), 16, 2, 1, Some(ActivationFunction::Tanh));
+    /// let (model_name, subset_accuracy, loss) = net.train(&data, labels, &TrainConfig::default()).unwrap();
+    /// ```
+    pub fn train(
+        &mut self,
+        data: &[MultiLabelSeries],
+        labels: Box<[String]>,
+        config: &TrainConfig,
+    ) -> Result<(Option<String>, f32, f32), DarjeelingError> {
+        self.train_from(data, labels, config, TrainState::new(config.seed))
+    }
+
+    /// Continues a training run from a checkpoint written during [`train`](MultiLabelNetwork::train),
+    /// exactly where it stopped.
+    /// The config saved in the checkpoint is used, so more epochs can be added by changing its `epochs`.
+    ///
+    /// ## Params
+    /// - Checkpoint: A checkpoint read with [`Checkpoint::read`], its categories are the labels
+    /// - Data: The same data the interrupted run was trained on
+    ///
+    /// ## Returns
+    /// The trained network, followed by the same values as [`train`](MultiLabelNetwork::train)
+    pub fn resume_from(
+        checkpoint: Checkpoint<MultiLabelNetwork>,
+        data: &[MultiLabelSeries],
+    ) -> Result<(MultiLabelNetwork, Option<String>, f32, f32), DarjeelingError> {
+        let Checkpoint {
+            mut network,
+            categories,
+            config,
+            state,
+        } = checkpoint;
+
+        let (model_name, subset_accuracy, loss) =
+            network.train_from(data, categories, &config, state)?;
+        Ok((network, model_name, subset_accuracy, loss))
+    }
+
+    /// Runs the training loop from the given state
+    fn train_from(
+        &mut self,
+        data: &[MultiLabelSeries],
+        labels: Box<[String]>,
+        config: &TrainConfig,
+        mut state: TrainState,
+    ) -> Result<(Option<String>, f32, f32), DarjeelingError> {
+        config.check_unused(
+            "multi-label training",
+            &[ConfigField::ClassWeights, ConfigField::Loss],
+        )?;
+        let activation_function = match self.activation_function {
+            Some(s) => s,
+            None => return Err(DarjeelingError::ModelMissingActivationFunction),
+        };
+        if labels.len() != self.answer_width() {
+            return Err(DarjeelingError::InvalidLayerSpec(format!(
+                "the answer layer has {} nodes, but there are {} labels",
+                self.answer_width(),
+                labels.len()
+            )));
+        }
+        let targets: Box<[Box<[f32]>]> = data
+            .iter()
+            .map(|series| targets(&labels, series))
+            .collect::<Result<_, _>>()?;
+
+        if let Some(pipeline) = self
+            .pipeline
+            .as_mut()
+            .filter(|pipeline| !pipeline.is_fitted())
+        {
+            pipeline.fit_rows(
+                &data
+                    .iter()
+                    .map(|series| series.data.as_ref())
+                    .collect::<Vec<&[f32]>>(),
+            )?;
+        }
+        let rows = self.preprocess(data)?;
+        check_missing(&rows.iter().map(|row| row.as_ref()).collect::<Vec<&[f32]>>())?;
+        rows.iter()
+            .try_for_each(|row| layer::check_input(&self.node_array, row))?;
+        self.labels = Some(labels.clone());
+        let thresholds = self.thresholds();
+
+        let mut last_checkpoint = Instant::now();
+        let mut subset_accuracy = 0.0;
+        let mut loss = 0.0;
+        while state.epoch < config.epochs {
+            let outputs;
+            (loss, outputs) = trainer::epoch(
+                self,
+                activation_function,
+                &rows,
+                config,
+                &mut state,
+                |i, outputs| {
+                    // The derivative of the binary cross-entropy with respect to each answer node's weighted input,
+                    // the sigmoid's derivative cancels out so it's just the output minus the target
+                    let answer_grads = outputs
+                        .iter()
+                        .zip(targets[i].iter())
+                        .map(|(output, target)| output - target)
+                        .collect();
+                    (
+                        binary_cross_entropy(outputs, &targets[i]),
+                        Gradient::Weighted(answer_grads),
+                    )
+                },
+            )?;
+            let exact = outputs
+                .iter()
+                .zip(targets.iter())
+                .filter(|(outputs, targets)| {
+                    outputs
+                        .iter()
+                        .zip(thresholds.iter())
+                        .zip(targets.iter())
+                        .all(|((output, threshold), target)| {
+                            (*output >= *threshold) == (*target == 1.0)
+                        })
+                })
+                .count();
+            subset_accuracy = exact as f32 / rows.len().max(1) as f32 * 100.0;
+
+            let stop = trainer::end_epoch(
+                config,
+                &mut state,
+                loss,
+                Some(subset_accuracy),
+                format_args!("Training Subset Accuracy: {:?}", subset_accuracy),
+            )?;
+
+            trainer::checkpoint(self, &labels, config, &state, &mut last_checkpoint)?;
+            if stop {
+                break;
+            }
+        }
+
+        self.config = Some(config.clone());
+        let model_name = trainer::save(self, config)?;
+
+        println!(
+            "Training: Finished with a subset accuracy of {:?} percent after {:?} epochs\nloss: {}",
+            subset_accuracy, state.epoch, loss
+        );
+
+        Ok((model_name, subset_accuracy, loss))
+    }
+
+    /// The probability of each label for each input, in the order of the labels
+    ///
+    /// ## Err
+    /// ### ModelMissingActivationFunction
+    /// The network has no activation function
+    /// ### TransformFailed
+    /// An input doesn't have the features the pipeline was fitted on
+    /// ### InvalidLayerSpec
+    /// An input isn't as wide as the input layer once it's been through the pipeline
+    pub fn scores(
+        &mut self,
+        data: &[MultiLabelSeries],
+    ) -> Result<Box<[Box<[f32]>]>, DarjeelingError> {
+        let rows = self.preprocess(data)?;
+        rows.iter()
+            .map(|row| {
+                layer::outputs(
+                    &mut self.node_array,
+                    &self.layers,
+                    self.activation_function,
+                    row,
+                )
+            })
+            .collect()
+    }
+
+    /// Labels a single raw input with a trained model, running it through the pipeline first
+    ///
+    /// ## Returns
+    /// Every label whose probability reaches its threshold, in the order of the labels
+    ///
+    /// ## Err
+    /// ### ModelMissingActivationFunction
+    /// The network has no activation function
+    /// ### ModelMissingCategories
+    /// The network hasn't been trained, so its answer nodes have no labels
+    /// ### TransformFailed
+    /// The input doesn't have the features the pipeline was fitted on
+    /// ### InvalidLayerSpec
+    /// The input isn't as wide as the input layer once it's been through the pipeline
+    pub fn predict(&mut self, data: &[f32]) -> Result<Box<[String]>, DarjeelingError> {
+        let data = match &self.pipeline {
+            Some(pipeline) => pipeline.transform_row(data)?,
+            None => data.into(),
+        };
+        let outputs = layer::outputs(
+            &mut self.node_array,
+            &self.layers,
+            self.activation_function,
+            &data,
+        )?;
+        self.given_labels(&outputs)
+    }
+
+    /// Labels every input with a trained model
+    ///
+    /// ## Returns
+    /// The labels given to each input, in the same order as the data
+    ///
+    /// ## Err
+    /// The errors of [`predict`](MultiLabelNetwork::predict)
+    pub fn test(
+        &mut self,
+        data: &[MultiLabelSeries],
+    ) -> Result<Box<[Box<[String]>]>, DarjeelingError> {
+        self.scores(data)?
+            .iter()
+            .map(|outputs| self.given_labels(outputs))
+            .collect()
+    }
+
+    /// Evaluates a trained model against labelled data
+    ///
+    /// ## Returns
+    /// A report with per label metrics, the hamming loss, the subset accuracy, and micro and macro averages
+    ///
+    /// ## Err
+    /// The errors of [`predict`](MultiLabelNetwork::predict), and
+    /// ### CategoryDoesNotExist
+    /// An input has a label that isn't one of the labels
+    ///
+    /// ## Examples
+    /// ```ignore
+    /// let report = net.evaluate(&data).unwrap();
+    /// println!("{}", report);
+    /// ```
+    pub fn evaluate(
+        &mut self,
+        data: &[MultiLabelSeries],
+    ) -> Result<MultiLabelReport, DarjeelingError> {
+        let predictions = self.test(data)?;
+        let answers: Box<[Box<[String]>]> =
+            data.iter().map(|series| series.answers.clone()).collect();
+        let labels = self
+            .labels
+            .as_ref()
+            .ok_or(DarjeelingError::ModelMissingCategories)?;
+        MultiLabelReport::new(labels, &answers, &predictions)
+    }
+
+    /// Runs inputs through the pipeline, if the network has one
+    fn preprocess(&self, data: &[MultiLabelSeries]) -> Result<Box<[Box<[f32]>]>, DarjeelingError> {
+        data.iter()
+            .map(|series| match &self.pipeline {
+                Some(pipeline) => pipeline.transform_row(&series.data),
+                None => Ok(series.data.clone()),
+            })
+            .collect()
+    }
+
+    /// The labels whose outputs reach their thresholds
+    fn given_labels(&self, outputs: &[f32]) -> Result<Box<[String]>, DarjeelingError> {
+        let labels = self
+            .labels
+            .as_ref()
+            .ok_or(DarjeelingError::ModelMissingCategories)?;
+        Ok(outputs
+            .iter()
+            .zip(self.thresholds().iter())
+            .zip(labels.iter())
+            .filter(|((output, threshold), _)| **output >= **threshold)
+            .map(|(_, label)| label.clone())
+            .collect())
+    }
+
+    fn answer_width(&self) -> usize {
+        self.node_array.last().map_or(0, |layer| layer.len())
+    }
+
+    /// Sets the activation function of the hidden layers, the answer layer always uses the sigmoid function
+    pub fn set_activation_func(&mut self, new_activation_function: ActivationFunction) {
+        self.activation_function = Some(new_activation_function);
+    }
+}
+
+impl Model for MultiLabelNetwork {}
+
+impl Supervised for MultiLabelNetwork {
+    fn parts(&mut self) -> (&mut NodeArray, &[LayerSpec]) {
+        (&mut self.node_array, &self.layers)
+    }
+}
+
+/// One for each label the input has and zero for the rest, in the order of the labels
+fn targets(labels: &[String], series: &MultiLabelSeries) -> Result<Box<[f32]>, DarjeelingError> {
+    let mut targets = vec![0.0; labels.len()].into_boxed_slice();
+    for answer in series.answers.iter() {
+        targets[metrics::category_index(labels, answer)?] = 1.0;
+    }
+    Ok(targets)
+}
+
+/// The binary cross-entropy of every answer node, summed
+fn binary_cross_entropy(outputs: &[f32], targets: &[f32]) -> f32 {
+    outputs
+        .iter()
+        .zip(targets.iter())
+        .map(|(output, target)| {
+            let output = output.clamp(PROBABILITY_EPSILON, 1.0 - PROBABILITY_EPSILON);
+            -(target * output.ln() + (1.0 - target) * (1.0 - output).ln())
+        })
+        .sum()
+}
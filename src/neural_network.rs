@@ -1,6 +1,10 @@
 use crate::activation::ActivationFunction;
+use crate::config::TrainConfig;
 use crate::error::DarjeelingError;
 use crate::series::Series;
+use rand::Rng;
+use serde::{de::DeserializeOwned, Serialize};
+use std::fs;
 
 pub trait NeuralNetwork {
     fn new(
@@ -13,17 +17,79 @@ pub trait NeuralNetwork {
 
     fn train(
         &mut self,
-        data: &Box<[Series]>,
+        data: &[Series],
         categories: Box<[String]>,
-        learning_rate: f32,
-        name: &str,
-        target_err_percent: f32,
-        write: bool,
+        config: &TrainConfig,
     ) -> Result<(Option<String>, f32, f32), DarjeelingError>;
 
     fn test(
         &mut self,
-        data: &Box<[Series]>,
+        data: &[Series],
         categories: Box<[String]>,
     ) -> Result<Vec<String>, DarjeelingError>;
 }
+
+/// Writing trained models to files and reading them back, every kind of network can be saved
+///
+/// ## Examples
+/// ```ignore
+/// use darjeeling::{categorize::CatNetwork, neural_network::Model};
+///
+/// net.save_model("digits.darj").unwrap();
+/// let mut net = CatNetwork::read_model("digits.darj".to_string()).unwrap();
+/// ```
+pub trait Model: Serialize + DeserializeOwned {
+    /// Serializes a trained model so it can be used later
+    ///
+    /// ## Returns
+    /// The name of the model, the given name with a random number and the .darj extension appended
+    ///
+    /// ## Err
+    /// ### SaveModelFailed:
+    /// The model couldn't be serialized or the file couldn't be written
+    fn write_model(&self, name: &str) -> Result<String, DarjeelingError> {
+        let mut rng = rand::thread_rng();
+        let num = rng.gen_range(0..i32::MAX);
+
+        let model_name = format!("{}_{}.darj", name, num);
+        self.save_model(&model_name)?;
+        Ok(model_name)
+    }
+
+    /// Serializes a model to exactly the given path
+    ///
+    /// ## Err
+    /// ### SaveModelFailed:
+    /// The model couldn't be serialized or the file couldn't be written
+    fn save_model(&self, path: &str) -> Result<(), DarjeelingError> {
+        let bin = match bincode::serialize(self) {
+            Ok(v) => v,
+            Err(err) => return Err(DarjeelingError::SaveModelFailed(err.to_string())),
+        };
+
+        match fs::write(path, bin) {
+            Ok(_) => Ok(()),
+            Err(err) => Err(DarjeelingError::SaveModelFailed(err.to_string())),
+        }
+    }
+
+    /// Reads a file containing a serialized model
+    ///
+    /// ## Params
+    /// - Model Name: The name(or more helpfully the path) of the model to be read
+    ///
+    /// ## Err
+    /// ### LoadModelFailed:
+    /// The file cannnot be read, or doesn't contain a valid serialized model of this kind
+    fn read_model(model_name: String) -> Result<Self, DarjeelingError> {
+        let read_bin = match fs::read(&model_name) {
+            Ok(bin) => bin,
+            Err(err) => return Err(DarjeelingError::LoadModelFailed(err.to_string())),
+        };
+
+        match bincode::deserialize(&read_bin) {
+            Ok(net) => Ok(net),
+            Err(err) => Err(DarjeelingError::LoadModelFailed(err.to_string())),
+        }
+    }
+}
@@ -1,4 +1,4 @@
-use crate::{activation::ActivationFunction, dbg_println, DEBUG};
+use crate::{dbg_println, DEBUG};
 use serde::{Deserialize, Serialize};
 
 /// Represents a node in the network
@@ -7,14 +7,26 @@ pub struct Node {
     pub link_weights: Box<[f32]>,
     pub link_vals: Box<[f32]>,
     pub b_weight: f32,
-    #[serde(skip_serializing)]
+    #[serde(skip)]
     pub err_sig: Option<f32>,
-    #[serde(skip_serializing)]
+    #[serde(skip)]
     pub correct_answer: Option<f32>,
-    #[serde(skip_serializing)]
+    #[serde(skip)]
     pub category: Option<String>,
-    #[serde(skip_serializing)]
+    #[serde(skip)]
     pub cached_output: Option<f32>,
+    /// The weighted input of the node before normalization and activation
+    #[serde(skip)]
+    pub cached_input: Option<f32>,
+    /// The factor dropout multiplied this node's output by, if dropout was applied
+    #[serde(skip)]
+    pub dropout_mask: Option<f32>,
+    /// The derivative of the loss with respect to each link weight, summed over the current batch
+    #[serde(skip)]
+    pub link_grads: Box<[f32]>,
+    /// The derivative of the loss with respect to the bias weight, summed over the current batch
+    #[serde(skip)]
+    pub b_grad: f32,
 }
 
 impl Node {
@@ -27,94 +39,36 @@ impl Node {
             link_weights,
             link_vals,
             b_weight,
-            err_sig: None,
-            cached_output: None,
-            correct_answer: None,
-            category: None,
+            ..Default::default()
         }
     }
 
-    fn input(&mut self) -> f32 {
+    /// The weighted sum of the node's links plus its bias
+    pub fn input(&self) -> f32 {
         (0..self.link_weights.len())
-            .into_iter()
             .map(|i| {
                 dbg_println!("Link Val: {:?}", self.link_vals[i]);
                 self.link_vals[i] * self.link_weights[i]
             })
             .sum::<f32>()
-            * self.b_weight
+            + self.b_weight
     }
 
-    pub fn output(&mut self, activation: ActivationFunction) -> f32 {
-        match activation {
-            ActivationFunction::Sigmoid => Node::sigmoid(self.input()),
-            ActivationFunction::Linear => Node::linear(self.input()),
-            ActivationFunction::Tanh => Node::tanh(self.input()),
-            // ActivationFunction::Step => Node::step(self.input()),
-        }
-    }
-
-    pub fn compute_answer_err_sig(&mut self, activation: ActivationFunction) -> f32 {
-        let cached_output = self
-            .cached_output
-            .expect("Answer Node Missing Cached Output");
-
-        let derivative: f32 = match activation {
-            ActivationFunction::Sigmoid => cached_output * (1.0 - cached_output),
-            ActivationFunction::Linear => 2.0,
-            // Unsupported
-            ActivationFunction::Tanh => 1.0, //- unsafe { std::intrinsics::powf32(y, 2.0) };
-        };
-
-        self.err_sig = Some((self.correct_answer.unwrap() - cached_output) * cached_output * derivative);
-        self.err_sig.unwrap()
-    }
-
-    pub fn compute_answer_err_sig_gen(&mut self, mse: f32, activation: ActivationFunction) -> f32 {
-        // This is where the derivative of the activation function goes I think
-        let output = self
-            .cached_output
-            .expect("Answer Node Missing Cached Output");
-        let derivative: f32 = match activation {
-            ActivationFunction::Sigmoid => output * (1.0 - output),
-            ActivationFunction::Linear => 2.0,
-            ActivationFunction::Tanh => 1.0, //- unsafe { std::intrinsics::powf32(y, 2.0) };
-        };
-        self.err_sig = Some(mse * derivative);
-        dbg_println!("Err Signal Post: {:?}", self.err_sig.unwrap());
-        self.err_sig.unwrap()
-    }
-
-    pub fn adjust_weights(&mut self, learning_rate: f32) {
+    /// Adds the gradients implied by the node's error signal to the gradients of the current batch
+    pub fn accumulate_grads(&mut self) {
         let err_sig = self.err_sig.expect("Node has no error signal");
-        self.b_weight += err_sig * learning_rate;
-        self.link_weights = self.link_weights
-            .iter()
-            .enumerate()
-            .map(|(link, link_weight)|
-                link_weight + err_sig * self.link_vals[link] * learning_rate)
-            .collect();
-    }
-
-    fn sigmoid(x: f32) -> f32 {
-        1.0 / (1.0 + ((-x).exp()))
-    }
-
-    fn linear(x: f32) -> f32 {
-        2.0 * x
-    }
-
-    fn tanh(x: f32) -> f32 {
-        let e = std::f64::consts::E as f32;
-
-        2.00 / (1.00 + e.powf(-2.00 * x)) - 1.00
+        if self.link_grads.len() != self.link_weights.len() {
+            self.link_grads = vec![0.0; self.link_weights.len()].into_boxed_slice();
+        }
+        self.b_grad -= err_sig;
+        self.link_grads
+            .iter_mut()
+            .zip(self.link_vals.iter())
+            .for_each(|(grad, link_val)| *grad -= err_sig * link_val);
     }
 
-    fn step(x: f32) -> f32 {
-        if x < 0.00 {
-            -1.00
-        } else {
-            1.00
-        }
+    pub fn clear_grads(&mut self) {
+        self.link_grads.iter_mut().for_each(|grad| *grad = 0.0);
+        self.b_grad = 0.0;
     }
 }
@@ -0,0 +1,113 @@
+use crate::node::Node;
+use serde::{Deserialize, Serialize};
+
+/// How the gradients of a batch are turned into weight adjustments
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Default)]
+pub enum Optimizer {
+    /// Plain stochastic gradient descent
+    #[default]
+    Sgd,
+    /// Gradient descent that keeps a decaying sum of the previous adjustments
+    Momentum { momentum: f32 },
+    Adam {
+        beta1: f32,
+        beta2: f32,
+        epsilon: f32,
+    },
+}
+
+impl Optimizer {
+    /// Adam with the hyperparameters recommended by its authors
+    pub fn adam() -> Optimizer {
+        Optimizer::Adam {
+            beta1: 0.9,
+            beta2: 0.999,
+            epsilon: 1e-8,
+        }
+    }
+}
+
+/// The running values an optimizer keeps for every weight of a network between batches
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq)]
+pub struct OptimizerState {
+    steps: u64,
+    first_moments: Vec<f32>,
+    second_moments: Vec<f32>,
+}
+
+impl OptimizerState {
+    pub fn new() -> OptimizerState {
+        OptimizerState::default()
+    }
+
+    /// The number of batches the optimizer has adjusted the weights for
+    pub fn steps(&self) -> u64 {
+        self.steps
+    }
+
+    /// Adjusts every link and bias weight of the network with the gradients accumulated over a batch,
+    /// then clears the gradients
+    pub(crate) fn step(
+        &mut self,
+        optimizer: Optimizer,
+        node_array: &mut [Box<[Node]>],
+        learning_rate: f32,
+        batch_len: usize,
+    ) {
+        let weight_count: usize = node_array
+            .iter()
+            .flat_map(|layer| layer.iter())
+            .map(|node| node.link_weights.len() + 1)
+            .sum();
+        if self.first_moments.len() != weight_count {
+            self.first_moments = vec![0.0; weight_count];
+            self.second_moments = vec![0.0; weight_count];
+        }
+        self.steps += 1;
+
+        let scale = 1.0 / batch_len.max(1) as f32;
+        let mut index = 0;
+        for node in node_array.iter_mut().flat_map(|layer| layer.iter_mut()) {
+            if node.link_grads.len() != node.link_weights.len() {
+                // The node hasn't accumulated anything since it was created or read
+                index += node.link_weights.len() + 1;
+                continue;
+            }
+
+            for link in 0..node.link_weights.len() {
+                let adjustment = self.adjustment(optimizer, index, node.link_grads[link] * scale);
+                node.link_weights[link] -= learning_rate * adjustment;
+                index += 1;
+            }
+            let adjustment = self.adjustment(optimizer, index, node.b_grad * scale);
+            node.b_weight -= learning_rate * adjustment;
+            index += 1;
+
+            node.clear_grads();
+        }
+    }
+
+    /// The amount the weight at `index` should move against its gradient, before the learning rate is applied
+    fn adjustment(&mut self, optimizer: Optimizer, index: usize, grad: f32) -> f32 {
+        match optimizer {
+            Optimizer::Sgd => grad,
+            Optimizer::Momentum { momentum } => {
+                self.first_moments[index] = momentum * self.first_moments[index] + grad;
+                self.first_moments[index]
+            }
+            Optimizer::Adam {
+                beta1,
+                beta2,
+                epsilon,
+            } => {
+                self.first_moments[index] =
+                    beta1 * self.first_moments[index] + (1.0 - beta1) * grad;
+                self.second_moments[index] =
+                    beta2 * self.second_moments[index] + (1.0 - beta2) * grad * grad;
+                let first = self.first_moments[index] / (1.0 - beta1.powi(self.steps as i32));
+                let second = self.second_moments[index] / (1.0 - beta2.powi(self.steps as i32));
+                first / (second.sqrt() + epsilon)
+            }
+        }
+    }
+}
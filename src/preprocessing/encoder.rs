@@ -0,0 +1,474 @@
+use crate::{
+    dataframe::{Column, DataFrame, Value},
+    error::DarjeelingError,
+};
+use serde::{Deserialize, Serialize};
+
+/// What an encoder does with a category it wasn't fitted on
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Unknown {
+    /// Fail with a `TransformFailed` error
+    #[default]
+    Error,
+    /// Encode it as no category: all zeros for one-hot, -1 for ordinal and the overall mean for target
+    Ignore,
+}
+
+/// How a categorical column is turned into numbers, see [`FrameEncoder::fit`]
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+pub enum Encoding {
+    /// A column per category, one for the row's category and zero for the rest
+    OneHot,
+    /// The category's position among the sorted categories
+    Ordinal,
+    /// A fixed number of columns, each category counting towards the column its hash lands in.
+    /// Nothing has to be fitted, so there are no unknown categories.
+    Hashing { buckets: usize },
+    /// The mean target of the category's rows, pulled towards the overall mean for rare categories.
+    /// Smoothing is how many rows a category needs before its own mean counts as much as the overall one.
+    Target { smoothing: f32 },
+}
+
+/// Encodes categories as one-hot vectors
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct OneHotEncoder {
+    /// Sorted, the position of a category is its column
+    pub categories: Box<[String]>,
+    pub unknown: Unknown,
+}
+
+/// Encodes categories as their position
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct OrdinalEncoder {
+    /// The position of a category is its encoding
+    pub categories: Box<[String]>,
+    pub unknown: Unknown,
+}
+
+/// Encodes categories by hashing them into buckets
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct HashingEncoder {
+    pub buckets: usize,
+}
+
+/// Encodes categories as the smoothed mean of their targets
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct TargetEncoder {
+    /// Sorted, with the encoding of each category at the same position in `means`
+    pub categories: Box<[String]>,
+    pub means: Box<[f32]>,
+    /// The mean target of every row
+    pub prior: f32,
+    pub unknown: Unknown,
+}
+
+/// Any of the fitted encoders
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub enum Encoder {
+    OneHot(OneHotEncoder),
+    Ordinal(OrdinalEncoder),
+    Hashing(HashingEncoder),
+    Target(TargetEncoder),
+}
+
+impl OneHotEncoder {
+    /// Fits the encoder to every category in the values
+    pub fn fit(values: &[String], unknown: Unknown) -> OneHotEncoder {
+        OneHotEncoder {
+            categories: sorted_categories(values),
+            unknown,
+        }
+    }
+
+    /// ## Err
+    /// ### TransformFailed
+    /// The category is unknown and the unknown policy is [`Unknown::Error`]
+    pub fn encode(&self, value: &str) -> Result<Box<[f32]>, DarjeelingError> {
+        let mut encoded = vec![0.0; self.categories.len()];
+        match self
+            .categories
+            .binary_search_by(|category| category.as_str().cmp(value))
+        {
+            Ok(i) => encoded[i] = 1.0,
+            Err(_) => unknown(self.unknown, value)?,
+        }
+        Ok(encoded.into_boxed_slice())
+    }
+}
+
+impl OrdinalEncoder {
+    /// Fits the encoder to every category in the values, which are numbered in sorted order.
+    /// Create the encoder directly to use a different order.
+    pub fn fit(values: &[String], unknown: Unknown) -> OrdinalEncoder {
+        OrdinalEncoder {
+            categories: sorted_categories(values),
+            unknown,
+        }
+    }
+
+    /// ## Err
+    /// ### TransformFailed
+    /// The category is unknown and the unknown policy is [`Unknown::Error`]
+    pub fn encode(&self, value: &str) -> Result<f32, DarjeelingError> {
+        match self
+            .categories
+            .iter()
+            .position(|category| category == value)
+        {
+            Some(i) => Ok(i as f32),
+            None => unknown(self.unknown, value).map(|_| -1.0),
+        }
+    }
+}
+
+impl HashingEncoder {
+    /// ## Err
+    /// ### TransformFailed
+    /// There are no buckets
+    pub fn new(buckets: usize) -> Result<HashingEncoder, DarjeelingError> {
+        if buckets == 0 {
+            return Err(DarjeelingError::TransformFailed(
+                "a hashing encoder needs at least one bucket".to_string(),
+            ));
+        }
+        Ok(HashingEncoder { buckets })
+    }
+
+    pub fn encode(&self, value: &str) -> Box<[f32]> {
+        let mut encoded = vec![0.0; self.buckets];
+        encoded[(fnv_hash(value) % self.buckets as u64) as usize] = 1.0;
+        encoded.into_boxed_slice()
+    }
+}
+
+impl TargetEncoder {
+    /// Fits the encoder to the mean target of every category
+    ///
+    /// ## Err
+    /// ### TransformFailed
+    /// There aren't as many targets as values, there are none, or the smoothing is negative
+    pub fn fit(
+        values: &[String],
+        targets: &[f32],
+        smoothing: f32,
+        unknown: Unknown,
+    ) -> Result<TargetEncoder, DarjeelingError> {
+        if values.len() != targets.len() || values.is_empty() {
+            return Err(DarjeelingError::TransformFailed(format!(
+                "target encoding needs a target for every value, there are {} values and {} targets",
+                values.len(),
+                targets.len()
+            )));
+        }
+        if smoothing < 0.0 {
+            return Err(DarjeelingError::TransformFailed(format!(
+                "target encoding smoothing can't be negative, it's {}",
+                smoothing
+            )));
+        }
+
+        let prior = targets.iter().sum::<f32>() / targets.len() as f32;
+        let categories = sorted_categories(values);
+        let mut sums = vec![0.0; categories.len()];
+        let mut counts = vec![0.0; categories.len()];
+        for (value, target) in values.iter().zip(targets.iter()) {
+            let i = categories
+                .binary_search(value)
+                .expect("Every value is a category");
+            sums[i] += target;
+            counts[i] += 1.0;
+        }
+        let means = sums
+            .iter()
+            .zip(counts.iter())
+            .map(|(sum, count)| (sum + smoothing * prior) / (count + smoothing))
+            .collect();
+
+        Ok(TargetEncoder {
+            categories,
+            means,
+            prior,
+            unknown,
+        })
+    }
+
+    /// ## Err
+    /// ### TransformFailed
+    /// The category is unknown and the unknown policy is [`Unknown::Error`]
+    pub fn encode(&self, value: &str) -> Result<f32, DarjeelingError> {
+        match self
+            .categories
+            .binary_search_by(|category| category.as_str().cmp(value))
+        {
+            Ok(i) => Ok(self.means[i]),
+            Err(_) => unknown(self.unknown, value).map(|_| self.prior),
+        }
+    }
+}
+
+impl Encoder {
+    /// The number of features a category is encoded as
+    pub fn width(&self) -> usize {
+        match self {
+            Encoder::OneHot(encoder) => encoder.categories.len(),
+            Encoder::Ordinal(_) | Encoder::Target(_) => 1,
+            Encoder::Hashing(encoder) => encoder.buckets,
+        }
+    }
+
+    /// Encodes a single category
+    ///
+    /// ## Err
+    /// ### TransformFailed
+    /// The category is unknown and the unknown policy is [`Unknown::Error`]
+    pub fn encode(&self, value: &str) -> Result<Box<[f32]>, DarjeelingError> {
+        match self {
+            Encoder::OneHot(encoder) => encoder.encode(value),
+            Encoder::Ordinal(encoder) => Ok(vec![encoder.encode(value)?].into_boxed_slice()),
+            Encoder::Hashing(encoder) => Ok(encoder.encode(value)),
+            Encoder::Target(encoder) => Ok(vec![encoder.encode(value)?].into_boxed_slice()),
+        }
+    }
+
+    /// The labels of the columns an encoded column is replaced with
+    fn labels(&self, column: &str) -> Vec<String> {
+        match self {
+            Encoder::OneHot(encoder) => encoder
+                .categories
+                .iter()
+                .map(|category| format!("{}={}", column, category))
+                .collect(),
+            Encoder::Ordinal(_) | Encoder::Target(_) => vec![column.to_string()],
+            Encoder::Hashing(encoder) => (0..encoder.buckets)
+                .map(|bucket| format!("{}#{}", column, bucket))
+                .collect(),
+        }
+    }
+}
+
+/// Encodes the categorical columns of dataframes, so they can be converted into inputs
+///
+/// ## Examples
+/// ``` rust
+/// use darjeeling::{
+///     dataframe::{Column, DataFrame},
+///     preprocessing::encoder::{Encoding, FrameEncoder, Unknown},
+/// };
+///
+/// let frame = DataFrame::from_columns(vec![
+///     ("colour", Column::Categorical(vec!["red".to_string(), "blue".to_string()])),
+///     ("weight", Column::Float(vec![1.5, 2.0])),
+///     ("label", Column::Integer(vec![1, 0])),
+/// ])
+/// .unwrap();
+///
+/// let encoder = FrameEncoder::fit(&frame, &[("colour", Encoding::OneHot)], Unknown::Ignore, None).unwrap();
+/// let encoded = encoder.transform(&frame).unwrap();
+/// assert_eq!(encoded.col_labels(), &["colour=blue", "colour=red", "weight", "label"]);
+/// let data = encoded.to_series("label").unwrap();
+/// assert_eq!(data[0].data.as_ref(), &[0.0, 1.0, 1.5]);
+/// ```
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct FrameEncoder {
+    /// Each encoded column's label and its fitted encoder
+    pub columns: Vec<(String, Encoder)>,
+}
+
+impl FrameEncoder {
+    /// Fits an encoder to each of the given columns
+    ///
+    /// ## Params
+    /// - Frame: The training data
+    /// - Columns: The categorical columns to encode, and how
+    /// - Unknown: What to do with categories that aren't in the training data
+    /// - Target: The numeric or boolean column target encoding averages, only needed for [`Encoding::Target`]
+    ///
+    /// ## Err
+    /// ### ColumnDoesNotExist
+    /// ### ColumnMismatch
+    /// A column to encode isn't categorical, or the target column isn't numeric
+    /// ### TransformFailed
+    /// An encoding is target without a target column, or its settings aren't valid
+    pub fn fit(
+        frame: &DataFrame,
+        columns: &[(&str, Encoding)],
+        unknown: Unknown,
+        target: Option<&str>,
+    ) -> Result<FrameEncoder, DarjeelingError> {
+        let targets = match target {
+            Some(target) => Some(numeric_column(frame, target)?),
+            None => None,
+        };
+
+        let columns = columns
+            .iter()
+            .map(|(label, encoding)| {
+                let values = categorical_column(frame, label)?;
+                let encoder = match encoding {
+                    Encoding::OneHot => Encoder::OneHot(OneHotEncoder::fit(values, unknown)),
+                    Encoding::Ordinal => Encoder::Ordinal(OrdinalEncoder::fit(values, unknown)),
+                    Encoding::Hashing { buckets } => {
+                        Encoder::Hashing(HashingEncoder::new(*buckets)?)
+                    }
+                    Encoding::Target { smoothing } => {
+                        let targets = targets.as_ref().ok_or_else(|| {
+                            DarjeelingError::TransformFailed(format!(
+                                "target encoding column {:?} needs a target column",
+                                label
+                            ))
+                        })?;
+                        Encoder::Target(TargetEncoder::fit(values, targets, *smoothing, unknown)?)
+                    }
+                };
+                Ok((label.to_string(), encoder))
+            })
+            .collect::<Result<Vec<(String, Encoder)>, DarjeelingError>>()?;
+        Ok(FrameEncoder { columns })
+    }
+
+    /// Replaces every encoded column with its float columns, in the same place.
+    /// The rest of the columns are left as they are.
+    ///
+    /// ## Err
+    /// ### ColumnDoesNotExist
+    /// ### ColumnMismatch
+    /// An encoded column isn't categorical
+    /// ### TransformFailed
+    /// A category is unknown and the unknown policy is [`Unknown::Error`]
+    pub fn transform(&self, frame: &DataFrame) -> Result<DataFrame, DarjeelingError> {
+        for (label, _) in self.columns.iter() {
+            categorical_column(frame, label)?;
+        }
+
+        let mut columns: Vec<(String, Column)> = vec![];
+        for label in frame.col_labels() {
+            let column = frame.column(label)?;
+            match self.encoder(label) {
+                Some(encoder) => {
+                    let values = categorical_column(frame, label)?;
+                    let encoded = values
+                        .iter()
+                        .map(|value| encoder.encode(value))
+                        .collect::<Result<Vec<Box<[f32]>>, DarjeelingError>>()?;
+                    for (i, encoded_label) in encoder.labels(label).into_iter().enumerate() {
+                        columns.push((
+                            encoded_label,
+                            Column::Float(encoded.iter().map(|row| row[i]).collect()),
+                        ));
+                    }
+                }
+                None => columns.push((label.clone(), column.clone())),
+            }
+        }
+
+        DataFrame::from_columns(columns)?.with_row_labels(frame.row_labels().to_vec())
+    }
+
+    /// Encodes a single record, with a value for every column of the frame the encoder was fitted on, in order.
+    /// This is what [`transform`](FrameEncoder::transform) does to each row.
+    ///
+    /// ## Params
+    /// - Labels: The label of each value's column
+    /// - Values: The record
+    ///
+    /// ## Err
+    /// ### ColumnMismatch
+    /// There aren't as many values as labels, or a value in an encoded column isn't categorical
+    /// ### TransformFailed
+    /// A category is unknown and the unknown policy is [`Unknown::Error`]
+    pub fn transform_record(
+        &self,
+        labels: &[String],
+        values: &[Value],
+    ) -> Result<Box<[Value]>, DarjeelingError> {
+        if labels.len() != values.len() {
+            return Err(DarjeelingError::ColumnMismatch(format!(
+                "there are {} columns, but {} values",
+                labels.len(),
+                values.len()
+            )));
+        }
+
+        let mut record = vec![];
+        for (label, value) in labels.iter().zip(values.iter()) {
+            match (self.encoder(label), value) {
+                (Some(encoder), Value::Categorical(category)) => record.extend(
+                    encoder
+                        .encode(category)?
+                        .iter()
+                        .map(|encoded| Value::Float(*encoded)),
+                ),
+                (Some(_), _) => {
+                    return Err(DarjeelingError::ColumnMismatch(format!(
+                        "column {:?} is encoded, but {} isn't categorical",
+                        label, value
+                    )))
+                }
+                (None, value) => record.push(value.clone()),
+            }
+        }
+        Ok(record.into_boxed_slice())
+    }
+
+    fn encoder(&self, label: &str) -> Option<&Encoder> {
+        self.columns
+            .iter()
+            .find(|(column, _)| column == label)
+            .map(|(_, encoder)| encoder)
+    }
+}
+
+fn sorted_categories(values: &[String]) -> Box<[String]> {
+    let mut categories = values.to_vec();
+    categories.sort_unstable();
+    categories.dedup();
+    categories.into_boxed_slice()
+}
+
+fn unknown(policy: Unknown, value: &str) -> Result<(), DarjeelingError> {
+    match policy {
+        Unknown::Error => Err(DarjeelingError::TransformFailed(format!(
+            "{:?} wasn't a category when the encoder was fitted",
+            value
+        ))),
+        Unknown::Ignore => Ok(()),
+    }
+}
+
+fn categorical_column<'a>(
+    frame: &'a DataFrame,
+    label: &str,
+) -> Result<&'a [String], DarjeelingError> {
+    match frame.column(label)? {
+        Column::Categorical(values) => Ok(values),
+        _ => Err(DarjeelingError::ColumnMismatch(format!(
+            "column {:?} isn't categorical, so it can't be encoded",
+            label
+        ))),
+    }
+}
+
+fn numeric_column(frame: &DataFrame, label: &str) -> Result<Vec<f32>, DarjeelingError> {
+    let column = frame.column(label)?;
+    (0..column.len())
+        .map(|row| {
+            column
+                .get(row)
+                .and_then(|value| value.as_f32())
+                .ok_or_else(|| {
+                    DarjeelingError::ColumnMismatch(format!(
+                        "target column {:?} isn't numeric",
+                        label
+                    ))
+                })
+        })
+        .collect()
+}
+
+/// 64 bit FNV-1a, which unlike the standard library's hasher won't change between versions,
+/// so saved encoders keep putting categories in the same buckets
+fn fnv_hash(value: &str) -> u64 {
+    value.bytes().fold(0xcbf29ce484222325, |hash, byte| {
+        (hash ^ byte as u64).wrapping_mul(0x100000001b3)
+    })
+}
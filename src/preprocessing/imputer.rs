@@ -0,0 +1,150 @@
+use super::{check_width, feature_count, feature_rows, Transform};
+use crate::{error::DarjeelingError, series::Series};
+use serde::{Deserialize, Serialize};
+
+/// What a missing feature is replaced with
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+pub enum Imputation {
+    /// The mean of the feature's values
+    Mean,
+    /// The median of the feature's values
+    Median,
+    /// The feature's most common value, the smallest if there's a tie
+    MostFrequent,
+    /// The same value for every feature
+    Constant(f32),
+}
+
+/// Replaces missing (NaN) features with values fitted on training data
+///
+/// ## Examples
+/// ``` rust
+/// use darjeeling::{
+///     preprocessing::{imputer::{Imputer, Imputation}, Transform},
+///     series::Series,
+/// };
+///
+/// let data = vec![
+///     Series::new(vec![1.0, f32::NAN], "a"),
+///     Series::new(vec![3.0, 4.0], "b"),
+/// ];
+/// let imputer = Imputer::fit(&data, Imputation::Mean, true).unwrap();
+/// assert_eq!(imputer.transform_row(&[f32::NAN, 5.0]).unwrap().as_ref(), &[2.0, 5.0, 0.0]);
+/// assert_eq!(imputer.transform_row(&[0.0, f32::NAN]).unwrap().as_ref(), &[0.0, 4.0, 1.0]);
+/// ```
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct Imputer {
+    /// The value each feature is replaced with when it's missing
+    pub fills: Box<[f32]>,
+    /// The features that get a missing indicator, each indicator is added after the features
+    /// and is one when its feature is missing
+    pub indicators: Option<Box<[usize]>>,
+}
+
+impl Imputer {
+    /// Fits the imputer to the features of training data, ignoring the missing ones
+    ///
+    /// ## Params
+    /// - Data: The training data
+    /// - Strategy: What missing features are replaced with
+    /// - Indicators: Whether to add a missing indicator feature for every feature that's missing in the training data
+    ///
+    /// ## Err
+    /// ### TransformFailed
+    /// There's no data, the inputs have different numbers of features,
+    /// or a feature is always missing and the strategy isn't [`Imputation::Constant`]
+    pub fn fit(
+        data: &[Series],
+        strategy: Imputation,
+        indicators: bool,
+    ) -> Result<Imputer, DarjeelingError> {
+        Imputer::fit_rows(&feature_rows(data), strategy, indicators)
+    }
+
+    /// Fits the imputer to rows of unlabelled data, see [`fit`](Imputer::fit)
+    pub fn fit_rows(
+        rows: &[&[f32]],
+        strategy: Imputation,
+        indicators: bool,
+    ) -> Result<Imputer, DarjeelingError> {
+        let width = feature_count(rows)?;
+
+        let mut fills = Vec::with_capacity(width);
+        let mut missing = vec![];
+        for i in 0..width {
+            let mut values: Vec<f32> = rows
+                .iter()
+                .map(|row| row[i])
+                .filter(|value| !value.is_nan())
+                .collect();
+            if values.len() < rows.len() {
+                missing.push(i);
+            }
+
+            if let Imputation::Constant(fill) = strategy {
+                fills.push(fill);
+                continue;
+            }
+            if values.is_empty() {
+                return Err(DarjeelingError::TransformFailed(format!(
+                    "feature {} is always missing, so there's nothing to impute it with",
+                    i
+                )));
+            }
+            values.sort_by(f32::total_cmp);
+            fills.push(match strategy {
+                Imputation::Mean => values.iter().sum::<f32>() / values.len() as f32,
+                Imputation::Median => median(&values),
+                _ => most_frequent(&values),
+            });
+        }
+
+        Ok(Imputer {
+            fills: fills.into_boxed_slice(),
+            indicators: indicators.then(|| missing.into_boxed_slice()),
+        })
+    }
+}
+
+impl Transform for Imputer {
+    fn transform_row(&self, row: &[f32]) -> Result<Box<[f32]>, DarjeelingError> {
+        check_width(row, self.fills.len())?;
+        let mut imputed: Vec<f32> = row
+            .iter()
+            .zip(self.fills.iter())
+            .map(|(value, fill)| if value.is_nan() { *fill } else { *value })
+            .collect();
+        if let Some(indicators) = &self.indicators {
+            imputed.extend(
+                indicators
+                    .iter()
+                    .map(|i| if row[*i].is_nan() { 1.0 } else { 0.0 }),
+            );
+        }
+        Ok(imputed.into_boxed_slice())
+    }
+}
+
+fn median(sorted: &[f32]) -> f32 {
+    let middle = sorted.len() / 2;
+    if sorted.len() % 2 == 0 {
+        (sorted[middle - 1] + sorted[middle]) / 2.0
+    } else {
+        sorted[middle]
+    }
+}
+
+/// The longest run of equal values, the first if there's a tie
+fn most_frequent(sorted: &[f32]) -> f32 {
+    let mut best = (sorted[0], 0);
+    let mut start = 0;
+    for i in 1..=sorted.len() {
+        if i == sorted.len() || sorted[i] != sorted[start] {
+            if i - start > best.1 {
+                best = (sorted[start], i - start);
+            }
+            start = i;
+        }
+    }
+    best.0
+}
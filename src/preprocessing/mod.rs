@@ -0,0 +1,83 @@
+pub mod encoder;
+pub mod imputer;
+pub mod pipeline;
+pub mod scaler;
+
+use crate::{error::DarjeelingError, series::Series};
+
+/// Transforms the features of inputs, after being fitted to training data
+pub trait Transform {
+    /// Transforms the features of a single input
+    ///
+    /// ## Err
+    /// ### TransformFailed
+    /// The input doesn't have the features the transform was fitted on
+    fn transform_row(&self, row: &[f32]) -> Result<Box<[f32]>, DarjeelingError>;
+
+    /// Transforms the features of every input, keeping their answers
+    fn transform(&self, data: &[Series]) -> Result<Box<[Series]>, DarjeelingError> {
+        data.iter()
+            .map(|series| {
+                Ok(Series::new(
+                    self.transform_row(&series.data)?,
+                    series.answer.clone(),
+                ))
+            })
+            .collect()
+    }
+
+    /// Transforms every row of unlabelled inputs
+    fn transform_rows(&self, rows: &[Box<[f32]>]) -> Result<Box<[Box<[f32]>]>, DarjeelingError> {
+        rows.iter().map(|row| self.transform_row(row)).collect()
+    }
+}
+
+/// The features of every input, as rows
+pub(crate) fn feature_rows(data: &[Series]) -> Vec<&[f32]> {
+    data.iter().map(|series| series.data.as_ref()).collect()
+}
+
+/// Checks every row has the same number of features, and returns it
+pub(crate) fn feature_count(rows: &[&[f32]]) -> Result<usize, DarjeelingError> {
+    let width = match rows.first() {
+        Some(row) => row.len(),
+        None => {
+            return Err(DarjeelingError::TransformFailed(
+                "there's no data to fit to".to_string(),
+            ))
+        }
+    };
+    if let Some(row) = rows.iter().find(|row| row.len() != width) {
+        return Err(DarjeelingError::TransformFailed(format!(
+            "every input needs {} features, but one has {}",
+            width,
+            row.len()
+        )));
+    }
+    Ok(width)
+}
+
+/// Checks a row has the number of features a transform was fitted on
+pub(crate) fn check_width(row: &[f32], width: usize) -> Result<(), DarjeelingError> {
+    if row.len() != width {
+        return Err(DarjeelingError::TransformFailed(format!(
+            "the transform was fitted on {} features, but the input has {}",
+            width,
+            row.len()
+        )));
+    }
+    Ok(())
+}
+
+/// Checks no input has a missing feature before training, so NaN doesn't spread through the network
+pub(crate) fn check_missing(rows: &[&[f32]]) -> Result<(), DarjeelingError> {
+    for (i, row) in rows.iter().enumerate() {
+        if let Some(feature) = row.iter().position(|value| value.is_nan()) {
+            return Err(DarjeelingError::NanEncountered(format!(
+                "input {} is missing feature {}, impute missing values before training",
+                i, feature
+            )));
+        }
+    }
+    Ok(())
+}
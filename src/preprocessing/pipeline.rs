@@ -0,0 +1,377 @@
+use super::{
+    check_width,
+    encoder::{Encoding, FrameEncoder, Unknown},
+    feature_count, feature_rows,
+    imputer::{Imputation, Imputer},
+    scaler::{MinMaxScaler, RobustScaler, Scaler, StandardScaler},
+    Transform,
+};
+use crate::{
+    dataframe::{DataFrame, Value},
+    error::DarjeelingError,
+    series::Series,
+};
+use serde::{Deserialize, Serialize};
+
+/// Which scaler a pipeline fits
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+pub enum Scaling {
+    Standard,
+    MinMax,
+    Robust,
+}
+
+/// A step of a pipeline, run on the features after any categorical columns have been encoded
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+pub enum Stage {
+    /// Replaces missing features, and adds missing indicator features if the flag is set
+    Impute(Imputation, bool),
+    Scale(Scaling),
+}
+
+/// A fitted stage
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub enum Transformer {
+    Imputer(Imputer),
+    Scaler(Scaler),
+}
+
+impl Transformer {
+    /// Undoes the transform as far as it can, missing indicators are dropped but imputed values stay
+    pub fn inverse_transform_row(&self, row: &[f32]) -> Result<Box<[f32]>, DarjeelingError> {
+        match self {
+            Transformer::Imputer(imputer) => {
+                let width = imputer.fills.len()
+                    + imputer
+                        .indicators
+                        .as_ref()
+                        .map_or(0, |indicators| indicators.len());
+                check_width(row, width)?;
+                Ok(row[..imputer.fills.len()].into())
+            }
+            Transformer::Scaler(scaler) => scaler.inverse_transform_row(row),
+        }
+    }
+}
+
+impl Transform for Transformer {
+    fn transform_row(&self, row: &[f32]) -> Result<Box<[f32]>, DarjeelingError> {
+        match self {
+            Transformer::Imputer(imputer) => imputer.transform_row(row),
+            Transformer::Scaler(scaler) => scaler.transform_row(row),
+        }
+    }
+}
+
+/// A chain of transformers in front of a network.
+/// Networks fit their pipeline the first time they're trained, save it in the model file,
+/// and run every input through it, so raw records go in and answers come out.
+///
+/// Categorical columns of a dataframe are encoded first, then the stages run in the order they were added.
+///
+/// ## Examples
+/// ```ignore
+/// use darjeeling::preprocessing::{
+///     encoder::{Encoding, Unknown},
+///     imputer::Imputation,
+///     pipeline::{Pipeline, Scaling},
+/// };
+///
+/// let pipeline = Pipeline::new()
+///     .encode("colour", Encoding::OneHot)
+///     .unknown(Unknown::Ignore)
+///     .impute(Imputation::Median, true)
+///     .scale(Scaling::Standard);
+/// net.set_pipeline(pipeline);
+/// net.train_frame(&frame, "species", categories, &config).unwrap();
+///
+/// let mut net = CatNetwork::read_model("species.darj".to_string()).unwrap();
+/// let species = net.predict_frame(&new_frame).unwrap();
+/// ```
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Default)]
+pub struct Pipeline {
+    /// The categorical columns to encode, and how
+    pub encodings: Vec<(String, Encoding)>,
+    pub unknown: Unknown,
+    pub stages: Vec<Stage>,
+    /// The feature columns of the dataframe the pipeline was fitted on, in order
+    columns: Option<Box<[String]>>,
+    encoder: Option<FrameEncoder>,
+    /// The number of features going into the stages
+    width: Option<usize>,
+    transformers: Option<Box<[Transformer]>>,
+}
+
+impl Pipeline {
+    /// A pipeline that doesn't change anything
+    pub fn new() -> Pipeline {
+        Pipeline::default()
+    }
+
+    /// Encodes a categorical column of dataframes, see [`FrameEncoder`].
+    /// Target encoding averages the label column, which must be numeric or boolean.
+    pub fn encode(mut self, column: &str, encoding: Encoding) -> Pipeline {
+        self.encodings.push((column.to_string(), encoding));
+        self
+    }
+
+    /// What the encoders do with categories that aren't in the training data
+    pub fn unknown(mut self, unknown: Unknown) -> Pipeline {
+        self.unknown = unknown;
+        self
+    }
+
+    /// Adds an [`Imputer`] stage
+    pub fn impute(mut self, strategy: Imputation, indicators: bool) -> Pipeline {
+        self.stages.push(Stage::Impute(strategy, indicators));
+        self
+    }
+
+    /// Adds a scaler stage
+    pub fn scale(mut self, scaling: Scaling) -> Pipeline {
+        self.stages.push(Stage::Scale(scaling));
+        self
+    }
+
+    /// Whether the pipeline has been fitted
+    pub fn is_fitted(&self) -> bool {
+        self.transformers.is_some()
+    }
+
+    /// The fitted stages, in order
+    pub fn transformers(&self) -> Option<&[Transformer]> {
+        self.transformers.as_deref()
+    }
+
+    /// The fitted encoder of the categorical columns
+    pub fn encoder(&self) -> Option<&FrameEncoder> {
+        self.encoder.as_ref()
+    }
+
+    /// Fits every stage to the features of training data, each on the output of the one before
+    ///
+    /// ## Err
+    /// ### TransformFailed
+    /// The pipeline encodes categorical columns, which needs [`fit_frame`](Pipeline::fit_frame),
+    /// or a stage couldn't be fitted
+    pub fn fit(&mut self, data: &[Series]) -> Result<(), DarjeelingError> {
+        self.fit_rows(&feature_rows(data))
+    }
+
+    /// Fits every stage to rows of unlabelled data, see [`fit`](Pipeline::fit)
+    pub fn fit_rows(&mut self, rows: &[&[f32]]) -> Result<(), DarjeelingError> {
+        if !self.encodings.is_empty() && self.encoder.is_none() {
+            return Err(DarjeelingError::TransformFailed(
+                "the pipeline encodes categorical columns, so it has to be fitted on a dataframe"
+                    .to_string(),
+            ));
+        }
+        let width = feature_count(rows)?;
+
+        let mut rows: Box<[Box<[f32]>]> = rows.iter().map(|row| Box::from(*row)).collect();
+        let mut transformers = Vec::with_capacity(self.stages.len());
+        for stage in self.stages.iter() {
+            let borrowed: Vec<&[f32]> = rows.iter().map(|row| row.as_ref()).collect();
+            let transformer = match stage {
+                Stage::Impute(strategy, indicators) => {
+                    Transformer::Imputer(Imputer::fit_rows(&borrowed, *strategy, *indicators)?)
+                }
+                Stage::Scale(Scaling::Standard) => {
+                    Transformer::Scaler(StandardScaler::fit_rows(&borrowed)?.into())
+                }
+                Stage::Scale(Scaling::MinMax) => {
+                    Transformer::Scaler(MinMaxScaler::fit_rows(&borrowed)?.into())
+                }
+                Stage::Scale(Scaling::Robust) => {
+                    Transformer::Scaler(RobustScaler::fit_rows(&borrowed)?.into())
+                }
+            };
+            rows = transformer.transform_rows(&rows)?;
+            transformers.push(transformer);
+        }
+
+        self.width = Some(width);
+        self.transformers = Some(transformers.into_boxed_slice());
+        Ok(())
+    }
+
+    /// Fits the encoders to the categorical columns of a dataframe, then the stages to the encoded features
+    ///
+    /// ## Params
+    /// - Frame: The training data
+    /// - Label: The column of answers, every other column is a feature
+    ///
+    /// ## Err
+    /// ### ColumnDoesNotExist
+    /// ### ColumnMismatch
+    /// A feature column is categorical but isn't encoded, or an encoded column isn't categorical
+    /// ### TransformFailed
+    /// An encoder or stage couldn't be fitted
+    pub fn fit_frame(&mut self, frame: &DataFrame, label: &str) -> Result<(), DarjeelingError> {
+        frame.column(label)?;
+        let encodings: Vec<(&str, Encoding)> = self
+            .encodings
+            .iter()
+            .map(|(column, encoding)| (column.as_str(), *encoding))
+            .collect();
+        let target = encodings
+            .iter()
+            .any(|(_, encoding)| matches!(encoding, Encoding::Target { .. }))
+            .then_some(label);
+
+        self.encoder = Some(FrameEncoder::fit(frame, &encodings, self.unknown, target)?);
+        self.columns = Some(
+            frame
+                .col_labels()
+                .iter()
+                .filter(|column| *column != label)
+                .cloned()
+                .collect(),
+        );
+        let data = self.encode_frame(frame, Some(label))?;
+        self.fit(&data)
+    }
+
+    /// Encodes the categorical columns of a dataframe, without running the stages
+    ///
+    /// ## Params
+    /// - Frame: The data, with the feature columns the pipeline was fitted on, in any order
+    /// - Label: The column of answers, if the data has one.
+    ///   Without one, the answers are empty.
+    ///
+    /// ## Err
+    /// ### ColumnDoesNotExist
+    /// ### ColumnMismatch
+    /// A feature column is categorical but isn't encoded
+    /// ### TransformFailed
+    /// The pipeline encodes categorical columns but hasn't been fitted, or a category is unknown
+    pub fn encode_frame(
+        &self,
+        frame: &DataFrame,
+        label: Option<&str>,
+    ) -> Result<Box<[Series]>, DarjeelingError> {
+        let selected = match &self.columns {
+            Some(columns) => {
+                let mut selected: Vec<&str> =
+                    columns.iter().map(|column| column.as_str()).collect();
+                selected.extend(label);
+                frame.select(&selected)?
+            }
+            None => frame.clone(),
+        };
+        let encoded = match &self.encoder {
+            Some(encoder) => encoder.transform(&selected)?,
+            None if self.encodings.is_empty() => selected,
+            None => return Err(not_fitted()),
+        };
+
+        match label {
+            Some(label) => encoded.to_series(label),
+            None => Ok(encoded
+                .to_rows()?
+                .into_vec()
+                .into_iter()
+                .map(|row| Series::new(row, ""))
+                .collect()),
+        }
+    }
+
+    /// Runs a dataframe through the whole pipeline, see [`encode_frame`](Pipeline::encode_frame)
+    pub fn transform_frame(
+        &self,
+        frame: &DataFrame,
+        label: Option<&str>,
+    ) -> Result<Box<[Series]>, DarjeelingError> {
+        self.transform(&self.encode_frame(frame, label)?)
+    }
+
+    /// Runs a raw record through the whole pipeline
+    ///
+    /// ## Params
+    /// - Values: A value for every feature column of the dataframe the pipeline was fitted on, in the same order
+    ///
+    /// ## Err
+    /// ### ColumnMismatch
+    /// There isn't a value for every column, or a categorical value isn't encoded
+    /// ### TransformFailed
+    /// The pipeline wasn't fitted on a dataframe, or a category is unknown
+    pub fn transform_record(&self, values: &[Value]) -> Result<Box<[f32]>, DarjeelingError> {
+        let columns = self.columns.as_ref().ok_or_else(|| {
+            DarjeelingError::TransformFailed(
+                "the pipeline wasn't fitted on a dataframe, so it doesn't know the record's columns"
+                    .to_string(),
+            )
+        })?;
+        let encoded = match &self.encoder {
+            Some(encoder) => encoder.transform_record(columns, values)?,
+            None => values.into(),
+        };
+
+        let row = encoded
+            .iter()
+            .map(|value| {
+                value.as_f32().ok_or_else(|| {
+                    DarjeelingError::ColumnMismatch(format!(
+                        "{:?} is categorical, but its column isn't encoded",
+                        value.to_string()
+                    ))
+                })
+            })
+            .collect::<Result<Box<[f32]>, DarjeelingError>>()?;
+        self.transform_row(&row)
+    }
+
+    /// Undoes the stages as far as they can be, in reverse.
+    /// Scaling is undone and missing indicators are dropped, but encoding and imputation can't be undone.
+    ///
+    /// ## Err
+    /// ### TransformFailed
+    /// The pipeline hasn't been fitted, or the row isn't as wide as the pipeline's output
+    pub fn inverse_transform_row(&self, row: &[f32]) -> Result<Box<[f32]>, DarjeelingError> {
+        self.transformers
+            .as_ref()
+            .ok_or_else(not_fitted)?
+            .iter()
+            .rev()
+            .try_fold(Box::from(row), |row, transformer| {
+                transformer.inverse_transform_row(&row)
+            })
+    }
+}
+
+impl Transform for Pipeline {
+    fn transform_row(&self, row: &[f32]) -> Result<Box<[f32]>, DarjeelingError> {
+        let (transformers, width) = match (&self.transformers, self.width) {
+            (Some(transformers), Some(width)) => (transformers, width),
+            _ => return Err(not_fitted()),
+        };
+        check_width(row, width)?;
+
+        transformers
+            .iter()
+            .try_fold(Box::from(row), |row, transformer| {
+                transformer.transform_row(&row)
+            })
+    }
+}
+
+impl From<Scaler> for Pipeline {
+    /// A fitted pipeline of just the scaler
+    fn from(scaler: Scaler) -> Pipeline {
+        let scaling = match scaler {
+            Scaler::Standard(_) => Scaling::Standard,
+            Scaler::MinMax(_) => Scaling::MinMax,
+            Scaler::Robust(_) => Scaling::Robust,
+        };
+        Pipeline {
+            stages: vec![Stage::Scale(scaling)],
+            width: Some(scaler.width()),
+            transformers: Some(Box::new([Transformer::Scaler(scaler)])),
+            ..Default::default()
+        }
+    }
+}
+
+fn not_fitted() -> DarjeelingError {
+    DarjeelingError::TransformFailed("the pipeline hasn't been fitted".to_string())
+}
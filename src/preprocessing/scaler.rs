@@ -0,0 +1,276 @@
+use super::{check_missing, check_width, feature_count, feature_rows, Transform};
+use crate::{error::DarjeelingError, series::Series};
+use serde::{Deserialize, Serialize};
+
+/// Scales every feature to a mean of zero and a standard deviation of one
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct StandardScaler {
+    pub means: Box<[f32]>,
+    /// Features that never change have a standard deviation of one, so they're only centered
+    pub stds: Box<[f32]>,
+}
+
+/// Scales every feature to the range [0, 1] of the training data
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct MinMaxScaler {
+    pub mins: Box<[f32]>,
+    /// Features that never change have a range of one, so they're only shifted
+    pub ranges: Box<[f32]>,
+}
+
+/// Centers every feature on its median and scales it by its interquartile range,
+/// so outliers have less effect than with a [`StandardScaler`]
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct RobustScaler {
+    pub medians: Box<[f32]>,
+    /// Features with no spread between their quartiles have a range of one, so they're only centered
+    pub ranges: Box<[f32]>,
+}
+
+/// Any of the fitted scalers, so one can be stored on a network
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub enum Scaler {
+    Standard(StandardScaler),
+    MinMax(MinMaxScaler),
+    Robust(RobustScaler),
+}
+
+impl StandardScaler {
+    /// Fits the scaler to the features of training data
+    ///
+    /// ## Err
+    /// ### TransformFailed
+    /// There's no data, or the inputs have different numbers of features
+    /// ### NanEncountered
+    /// An input has a missing (NaN) feature, impute it first
+    pub fn fit(data: &[Series]) -> Result<StandardScaler, DarjeelingError> {
+        StandardScaler::fit_rows(&feature_rows(data))
+    }
+
+    /// Fits the scaler to rows of unlabelled data, see [`fit`](StandardScaler::fit)
+    pub fn fit_rows(rows: &[&[f32]]) -> Result<StandardScaler, DarjeelingError> {
+        let width = feature_count(rows)?;
+        check_missing(rows)?;
+        let count = rows.len() as f32;
+
+        let means: Box<[f32]> = (0..width)
+            .map(|i| rows.iter().map(|row| row[i]).sum::<f32>() / count)
+            .collect();
+        let stds = (0..width)
+            .map(|i| {
+                let variance = rows
+                    .iter()
+                    .map(|row| (row[i] - means[i]).powi(2))
+                    .sum::<f32>()
+                    / count;
+                non_zero(variance.sqrt())
+            })
+            .collect();
+        Ok(StandardScaler { means, stds })
+    }
+
+    /// Undoes the scaling of a single input
+    pub fn inverse_transform_row(&self, row: &[f32]) -> Result<Box<[f32]>, DarjeelingError> {
+        check_width(row, self.means.len())?;
+        Ok(row
+            .iter()
+            .enumerate()
+            .map(|(i, value)| value * self.stds[i] + self.means[i])
+            .collect())
+    }
+}
+
+impl Transform for StandardScaler {
+    fn transform_row(&self, row: &[f32]) -> Result<Box<[f32]>, DarjeelingError> {
+        check_width(row, self.means.len())?;
+        Ok(row
+            .iter()
+            .enumerate()
+            .map(|(i, value)| (value - self.means[i]) / self.stds[i])
+            .collect())
+    }
+}
+
+impl MinMaxScaler {
+    /// Fits the scaler to the features of training data
+    ///
+    /// ## Err
+    /// ### TransformFailed
+    /// There's no data, or the inputs have different numbers of features
+    /// ### NanEncountered
+    /// An input has a missing (NaN) feature, impute it first
+    pub fn fit(data: &[Series]) -> Result<MinMaxScaler, DarjeelingError> {
+        MinMaxScaler::fit_rows(&feature_rows(data))
+    }
+
+    /// Fits the scaler to rows of unlabelled data, see [`fit`](MinMaxScaler::fit)
+    pub fn fit_rows(rows: &[&[f32]]) -> Result<MinMaxScaler, DarjeelingError> {
+        let width = feature_count(rows)?;
+        check_missing(rows)?;
+
+        let mins: Box<[f32]> = (0..width)
+            .map(|i| rows.iter().map(|row| row[i]).fold(f32::INFINITY, f32::min))
+            .collect();
+        let ranges = (0..width)
+            .map(|i| {
+                let max = rows
+                    .iter()
+                    .map(|row| row[i])
+                    .fold(f32::NEG_INFINITY, f32::max);
+                non_zero(max - mins[i])
+            })
+            .collect();
+        Ok(MinMaxScaler { mins, ranges })
+    }
+
+    /// Undoes the scaling of a single input
+    pub fn inverse_transform_row(&self, row: &[f32]) -> Result<Box<[f32]>, DarjeelingError> {
+        check_width(row, self.mins.len())?;
+        Ok(row
+            .iter()
+            .enumerate()
+            .map(|(i, value)| value * self.ranges[i] + self.mins[i])
+            .collect())
+    }
+}
+
+impl Transform for MinMaxScaler {
+    fn transform_row(&self, row: &[f32]) -> Result<Box<[f32]>, DarjeelingError> {
+        check_width(row, self.mins.len())?;
+        Ok(row
+            .iter()
+            .enumerate()
+            .map(|(i, value)| (value - self.mins[i]) / self.ranges[i])
+            .collect())
+    }
+}
+
+impl RobustScaler {
+    /// Fits the scaler to the features of training data
+    ///
+    /// ## Err
+    /// ### TransformFailed
+    /// There's no data, or the inputs have different numbers of features
+    /// ### NanEncountered
+    /// An input has a missing (NaN) feature, impute it first
+    pub fn fit(data: &[Series]) -> Result<RobustScaler, DarjeelingError> {
+        RobustScaler::fit_rows(&feature_rows(data))
+    }
+
+    /// Fits the scaler to rows of unlabelled data, see [`fit`](RobustScaler::fit)
+    pub fn fit_rows(rows: &[&[f32]]) -> Result<RobustScaler, DarjeelingError> {
+        let width = feature_count(rows)?;
+        check_missing(rows)?;
+
+        let mut medians = Vec::with_capacity(width);
+        let mut ranges = Vec::with_capacity(width);
+        for i in 0..width {
+            let mut column: Vec<f32> = rows.iter().map(|row| row[i]).collect();
+            column.sort_by(f32::total_cmp);
+            medians.push(quantile(&column, 0.5));
+            ranges.push(non_zero(quantile(&column, 0.75) - quantile(&column, 0.25)));
+        }
+        Ok(RobustScaler {
+            medians: medians.into_boxed_slice(),
+            ranges: ranges.into_boxed_slice(),
+        })
+    }
+
+    /// Undoes the scaling of a single input
+    pub fn inverse_transform_row(&self, row: &[f32]) -> Result<Box<[f32]>, DarjeelingError> {
+        check_width(row, self.medians.len())?;
+        Ok(row
+            .iter()
+            .enumerate()
+            .map(|(i, value)| value * self.ranges[i] + self.medians[i])
+            .collect())
+    }
+}
+
+impl Transform for RobustScaler {
+    fn transform_row(&self, row: &[f32]) -> Result<Box<[f32]>, DarjeelingError> {
+        check_width(row, self.medians.len())?;
+        Ok(row
+            .iter()
+            .enumerate()
+            .map(|(i, value)| (value - self.medians[i]) / self.ranges[i])
+            .collect())
+    }
+}
+
+impl Scaler {
+    /// The number of features the scaler was fitted on
+    pub fn width(&self) -> usize {
+        match self {
+            Scaler::Standard(scaler) => scaler.means.len(),
+            Scaler::MinMax(scaler) => scaler.mins.len(),
+            Scaler::Robust(scaler) => scaler.medians.len(),
+        }
+    }
+
+    /// Undoes the scaling of a single input
+    pub fn inverse_transform_row(&self, row: &[f32]) -> Result<Box<[f32]>, DarjeelingError> {
+        match self {
+            Scaler::Standard(scaler) => scaler.inverse_transform_row(row),
+            Scaler::MinMax(scaler) => scaler.inverse_transform_row(row),
+            Scaler::Robust(scaler) => scaler.inverse_transform_row(row),
+        }
+    }
+
+    /// Undoes the scaling of every input, keeping their answers
+    pub fn inverse_transform(&self, data: &[Series]) -> Result<Box<[Series]>, DarjeelingError> {
+        data.iter()
+            .map(|series| {
+                Ok(Series::new(
+                    self.inverse_transform_row(&series.data)?,
+                    series.answer.clone(),
+                ))
+            })
+            .collect()
+    }
+}
+
+impl Transform for Scaler {
+    fn transform_row(&self, row: &[f32]) -> Result<Box<[f32]>, DarjeelingError> {
+        match self {
+            Scaler::Standard(scaler) => scaler.transform_row(row),
+            Scaler::MinMax(scaler) => scaler.transform_row(row),
+            Scaler::Robust(scaler) => scaler.transform_row(row),
+        }
+    }
+}
+
+impl From<StandardScaler> for Scaler {
+    fn from(scaler: StandardScaler) -> Scaler {
+        Scaler::Standard(scaler)
+    }
+}
+
+impl From<MinMaxScaler> for Scaler {
+    fn from(scaler: MinMaxScaler) -> Scaler {
+        Scaler::MinMax(scaler)
+    }
+}
+
+impl From<RobustScaler> for Scaler {
+    fn from(scaler: RobustScaler) -> Scaler {
+        Scaler::Robust(scaler)
+    }
+}
+
+/// Keeps features that never change from being divided by zero
+fn non_zero(spread: f32) -> f32 {
+    if spread > f32::EPSILON {
+        spread
+    } else {
+        1.0
+    }
+}
+
+/// The linearly interpolated quantile of sorted values
+fn quantile(sorted: &[f32], q: f32) -> f32 {
+    let position = q * (sorted.len() - 1) as f32;
+    let lower = position.floor() as usize;
+    let upper = position.ceil() as usize;
+    sorted[lower] + (sorted[upper] - sorted[lower]) * (position - lower as f32)
+}
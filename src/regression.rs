@@ -0,0 +1,380 @@
+use crate::{
+    activation::ActivationFunction,
+    builder::NetworkBuilder,
+    checkpoint::{Checkpoint, TrainState},
+    config::{ConfigField, TrainConfig},
+    error::DarjeelingError,
+    layer::{self, LayerSpec, NodeArray},
+    metrics::RegressionReport,
+    neural_network::Model,
+    node::Node,
+    preprocessing::{check_missing, pipeline::Pipeline, Transform},
+    series::RegressionSeries,
+    trainer::{self, Gradient, Supervised},
+};
+use serde::{Deserialize, Serialize};
+use std::time::Instant;
+
+/// The regression Neural Network struct, which predicts continuous values.
+///
+/// The answer layer always uses the linear activation function, so its outputs aren't bounded,
+/// and it's trained with the config's [`Loss`].
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct RegNetwork {
+    node_array: Box<[Box<[Node]>]>,
+    layers: Box<[LayerSpec]>,
+    activation_function: Option<ActivationFunction>,
+    /// The config the network was last trained with
+    config: Option<TrainConfig>,
+    /// Every input goes through it before it reaches the network
+    pipeline: Option<Pipeline>,
+}
+
+impl RegNetwork {
+    /// Constructor function for a regression neural network
+    /// Fills a Neural Network's node_array with empty nodes.
+    /// Initializes random starting link and bias weights between -.5 and .5
+    ///
+    /// ## Params
+    /// - Inputs: The number of sensors in the input layer
+    /// - Hidden: The number of hidden nodes per hidden layer
+    /// - Answer: The number of answer nodes, or values predicted for each input
+    /// - Hidden Layers: The number of different hidden layers
+    /// - Activation Function: Which activation function is used by the hidden layers, the answer layer is always linear
+    ///
+    /// Every hidden layer has the same width, use a [`NetworkBuilder`] for anything else.
+    ///
+    /// ## Examples
+    /// ``` rust
+    /// use darjeeling::{activation::ActivationFunction, regression::RegNetwork};
+    ///
+    /// let mut net = RegNetwork::new(10, 40, 1, 1, Some(ActivationFunction::Tanh));
+    /// ```
+    pub fn new(
+        input_nodes: usize,
+        hidden_nodes: usize,
+        answer_nodes: usize,
+        hidden_layers: usize,
+        activation_function: Option<ActivationFunction>,
+    ) -> RegNetwork {
+        let mut builder = (0..hidden_layers.max(1))
+            .fold(NetworkBuilder::new(input_nodes), |builder, _| {
+                builder.hidden(hidden_nodes)
+            })
+            .hidden(answer_nodes);
+        if let Some(activation_function) = activation_function {
+            builder = builder.activation(activation_function);
+        }
+
+        builder
+            .build_reg()
+            .expect("Every layer of the network needs at least one node")
+    }
+
+    pub(crate) fn from_parts(
+        node_array: Box<[Box<[Node]>]>,
+        mut layers: Box<[LayerSpec]>,
+        activation_function: Option<ActivationFunction>,
+    ) -> RegNetwork {
+        if let Some(answer) = layers.last_mut() {
+            answer.activation = Some(ActivationFunction::Linear);
+        }
+
+        RegNetwork {
+            node_array,
+            layers,
+            activation_function,
+            config: None,
+            pipeline: None,
+        }
+    }
+
+    /// The config the network was last trained with, saved models keep it
+    pub fn config(&self) -> Option<&TrainConfig> {
+        self.config.as_ref()
+    }
+
+    /// Sets the pipeline every input goes through before it reaches the network.
+    /// An unfitted pipeline is fitted on the training data the next time the network is trained,
+    /// and it's saved with the model. Pipelines that encode categorical columns can't be used,
+    /// since there are no dataframes to fit them on.
+    pub fn set_pipeline(&mut self, pipeline: Pipeline) {
+        self.pipeline = Some(pipeline);
+    }
+
+    /// The pipeline every input goes through, saved models keep it
+    pub fn pipeline(&self) -> Option<&Pipeline> {
+        self.pipeline.as_ref()
+    }
+
+    /// Trains the neural network model to predict the targets of each input.
+    /// Targets far from zero train better once they're scaled down.
+    ///
+    /// ## Params
+    /// - Data: List of inputs, each with one target per answer node
+    /// - Config: The hyperparameters of the training, see [`TrainConfig`].
+    ///   Its loss is what's minimized, and its target accuracy is compared to the R² as a percentage.
+    ///   The model is written to the config's save path once training finishes, if it has one,
+    ///   and checkpoints that [`resume_from`](RegNetwork::resume_from) continues are written to its checkpoint path.
+    ///
+    /// ## Returns
+    /// The fallible:
+    /// - save path the model was written to, none if the config has no save path
+    /// - the R² of the last epoch
+    /// - the mean loss of the last epoch
+    ///
+    /// ## Err
+    /// - ### ModelMissingActivationFunction
+    /// The network has no activation function to train with
+    /// - ### InvalidLayerSpec
+    /// An input doesn't have one target per answer node, or isn't as wide as the input layer
+    /// - ### NanEncountered
+    /// An input has a missing (NaN) feature or target, or the training diverged
+    /// - ### WriteModelFailed
+    /// There was a problem when saving the model to a file
+    /// - ### UnsupportedConfig
+    /// The config has class weights
+    ///
+    /// ## Examples
+    /// ```ignore
+    /// let data = vec![RegressionSeries::new(vec![3.0, 2.0], vec![1.85])];
+    /// let mut net = RegNetwork::new(2, 16, 1, 1, Some(ActivationFunction::Tanh));
+    /// let config = TrainConfig {
+    ///     loss: Loss::Huber { delta: 1.0 },
+    ///     ..Default::default()
+    /// };
+    /// let (model_name, r2, loss) = net.train(&data, &config).unwrap();
+    /// ```
+    pub fn train(
+        &mut self,
+        data: &[RegressionSeries],
+        config: &TrainConfig,
+    ) -> Result<(Option<String>, f32, f32), DarjeelingError> {
+        self.train_from(data, config, TrainState::new(config.seed))
+    }
+
+    /// Continues a training run from a checkpoint written during [`train`](RegNetwork::train),
+    /// exactly where it stopped.
+    /// The config saved in the checkpoint is used, so more epochs can be added by changing its `epochs`.
+    ///
+    /// ## Params
+    /// - Checkpoint: A checkpoint read with [`Checkpoint::read`]
+    /// - Data: The same data the interrupted run was trained on
+    ///
+    /// ## Returns
+    /// The trained network, followed by the same values as [`train`](RegNetwork::train)
+    pub fn resume_from(
+        checkpoint: Checkpoint<RegNetwork>,
+        data: &[RegressionSeries],
+    ) -> Result<(RegNetwork, Option<String>, f32, f32), DarjeelingError> {
+        let Checkpoint {
+            mut network,
+            config,
+            state,
+            ..
+        } = checkpoint;
+
+        let (model_name, r2, loss) = network.train_from(data, &config, state)?;
+        Ok((network, model_name, r2, loss))
+    }
+
+    /// Runs the training loop from the given state
+    fn train_from(
+        &mut self,
+        data: &[RegressionSeries],
+        config: &TrainConfig,
+        mut state: TrainState,
+    ) -> Result<(Option<String>, f32, f32), DarjeelingError> {
+        config.check_unused("regression training", &[ConfigField::ClassWeights])?;
+        let activation_function = match self.activation_function {
+            Some(s) => s,
+            None => return Err(DarjeelingError::ModelMissingActivationFunction),
+        };
+        let width = self.node_array.last().map_or(0, |layer| layer.len());
+        if let Some(series) = data.iter().find(|series| series.targets.len() != width) {
+            return Err(DarjeelingError::InvalidLayerSpec(format!(
+                "the answer layer has {} nodes, but an input has {} targets",
+                width,
+                series.targets.len()
+            )));
+        }
+        if data
+            .iter()
+            .any(|series| series.targets.iter().any(|target| target.is_nan()))
+        {
+            return Err(DarjeelingError::NanEncountered(
+                "an input has a missing target, remove it before training".to_string(),
+            ));
+        }
+
+        if let Some(pipeline) = self
+            .pipeline
+            .as_mut()
+            .filter(|pipeline| !pipeline.is_fitted())
+        {
+            pipeline.fit_rows(
+                &data
+                    .iter()
+                    .map(|series| series.data.as_ref())
+                    .collect::<Vec<&[f32]>>(),
+            )?;
+        }
+        let rows = self.preprocess(data)?;
+        check_missing(&rows.iter().map(|row| row.as_ref()).collect::<Vec<&[f32]>>())?;
+        rows.iter()
+            .try_for_each(|row| layer::check_input(&self.node_array, row))?;
+        let targets: Box<[Box<[f32]>]> = data.iter().map(|series| series.targets.clone()).collect();
+
+        let mut last_checkpoint = Instant::now();
+        let mut r2 = 0.0;
+        let mut loss = 0.0;
+        while state.epoch < config.epochs {
+            let predictions;
+            (loss, predictions) = trainer::epoch(
+                self,
+                activation_function,
+                &rows,
+                config,
+                &mut state,
+                |i, outputs| {
+                    let loss = outputs
+                        .iter()
+                        .zip(targets[i].iter())
+                        .map(|(output, target)| config.loss.loss(*output, *target))
+                        .sum::<f32>()
+                        / width as f32;
+                    let output_grads = outputs
+                        .iter()
+                        .zip(targets[i].iter())
+                        .map(|(output, target)| config.loss.derivative(*output, *target))
+                        .collect();
+                    (loss, Gradient::Outputs(output_grads))
+                },
+            )?;
+            r2 = RegressionReport::new(&targets, &predictions).map_or(0.0, |report| report.r2);
+
+            let stop = trainer::end_epoch(
+                config,
+                &mut state,
+                loss,
+                Some(r2 * 100.0),
+                format_args!("Training R2: {:?}", r2),
+            )?;
+
+            trainer::checkpoint(self, &[], config, &state, &mut last_checkpoint)?;
+            if stop {
+                break;
+            }
+        }
+
+        self.config = Some(config.clone());
+        let model_name = trainer::save(self, config)?;
+
+        println!(
+            "Training: Finished with an R2 of {:?} after {:?} epochs\nloss: {}",
+            r2, state.epoch, loss
+        );
+
+        Ok((model_name, r2, loss))
+    }
+
+    /// Predicts the targets of a single raw input with a trained model, running it through the pipeline first
+    ///
+    /// ## Returns
+    /// The value of each answer node
+    ///
+    /// ## Err
+    /// ### ModelMissingActivationFunction
+    /// The network has no activation function
+    /// ### TransformFailed
+    /// The input doesn't have the features the pipeline was fitted on
+    /// ### InvalidLayerSpec
+    /// The input isn't as wide as the input layer once it's been through the pipeline
+    ///
+    /// ## Examples
+    /// ```ignore
+    /// let mut net = RegNetwork::read_model("prices.darj".to_string()).unwrap();
+    /// let price = net.predict(&[3.0, 2.0]).unwrap()[0];
+    /// ```
+    pub fn predict(&mut self, data: &[f32]) -> Result<Box<[f32]>, DarjeelingError> {
+        let data = match &self.pipeline {
+            Some(pipeline) => pipeline.transform_row(data)?,
+            None => data.into(),
+        };
+        layer::outputs(
+            &mut self.node_array,
+            &self.layers,
+            self.activation_function,
+            &data,
+        )
+    }
+
+    /// Predicts the targets of every input with a trained model
+    ///
+    /// ## Returns
+    /// The predictions for each input, in the same order as the data
+    ///
+    /// ## Err
+    /// The errors of [`predict`](RegNetwork::predict)
+    pub fn test(
+        &mut self,
+        data: &[RegressionSeries],
+    ) -> Result<Box<[Box<[f32]>]>, DarjeelingError> {
+        let rows = self.preprocess(data)?;
+        rows.iter()
+            .map(|row| {
+                layer::outputs(
+                    &mut self.node_array,
+                    &self.layers,
+                    self.activation_function,
+                    row,
+                )
+            })
+            .collect()
+    }
+
+    /// Evaluates a trained model against labelled data
+    ///
+    /// ## Returns
+    /// A report with the R², RMSE and MAE of each target and overall
+    ///
+    /// ## Err
+    /// The errors of [`predict`](RegNetwork::predict) and [`RegressionReport::new`]
+    ///
+    /// ## Examples
+    /// ```ignore
+    /// let report = net.evaluate(&data).unwrap();
+    /// println!("{}", report);
+    /// ```
+    pub fn evaluate(
+        &mut self,
+        data: &[RegressionSeries],
+    ) -> Result<RegressionReport, DarjeelingError> {
+        let predictions = self.test(data)?;
+        let targets: Box<[Box<[f32]>]> = data.iter().map(|series| series.targets.clone()).collect();
+        RegressionReport::new(&targets, &predictions)
+    }
+
+    /// Runs inputs through the pipeline, if the network has one
+    fn preprocess(&self, data: &[RegressionSeries]) -> Result<Box<[Box<[f32]>]>, DarjeelingError> {
+        data.iter()
+            .map(|series| match &self.pipeline {
+                Some(pipeline) => pipeline.transform_row(&series.data),
+                None => Ok(series.data.clone()),
+            })
+            .collect()
+    }
+
+    /// Sets the activation function of the hidden layers, the answer layer is always linear
+    pub fn set_activation_func(&mut self, new_activation_function: ActivationFunction) {
+        self.activation_function = Some(new_activation_function);
+    }
+}
+
+impl Model for RegNetwork {}
+
+impl Supervised for RegNetwork {
+    fn parts(&mut self) -> (&mut NodeArray, &[LayerSpec]) {
+        (&mut self.node_array, &self.layers)
+    }
+}
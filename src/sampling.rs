@@ -0,0 +1,157 @@
+use crate::{error::DarjeelingError, series::Series, split::shuffle};
+use rand::{Rng, SeedableRng};
+use rand_pcg::Pcg64;
+
+/// Randomly repeats the inputs of every answer until each answer has as many inputs as the most common one
+///
+/// ## Params
+/// - Data: The inputs to resample
+/// - Seed: The same seed always gives the same inputs
+///
+/// ## Examples
+/// ``` rust
+/// use darjeeling::{sampling::oversample, series::Series};
+///
+/// let mut data = vec![Series::new(vec![0.0], "fraud")];
+/// data.extend((0..9).map(|i| Series::new(vec![i as f32], "legit")));
+/// let balanced = oversample(&data, 42);
+/// assert_eq!(balanced.iter().filter(|series| series.answer == "fraud").count(), 9);
+/// ```
+pub fn oversample(data: &[Series], seed: u64) -> Box<[Series]> {
+    let mut rng = Pcg64::seed_from_u64(seed);
+    let groups = groups(data);
+    let largest = groups.iter().map(|group| group.len()).max().unwrap_or(0);
+
+    let mut sampled: Vec<Series> = data.to_vec();
+    for group in groups.iter() {
+        sampled.extend(
+            (group.len()..largest).map(|_| data[group[rng.gen_range(0..group.len())]].clone()),
+        );
+    }
+    shuffle(&mut sampled, &mut rng);
+    sampled.into_boxed_slice()
+}
+
+/// Randomly drops inputs of every answer until each answer has as many inputs as the least common one
+///
+/// ## Params
+/// - Data: The inputs to resample
+/// - Seed: The same seed always gives the same inputs
+pub fn undersample(data: &[Series], seed: u64) -> Box<[Series]> {
+    let mut rng = Pcg64::seed_from_u64(seed);
+    let mut groups = groups(data);
+    let smallest = groups.iter().map(|group| group.len()).min().unwrap_or(0);
+
+    let mut sampled: Vec<Series> = vec![];
+    for group in groups.iter_mut() {
+        shuffle(group, &mut rng);
+        sampled.extend(group[..smallest].iter().map(|i| data[*i].clone()));
+    }
+    shuffle(&mut sampled, &mut rng);
+    sampled.into_boxed_slice()
+}
+
+/// Creates synthetic inputs for every answer until each answer has as many inputs as the most common one,
+/// with the Synthetic Minority Over-sampling Technique.
+///
+/// Each synthetic input is a random point on the line between a random input of the answer
+/// and one of its nearest neighbours with the same answer.
+///
+/// ## Params
+/// - Data: The inputs to resample, which can't have missing features
+/// - Neighbours: How many of the nearest inputs a neighbour is chosen from, usually five
+/// - Seed: The same seed always gives the same inputs
+///
+/// ## Err
+/// ### InvalidSampling
+/// There are no neighbours to choose from, an answer that needs synthetic inputs only has one,
+/// or the inputs have different numbers of features
+pub fn smote(
+    data: &[Series],
+    neighbours: usize,
+    seed: u64,
+) -> Result<Box<[Series]>, DarjeelingError> {
+    if neighbours == 0 {
+        return Err(DarjeelingError::InvalidSampling(
+            "SMOTE needs at least one neighbour to choose from".to_string(),
+        ));
+    }
+    let width = data.first().map_or(0, |series| series.data.len());
+    if data.iter().any(|series| series.data.len() != width) {
+        return Err(DarjeelingError::InvalidSampling(
+            "every input needs the same number of features".to_string(),
+        ));
+    }
+
+    let mut rng = Pcg64::seed_from_u64(seed);
+    let groups = groups(data);
+    let largest = groups.iter().map(|group| group.len()).max().unwrap_or(0);
+
+    let mut sampled: Vec<Series> = data.to_vec();
+    for group in groups.iter().filter(|group| group.len() < largest) {
+        if group.len() < 2 {
+            return Err(DarjeelingError::InvalidSampling(format!(
+                "{:?} only has one input, so it has no neighbours",
+                data[group[0]].answer
+            )));
+        }
+
+        let nearest: Vec<Vec<usize>> = group
+            .iter()
+            .map(|i| nearest(data, group, *i, neighbours))
+            .collect();
+        for _ in group.len()..largest {
+            let chosen = rng.gen_range(0..group.len());
+            let neighbour = &data[nearest[chosen][rng.gen_range(0..nearest[chosen].len())]];
+            let origin = &data[group[chosen]];
+            let gap: f32 = rng.gen();
+            let synthetic: Box<[f32]> = origin
+                .data
+                .iter()
+                .zip(neighbour.data.iter())
+                .map(|(from, to)| from + gap * (to - from))
+                .collect();
+            sampled.push(Series::new(synthetic, origin.answer.clone()));
+        }
+    }
+    shuffle(&mut sampled, &mut rng);
+    Ok(sampled.into_boxed_slice())
+}
+
+/// The indices of the inputs of each answer, in order of the answers
+fn groups(data: &[Series]) -> Vec<Vec<usize>> {
+    let mut answers: Vec<&str> = data.iter().map(|series| series.answer.as_str()).collect();
+    answers.sort_unstable();
+    answers.dedup();
+    answers
+        .iter()
+        .map(|answer| {
+            (0..data.len())
+                .filter(|i| data[*i].answer == *answer)
+                .collect()
+        })
+        .collect()
+}
+
+/// The nearest inputs of a group to one of its inputs, by euclidean distance
+fn nearest(data: &[Series], group: &[usize], origin: usize, neighbours: usize) -> Vec<usize> {
+    let mut distances: Vec<(f32, usize)> = group
+        .iter()
+        .filter(|i| **i != origin)
+        .map(|i| {
+            let distance = data[origin]
+                .data
+                .iter()
+                .zip(data[*i].data.iter())
+                .map(|(a, b)| (a - b).powi(2))
+                .sum::<f32>();
+            (distance, *i)
+        })
+        .collect();
+    distances.sort_by(|a, b| a.0.total_cmp(&b.0));
+    distances
+        .into_iter()
+        .take(neighbours)
+        .map(|(_, i)| i)
+        .collect()
+}
@@ -0,0 +1,440 @@
+use crate::{
+    builder::NetworkBuilder,
+    config::TrainConfig,
+    error::DarjeelingError,
+    metrics::ClassificationReport,
+    neural_network::NeuralNetwork,
+    optimizer::Optimizer,
+    preprocessing::{check_missing, feature_rows},
+    series::Series,
+    split::shuffle,
+    validation::{cross_validate, Folds},
+};
+use rand::SeedableRng;
+use rand_pcg::Pcg64;
+use serde::{Deserialize, Serialize};
+use std::{cmp::Ordering, fmt};
+
+/// The values tried for each hyperparameter.
+/// An empty list leaves that hyperparameter as it is in the base builder or config.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Default)]
+#[serde(default)]
+pub struct SearchSpace {
+    pub learning_rates: Vec<f32>,
+    /// The width of every hidden layer
+    pub hidden_widths: Vec<usize>,
+    /// The number of hidden layers
+    pub hidden_layers: Vec<usize>,
+    pub batch_sizes: Vec<usize>,
+    pub optimizers: Vec<Optimizer>,
+}
+
+impl SearchSpace {
+    /// Every combination of the values in the space, applied to the base builder and config
+    pub fn grid(&self, builder: &NetworkBuilder, config: &TrainConfig) -> Vec<Candidate> {
+        let mut candidates = vec![Candidate {
+            builder: builder.clone(),
+            config: config.clone(),
+        }];
+
+        candidates = expand(
+            candidates,
+            &self.learning_rates,
+            |candidate, learning_rate| {
+                candidate.config.learning_rate = learning_rate;
+            },
+        );
+        candidates = expand(candidates, &self.hidden_widths, |candidate, width| {
+            candidate.builder = candidate.builder.with_hidden(Some(width), None);
+        });
+        candidates = expand(
+            candidates,
+            &self.hidden_layers,
+            |candidate, hidden_layers| {
+                candidate.builder = candidate.builder.with_hidden(None, Some(hidden_layers));
+            },
+        );
+        candidates = expand(candidates, &self.batch_sizes, |candidate, batch_size| {
+            candidate.config.batch_size = batch_size;
+        });
+        expand(candidates, &self.optimizers, |candidate, optimizer| {
+            candidate.config.optimizer = optimizer;
+        })
+    }
+}
+
+/// Makes a copy of every candidate for every value
+fn expand<T: Copy>(
+    candidates: Vec<Candidate>,
+    values: &[T],
+    apply: impl Fn(&mut Candidate, T),
+) -> Vec<Candidate> {
+    if values.is_empty() {
+        return candidates;
+    }
+
+    candidates
+        .iter()
+        .flat_map(|candidate| {
+            values.iter().map(|value| {
+                let mut candidate = candidate.clone();
+                apply(&mut candidate, *value);
+                candidate
+            })
+        })
+        .collect()
+}
+
+/// Which candidates are evaluated, and for how long
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+pub enum Strategy {
+    /// Every combination in the space
+    Grid,
+    /// The given number of combinations, picked at random without repeats
+    Random { candidates: usize },
+    /// Starts every candidate (or the given number of random ones) with a few epochs,
+    /// then repeatedly keeps the best `1 / factor` of them and multiplies their epochs by `factor`,
+    /// until one is left or the config's epochs are reached
+    SuccessiveHalving {
+        candidates: Option<usize>,
+        min_epochs: usize,
+        factor: usize,
+    },
+}
+
+/// How each candidate is scored
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub enum Evaluation {
+    /// Trained on all the data, then evaluated on this separate data
+    Holdout(Box<[Series]>),
+    /// Cross validated on the data, scored by the mean over the folds
+    CrossValidate(Folds),
+}
+
+/// The metric candidates are ranked by
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Default)]
+pub enum Metric {
+    #[default]
+    Accuracy,
+    MacroF1,
+    /// Lower is better
+    LogLoss,
+}
+
+impl Metric {
+    fn of(&self, report: &ClassificationReport) -> f32 {
+        match self {
+            Metric::Accuracy => report.accuracy,
+            Metric::MacroF1 => report.macro_avg.f1,
+            Metric::LogLoss => report.log_loss,
+        }
+    }
+
+    /// Orders two scores best first, with NaN scores after every other one
+    fn compare(&self, score: f32, other: f32) -> Ordering {
+        score
+            .is_nan()
+            .cmp(&other.is_nan())
+            .then_with(|| match self {
+                Metric::LogLoss => score.total_cmp(&other),
+                _ => other.total_cmp(&score),
+            })
+    }
+}
+
+/// A network and the config it's trained with
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct Candidate {
+    pub builder: NetworkBuilder,
+    pub config: TrainConfig,
+}
+
+impl Candidate {
+    pub fn to_json(&self) -> Result<String, DarjeelingError> {
+        serde_json::to_string_pretty(self)
+            .map_err(|err| DarjeelingError::SerializationFailed(err.to_string()))
+    }
+
+    pub fn from_json(json: &str) -> Result<Candidate, DarjeelingError> {
+        serde_json::from_str(json).map_err(|err| DarjeelingError::ConfigReadFailed(err.to_string()))
+    }
+}
+
+/// How a candidate scored
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct SearchResult {
+    pub candidate: Candidate,
+    /// NaN if the candidate's training diverged
+    pub score: f32,
+    /// The epochs the candidate was last trained for, fewer than the config's if successive halving dropped it
+    pub epochs: usize,
+    /// Why the candidate's training diverged, none if it didn't
+    pub error: Option<String>,
+}
+
+/// Every candidate's result, best first
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct Leaderboard {
+    pub metric: Metric,
+    pub results: Box<[SearchResult]>,
+}
+
+impl Leaderboard {
+    pub fn best(&self) -> Option<&SearchResult> {
+        self.results.first()
+    }
+
+    pub fn to_json(&self) -> Result<String, DarjeelingError> {
+        serde_json::to_string_pretty(self)
+            .map_err(|err| DarjeelingError::SerializationFailed(err.to_string()))
+    }
+}
+
+impl fmt::Display for Leaderboard {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "{:>5} {:>10} {:>8} {:>14} {:>8} {:>7} {:>10}",
+            "rank",
+            format!("{:?}", self.metric),
+            "epochs",
+            "learning rate",
+            "hidden",
+            "batch",
+            "optimizer"
+        )?;
+        for (rank, result) in self.results.iter().enumerate() {
+            let layers = result.candidate.builder.layers();
+            let hidden = layers[..layers.len().saturating_sub(1)]
+                .iter()
+                .map(|layer| layer.width.to_string())
+                .collect::<Vec<String>>()
+                .join("x");
+            let optimizer = match result.candidate.config.optimizer {
+                Optimizer::Sgd => "sgd",
+                Optimizer::Momentum { .. } => "momentum",
+                Optimizer::Adam { .. } => "adam",
+            };
+            writeln!(
+                f,
+                "{:>5} {:>10.4} {:>8} {:>14} {:>8} {:>7} {:>10}",
+                rank + 1,
+                result.score,
+                result.epochs,
+                result.candidate.config.learning_rate,
+                hidden,
+                result.candidate.config.batch_size,
+                optimizer
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// Hyperparameter search over categorization networks
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct Search {
+    pub space: SearchSpace,
+    pub strategy: Strategy,
+    pub evaluation: Evaluation,
+    pub metric: Metric,
+}
+
+impl Search {
+    /// Trains and scores candidates from the space
+    ///
+    /// ## Params
+    /// - Builder: The base network, its input and answer layers are kept by every candidate
+    /// - Config: The base config, candidates are never saved or checkpointed.
+    ///   Its seed also picks the random candidates.
+    /// - Data: The data candidates are trained on, with its correct answers
+    /// - Categories: The categories of the answer layer, in order
+    ///
+    /// ## Returns
+    /// A leaderboard of every candidate, the best one's config can be saved with [`Candidate::to_json`].
+    /// Candidates whose training diverged, usually because their learning rate is too high,
+    /// have a NaN score and are ranked last.
+    ///
+    /// ## Err
+    /// ### InvalidSearch
+    /// The strategy has no candidates, or successive halving has a factor below 2
+    /// ### NanEncountered
+    /// An input has a missing (NaN) feature
+    /// ### InvalidLayerSpec
+    /// A candidate's network couldn't be built
+    /// ### InvalidFolds
+    /// The data can't be split into the evaluation's folds
+    ///
+    /// ## Examples
+    /// ```ignore
+    /// use darjeeling::search::{Evaluation, Metric, Search, SearchSpace, Strategy};
+    ///
+    /// let search = Search {
+    ///     space: SearchSpace {
+    ///         learning_rates: vec![0.01, 0.05, 0.1],
+    ///         hidden_widths: vec![32, 64, 128],
+    ///         ..Default::default()
+    ///     },
+    ///     strategy: Strategy::SuccessiveHalving { candidates: None, min_epochs: 5, factor: 2 },
+    ///     evaluation: Evaluation::CrossValidate(Folds::new(3).stratified()),
+    ///     metric: Metric::MacroF1,
+    /// };
+    /// let leaderboard = search.run(&builder, &config, &data, &categories).unwrap();
+    /// fs::write("best.json", leaderboard.best().unwrap().candidate.to_json().unwrap()).unwrap();
+    /// ```
+    pub fn run(
+        &self,
+        builder: &NetworkBuilder,
+        config: &TrainConfig,
+        data: &[Series],
+        categories: &[String],
+    ) -> Result<Leaderboard, DarjeelingError> {
+        // Checked up front, so only diverging candidates fail with NaN
+        check_missing(&feature_rows(data))?;
+        let mut rng = match config.seed {
+            Some(seed) => Pcg64::seed_from_u64(seed),
+            None => Pcg64::from_entropy(),
+        };
+        let base = TrainConfig {
+            save_path: None,
+            checkpoint: None,
+            ..config.clone()
+        };
+        let mut candidates = self.space.grid(builder, &base);
+
+        let pick = match self.strategy {
+            Strategy::Grid => None,
+            Strategy::Random { candidates } => Some(candidates),
+            Strategy::SuccessiveHalving { candidates, .. } => candidates,
+        };
+        if let Some(count) = pick {
+            shuffle(&mut candidates, &mut rng);
+            candidates.truncate(count);
+        }
+        if candidates.is_empty() {
+            return Err(DarjeelingError::InvalidSearch(
+                "there are no candidates to evaluate".to_string(),
+            ));
+        }
+
+        let results = match self.strategy {
+            Strategy::SuccessiveHalving {
+                min_epochs, factor, ..
+            } => self.successive_halving(candidates, min_epochs, factor, data, categories)?,
+            _ => {
+                let mut results = candidates
+                    .into_iter()
+                    .map(|candidate| self.score(candidate, base.epochs, data, categories))
+                    .collect::<Result<Vec<SearchResult>, DarjeelingError>>()?;
+                self.rank(&mut results);
+                results
+            }
+        };
+
+        Ok(Leaderboard {
+            metric: self.metric,
+            results: results.into_boxed_slice(),
+        })
+    }
+
+    /// Runs rounds of training, dropping the worst candidates after each one.
+    /// Dropped candidates are ranked below every candidate that outlasted them.
+    fn successive_halving(
+        &self,
+        mut candidates: Vec<Candidate>,
+        min_epochs: usize,
+        factor: usize,
+        data: &[Series],
+        categories: &[String],
+    ) -> Result<Vec<SearchResult>, DarjeelingError> {
+        if factor < 2 {
+            return Err(DarjeelingError::InvalidSearch(format!(
+                "successive halving needs a factor of at least 2, not {}",
+                factor
+            )));
+        }
+
+        let max_epochs = candidates[0].config.epochs;
+        let mut epochs = min_epochs.clamp(1, max_epochs.max(1));
+        let mut dropped: Vec<SearchResult> = vec![];
+        loop {
+            let mut results = candidates
+                .into_iter()
+                .map(|candidate| self.score(candidate, epochs, data, categories))
+                .collect::<Result<Vec<SearchResult>, DarjeelingError>>()?;
+            self.rank(&mut results);
+
+            if results.len() == 1 || epochs >= max_epochs {
+                // Later rounds are at the front
+                results.extend(dropped);
+                return Ok(results);
+            }
+
+            let kept = (results.len() / factor).max(1);
+            dropped = results.split_off(kept).into_iter().chain(dropped).collect();
+            candidates = results.into_iter().map(|result| result.candidate).collect();
+            epochs = (epochs * factor).min(max_epochs);
+        }
+    }
+
+    /// Trains a candidate for the given epochs, and scores it.
+    /// A candidate whose training diverges scores NaN, the search carries on without it.
+    fn score(
+        &self,
+        mut candidate: Candidate,
+        epochs: usize,
+        data: &[Series],
+        categories: &[String],
+    ) -> Result<SearchResult, DarjeelingError> {
+        let full_epochs = candidate.config.epochs;
+        let config = TrainConfig {
+            epochs,
+            ..candidate.config.clone()
+        };
+
+        let (score, error) = match self.evaluate(&candidate.builder, &config, data, categories) {
+            Ok(score) => (score, None),
+            Err(DarjeelingError::NanEncountered(err)) => (f32::NAN, Some(err)),
+            Err(err) => return Err(err),
+        };
+
+        candidate.config.epochs = full_epochs;
+        Ok(SearchResult {
+            candidate,
+            score,
+            epochs,
+            error,
+        })
+    }
+
+    /// Trains a network with the config, and scores it with the search's evaluation and metric
+    fn evaluate(
+        &self,
+        builder: &NetworkBuilder,
+        config: &TrainConfig,
+        data: &[Series],
+        categories: &[String],
+    ) -> Result<f32, DarjeelingError> {
+        match &self.evaluation {
+            Evaluation::Holdout(validation) => {
+                let mut net = builder.build_cat()?;
+                net.train(data, categories.into(), config)?;
+                Ok(self.metric.of(&net.evaluate(validation, categories)?))
+            }
+            Evaluation::CrossValidate(folds) => {
+                let report = cross_validate(builder, config, data, categories, *folds)?;
+                Ok(report
+                    .folds
+                    .iter()
+                    .map(|fold| self.metric.of(&fold.report))
+                    .sum::<f32>()
+                    / report.folds.len() as f32)
+            }
+        }
+    }
+
+    /// Sorts results best first, and the ones that diverged last
+    fn rank(&self, results: &mut [SearchResult]) {
+        results.sort_by(|a, b| self.metric.compare(a.score, b.score));
+    }
+}
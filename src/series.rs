@@ -1,7 +1,8 @@
+use serde::{Deserialize, Serialize};
 use std::fmt::Display;
 
 #[allow(dead_code)]
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 
 /// Represents the training inputs to a neural network
 pub struct Series {
@@ -18,10 +19,9 @@ impl Series {
     /// # Examples
     /// This example is for one input into an XOR gate
     /// ```
-    /// use darjeeling::input::Series;
-    /// use darjeeling::types::Types;
+    /// use darjeeling::series::Series;
     /// let inputs = vec![0.0,1.0];
-    /// let answer = String::from("1")
+    /// let answer = String::from("1");
     /// let formated_input = Series::new(inputs, answer);
     /// ```
     pub fn new<T, U>(data: T, answer: U) -> Series
@@ -36,6 +36,74 @@ impl Series {
     }
 }
 
+/// Represents a training input that can belong to any number of labels at once
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct MultiLabelSeries {
+    pub data: Box<[f32]>,
+    /// Every label of the input, in any order
+    pub answers: Box<[String]>,
+}
+
+impl MultiLabelSeries {
+    /// Creates new input
+    /// # Params
+    /// - Inputs: A list of 32-bit floating point numbers.
+    /// - Answers: The labels of this input, which can be empty.
+    ///
+    /// # Examples
+    /// ```
+    /// use darjeeling::series::MultiLabelSeries;
+    /// let formated_input = MultiLabelSeries::new(vec![0.2, 0.9], &["rust", "tutorial"]);
+    /// assert!(formated_input.has("rust"));
+    /// ```
+    pub fn new<T, U>(data: T, answers: &[U]) -> MultiLabelSeries
+    where
+        T: Into<Box<[f32]>>,
+        U: ToString,
+    {
+        MultiLabelSeries {
+            data: data.into(),
+            answers: answers.iter().map(|answer| answer.to_string()).collect(),
+        }
+    }
+
+    /// Whether the input has the label
+    pub fn has(&self, label: &str) -> bool {
+        self.answers.iter().any(|answer| answer == label)
+    }
+}
+
+/// Represents a training input whose answer is one or more continuous values
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct RegressionSeries {
+    pub data: Box<[f32]>,
+    /// The value each answer node should output for this input, in order
+    pub targets: Box<[f32]>,
+}
+
+impl RegressionSeries {
+    /// Creates new input
+    /// # Params
+    /// - Inputs: A list of 32-bit floating point numbers.
+    /// - Targets: The values the network should predict for this input.
+    ///
+    /// # Examples
+    /// ```
+    /// use darjeeling::series::RegressionSeries;
+    /// let formated_input = RegressionSeries::new(vec![3.0, 2.0], vec![1850.0]);
+    /// ```
+    pub fn new<T, U>(data: T, targets: U) -> RegressionSeries
+    where
+        T: Into<Box<[f32]>>,
+        U: Into<Box<[f32]>>,
+    {
+        RegressionSeries {
+            data: data.into(),
+            targets: targets.into(),
+        }
+    }
+}
+
 impl Display for Series {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         let mut buffer = String::from("");
@@ -0,0 +1,114 @@
+use crate::{error::DarjeelingError, series::Series};
+use rand::{Rng, SeedableRng};
+use rand_pcg::Pcg64;
+
+/// A training set, then a test set
+pub type Split = (Box<[Series]>, Box<[Series]>);
+
+/// Shuffles data in place with a Fisher–Yates shuffle, so every order is equally likely
+/// and the same rng state always gives the same order
+pub fn shuffle<T>(data: &mut [T], rng: &mut impl Rng) {
+    for i in (1..data.len()).rev() {
+        data.swap(i, rng.gen_range(0..=i));
+    }
+}
+
+/// Every item of the data in a random order, without moving the data
+pub fn shuffled<'a, T>(data: &'a [T], rng: &mut impl Rng) -> Vec<&'a T> {
+    let mut shuffled: Vec<&T> = data.iter().collect();
+    shuffle(&mut shuffled, rng);
+    shuffled
+}
+
+/// Splits inputs into a training set and a test set, after shuffling them
+///
+/// ## Params
+/// - Data: The inputs to split
+/// - Test Size: The share of the inputs in the test set, between zero and one
+/// - Seed: The same seed always gives the same split
+///
+/// ## Returns
+/// The training set, then the test set
+///
+/// ## Err
+/// ### InvalidSplit
+/// The test size would leave either set empty
+///
+/// ## Examples
+/// ``` rust
+/// use darjeeling::{series::Series, split::train_test_split};
+///
+/// let data: Vec<Series> = (0..10).map(|i| Series::new(vec![i as f32], "a")).collect();
+/// let (train, test) = train_test_split(&data, 0.2, 42).unwrap();
+/// assert_eq!((train.len(), test.len()), (8, 2));
+/// ```
+pub fn train_test_split(
+    data: &[Series],
+    test_size: f32,
+    seed: u64,
+) -> Result<Split, DarjeelingError> {
+    let test_len = test_len(data.len(), test_size)?;
+    let mut indices: Vec<usize> = (0..data.len()).collect();
+    shuffle(&mut indices, &mut Pcg64::seed_from_u64(seed));
+
+    let (test, train) = indices.split_at(test_len);
+    Ok((collect(data, train), collect(data, test)))
+}
+
+/// Splits inputs like [`train_test_split`], but gives both sets the same share of each answer as the whole data.
+/// Each answer's share of the test set is rounded separately, so answers with very few inputs may only be in the training set.
+///
+/// ## Err
+/// ### InvalidSplit
+/// The test size would leave either set empty
+pub fn stratified_split(
+    data: &[Series],
+    test_size: f32,
+    seed: u64,
+) -> Result<Split, DarjeelingError> {
+    test_len(data.len(), test_size)?;
+    let mut rng = Pcg64::seed_from_u64(seed);
+
+    let mut answers: Vec<&str> = data.iter().map(|series| series.answer.as_str()).collect();
+    answers.sort_unstable();
+    answers.dedup();
+
+    let mut train = vec![];
+    let mut test = vec![];
+    for answer in answers {
+        let mut group: Vec<usize> = (0..data.len())
+            .filter(|i| data[*i].answer == answer)
+            .collect();
+        shuffle(&mut group, &mut rng);
+        let group_test = (group.len() as f32 * test_size).round() as usize;
+        test.extend_from_slice(&group[..group_test]);
+        train.extend_from_slice(&group[group_test..]);
+    }
+    if train.is_empty() || test.is_empty() {
+        return Err(DarjeelingError::InvalidSplit(format!(
+            "a test size of {} leaves a set empty once every answer is rounded",
+            test_size
+        )));
+    }
+
+    // Otherwise the sets would be ordered by answer
+    shuffle(&mut train, &mut rng);
+    shuffle(&mut test, &mut rng);
+    Ok((collect(data, &train), collect(data, &test)))
+}
+
+/// The number of inputs in the test set
+fn test_len(len: usize, test_size: f32) -> Result<usize, DarjeelingError> {
+    let test_len = (len as f32 * test_size).round() as usize;
+    if !(0.0..1.0).contains(&test_size) || test_len == 0 || test_len >= len {
+        return Err(DarjeelingError::InvalidSplit(format!(
+            "a test size of {} leaves a set empty, with {} inputs",
+            test_size, len
+        )));
+    }
+    Ok(test_len)
+}
+
+fn collect(data: &[Series], indices: &[usize]) -> Box<[Series]> {
+    indices.iter().map(|i| data[*i].clone()).collect()
+}
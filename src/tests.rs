@@ -1,12 +1,51 @@
 use core::panic;
+use rand::{Rng, SeedableRng};
+use rand_pcg::Pcg64;
 use std::{
     fs,
     io::{BufRead, BufReader},
+    time::{Duration, Instant},
 };
 
 use crate::{
-    activation::ActivationFunction, categorize::CatNetwork, dbg_println, generation::GenNetwork,
-    neural_network::NeuralNetwork, series::Series, DEBUG,
+    activation::ActivationFunction,
+    autoencoder::{AutoEncoder, Corruption},
+    builder::NetworkBuilder,
+    categorize::CatNetwork,
+    checkpoint::{Checkpoint, CheckpointConfig},
+    config::{ClassWeights, EarlyStopping, Loss, Schedule, TrainConfig},
+    dataframe::{Column as DataColumn, DataFrame, Value},
+    dataset::{
+        csv::{Column, CsvReader},
+        idx, libsvm, npy,
+    },
+    error::DarjeelingError,
+    generation::{GenNetwork, Latent, Monitor},
+    layer::{
+        accumulate_grads, answer_outputs, backpropogate, push_downstream, LayerSpec, NodeArray,
+        Normalization,
+    },
+    metrics::{
+        macro_auc, ClassificationReport, GenerationReport, MultiLabelReport, PrCurve,
+        RegressionReport, RocCurve,
+    },
+    multilabel::MultiLabelNetwork,
+    neural_network::{Model, NeuralNetwork},
+    optimizer::Optimizer,
+    preprocessing::{
+        encoder::{Encoder, Encoding, FrameEncoder, HashingEncoder, Unknown},
+        imputer::{Imputation, Imputer},
+        pipeline::{Pipeline, Scaling, Transformer},
+        scaler::{MinMaxScaler, RobustScaler, Scaler, StandardScaler},
+        Transform,
+    },
+    regression::RegNetwork,
+    sampling::{oversample, smote, undersample},
+    search::{Candidate, Evaluation, Metric, Search, SearchSpace, Strategy},
+    series::{MultiLabelSeries, RegressionSeries, Series},
+    split::{shuffle, shuffled, stratified_split, train_test_split},
+    validation::{cross_validate, Folds},
+    variational::VarAutoEncoder,
 };
 
 // #[test]
@@ -49,13 +88,322 @@ fn train_network_xor(
         Some(ActivationFunction::Sigmoid),
     );
 
-    net.train(&data, categories, learning_rate, "xor", 99.0, true)
+    let config = TrainConfig {
+        learning_rate,
+        epochs: 10_000,
+        target_accuracy: Some(99.0),
+        save_path: Some("xor.darj".to_string()),
+        ..Default::default()
+    };
+    net.train(&data, categories, &config)
         .expect("Failed to train xor network");
     net
 }
 
+#[test]
+fn train_test_xor_builder() {
+    let categories: Box<[String]> = vec!["1".to_string(), "0".to_string()].into_boxed_slice();
+    let data: Box<[Series]> = xor_file();
+
+    let mut net = NetworkBuilder::new(2)
+        .activation(ActivationFunction::Sigmoid)
+        .layer(LayerSpec::new(8).activation(ActivationFunction::Tanh))
+        .layer(LayerSpec::new(8).normalization(Normalization::Layer))
+        .layer(LayerSpec::new(2))
+        .build_cat()
+        .unwrap();
+
+    // Accuracy is measured while the weights change during the epoch,
+    // so training carries on past the first epoch that gets every answer right
+    let config = TrainConfig {
+        learning_rate: 0.5,
+        epochs: 2_000,
+        seed: Some(1),
+        ..Default::default()
+    };
+    let (_, accuracy, _) = net
+        .train(&data, categories.clone(), &config)
+        .expect("Failed to train xor network");
+    assert_eq!(accuracy, 100.0);
+    let answers = net.test(&data, categories).unwrap();
+    assert!(answers
+        .iter()
+        .zip(data.iter())
+        .all(|(answer, series)| *answer == series.answer));
+}
+
+#[test]
+fn builder_rejects_invalid_layers() {
+    assert!(NetworkBuilder::new(2).build_cat().is_err());
+    assert!(NetworkBuilder::new(2).hidden(0).build_cat().is_err());
+    assert!(NetworkBuilder::new(2)
+        .layer(LayerSpec::new(2).dropout(1.0))
+        .build_gen()
+        .is_err());
+}
+
+#[test]
+fn with_hidden_without_hidden_layers() {
+    let builder = NetworkBuilder::new(3)
+        .activation(ActivationFunction::Sigmoid)
+        .hidden(2);
+
+    let deeper = builder.with_hidden(None, Some(2));
+    let widths: Vec<usize> = deeper.layers().iter().map(|layer| layer.width).collect();
+    assert_eq!(widths, [3, 3, 2]);
+    assert!(deeper.build_cat().is_ok());
+
+    let widened = builder.with_hidden(Some(5), Some(1));
+    assert_eq!(widened.layers()[0].width, 5);
+
+    // Every candidate of a search over a builder without hidden layers can be built
+    let space = SearchSpace {
+        hidden_layers: vec![0, 1, 2],
+        ..Default::default()
+    };
+    let grid = space.grid(&builder, &TrainConfig::default());
+    assert_eq!(grid.len(), 3);
+    assert!(grid
+        .iter()
+        .all(|candidate| candidate.builder.build_cat().is_ok()));
+}
+
+#[test]
+fn convolution_gradients() {
+    // 2 channels of 9, through a strided, padded and dilated convolution and both poolings
+    let builder = NetworkBuilder::new(18)
+        .input_channels(2)
+        .activation(ActivationFunction::Tanh)
+        .layer(LayerSpec::conv1d(3, 3).stride(2).padding(2).dilation(2))
+        .layer(LayerSpec::max_pool1d(2).stride(1).padding(1))
+        .layer(LayerSpec::avg_pool1d(3).padding(1))
+        .hidden(4);
+    let (mut node_array, layers) = builder.build_parts().unwrap();
+    // (9 + 4 - 5) / 2 + 1 = 5, (5 + 2 - 2) / 1 + 1 = 6, (6 + 2 - 3) / 3 + 1 = 2
+    let widths: Vec<usize> = layers.iter().map(|layer| layer.width).collect();
+    assert_eq!(widths, [18, 15, 18, 6, 4]);
+    assert_eq!(layers[2].channels, 3);
+
+    let mut rng = Pcg64::seed_from_u64(41);
+    let data: Box<[f32]> = (0..18).map(|_| rng.gen_range(-1.0..1.0)).collect();
+    let weights = [0.3, -0.7, 0.5, 0.9];
+    let mut loss = |node_array: &mut NodeArray, data: &[f32]| -> f32 {
+        push_downstream(
+            node_array,
+            &layers,
+            ActivationFunction::Tanh,
+            data,
+            false,
+            &mut rng,
+        );
+        answer_outputs(node_array)
+            .iter()
+            .zip(weights.iter())
+            .map(|(output, weight)| output * weight)
+            .sum()
+    };
+    loss(&mut node_array, &data);
+    let input_grads = backpropogate(&mut node_array, &layers, ActivationFunction::Tanh, &weights);
+    accumulate_grads(&mut node_array, &layers);
+
+    let epsilon = 1e-3;
+    for i in 0..data.len() {
+        let (mut up, mut down) = (data.to_vec(), data.to_vec());
+        up[i] += epsilon;
+        down[i] -= epsilon;
+        let numeric = (loss(&mut node_array.clone(), &up) - loss(&mut node_array.clone(), &down))
+            / (2.0 * epsilon);
+        assert!(
+            (numeric - input_grads[i]).abs() < 1e-2,
+            "input {} numeric {} backpropogated {}",
+            i,
+            numeric,
+            input_grads[i]
+        );
+    }
+
+    // Every position of a filter shares its weights and gradients
+    let filter = &node_array[1][5..10];
+    assert!(filter
+        .iter()
+        .all(|node| node.link_weights == filter[0].link_weights
+            && node.link_grads == filter[0].link_grads));
+    for link in [0, 4] {
+        let nudged = |amount: f32| {
+            let mut node_array = node_array.clone();
+            node_array[1][5..10]
+                .iter_mut()
+                .for_each(|node| node.link_weights[link] += amount);
+            node_array
+        };
+        let numeric = (loss(&mut nudged(epsilon), &data) - loss(&mut nudged(-epsilon), &data))
+            / (2.0 * epsilon);
+        assert!(
+            (numeric - filter[0].link_grads[link]).abs() < 1e-2,
+            "link {} numeric {} accumulated {}",
+            link,
+            numeric,
+            filter[0].link_grads[link]
+        );
+    }
+    assert!(node_array[2]
+        .iter()
+        .all(|node| node.link_weights.is_empty()));
+
+    assert!(NetworkBuilder::new(9)
+        .input_channels(2)
+        .hidden(1)
+        .build_cat()
+        .is_err());
+    assert!(NetworkBuilder::new(4)
+        .layer(LayerSpec::conv1d(2, 5))
+        .hidden(1)
+        .build_cat()
+        .is_err());
+    assert!(NetworkBuilder::new(4)
+        .layer(LayerSpec::max_pool1d(2).stride(0))
+        .hidden(1)
+        .build_cat()
+        .is_err());
+}
+
+#[test]
+fn convolution_digits() {
+    let categories: Box<[String]> = (0..10).map(|n| n.to_string()).collect();
+    let data: Box<[Series]> = digits_file()[..500].into();
+    // Each row of the 8x8 image is a channel
+    let mut net = NetworkBuilder::new(64)
+        .input_channels(8)
+        .activation(ActivationFunction::Sigmoid)
+        .layer(
+            LayerSpec::conv1d(8, 3)
+                .padding(1)
+                .activation(ActivationFunction::Tanh),
+        )
+        .layer(LayerSpec::max_pool1d(2))
+        .hidden(10)
+        .build_cat()
+        .unwrap();
+    net.set_pipeline(Pipeline::new().scale(Scaling::MinMax));
+    let config = TrainConfig {
+        learning_rate: 0.01,
+        epochs: 15,
+        batch_size: 8,
+        optimizer: Optimizer::adam(),
+        seed: Some(43),
+        save_path: Some("digits_conv.darj".to_string()),
+        ..Default::default()
+    };
+    let (model_name, accuracy, _) = net.train(&data, categories.clone(), &config).unwrap();
+    assert!(accuracy > 85.0, "accuracy {}", accuracy);
+
+    let mut model = CatNetwork::read_model(model_name.unwrap()).unwrap();
+    let report = model.evaluate(&data, &categories).unwrap();
+    assert!(report.accuracy > 0.85, "accuracy {}", report.accuracy);
+    assert_eq!(
+        model.predict(&data[0].data).unwrap(),
+        net.predict(&data[0].data).unwrap()
+    );
+    fs::remove_file("digits_conv.darj").unwrap();
+}
+
+#[test]
+fn train_test_xor_config() {
+    let categories: Box<[String]> = vec!["1".to_string(), "0".to_string()].into_boxed_slice();
+    let data: Box<[Series]> = xor_file();
+    let config = TrainConfig::from_toml(
+        "
+        learning_rate = 0.05
+        epochs = 200
+        batch_size = 2
+        seed = 7
+        save_path = \"xor_config.darj\"
+        schedule = { Step = { every = 50, factor = 0.5 } }
+        early_stopping = { patience = 20, min_delta = 0.0 }
+        optimizer = { Adam = { beta1 = 0.9, beta2 = 0.999, epsilon = 1e-8 } }
+        ",
+    )
+    .unwrap();
+    assert_eq!(config.optimizer, Optimizer::adam());
+    assert_eq!(
+        config.schedule,
+        Schedule::Step {
+            every: 50,
+            factor: 0.5
+        }
+    );
+    assert_eq!(
+        config.early_stopping,
+        Some(EarlyStopping {
+            patience: 20,
+            min_delta: 0.0
+        })
+    );
+    assert_eq!(
+        TrainConfig::from_json(&config.to_json().unwrap()).unwrap(),
+        config
+    );
+
+    let mut net = CatNetwork::new(2, 4, 2, 1, Some(ActivationFunction::Sigmoid));
+    let (model_name, _, _) = net.train(&data, categories, &config).unwrap();
+
+    let model = CatNetwork::read_model(model_name.unwrap()).unwrap();
+    assert_eq!(model.config(), Some(&config));
+}
+
+#[test]
+fn resume_digits_from_checkpoint() {
+    let categories: Box<[String]> = (0..10).map(|n| n.to_string()).collect();
+    let data: Box<[Series]> = digits_file()[..500].into();
+    let builder = NetworkBuilder::new(64)
+        .activation(ActivationFunction::Sigmoid)
+        .layer(LayerSpec::new(32).dropout(0.1))
+        .hidden(10);
+    let config = TrainConfig {
+        learning_rate: 0.01,
+        epochs: 4,
+        optimizer: Optimizer::Momentum { momentum: 0.9 },
+        seed: Some(3),
+        save_path: Some("digits_resumed.darj".to_string()),
+        checkpoint: Some(CheckpointConfig {
+            path: "digits_resumed.ckpt".to_string(),
+            every_epochs: Some(2),
+            every_minutes: None,
+        }),
+        ..Default::default()
+    };
+
+    let initial = builder.build_cat().unwrap();
+
+    let mut uninterrupted = initial.clone();
+    uninterrupted
+        .train(&data, categories.clone(), &config)
+        .unwrap();
+    let uninterrupted_bin = fs::read("digits_resumed.darj").unwrap();
+
+    // Interrupted after the checkpoint at epoch 2, then resumed
+    let mut interrupted = initial;
+    interrupted
+        .train(
+            &data,
+            categories,
+            &TrainConfig {
+                epochs: 2,
+                ..config.clone()
+            },
+        )
+        .unwrap();
+    let mut checkpoint = Checkpoint::<CatNetwork>::read("digits_resumed.ckpt").unwrap();
+    assert_eq!(checkpoint.state.epoch, 2);
+    checkpoint.config.epochs = 4;
+    CatNetwork::resume_from(checkpoint, &data).unwrap();
+
+    assert_eq!(fs::read("digits_resumed.darj").unwrap(), uninterrupted_bin);
+    fs::remove_file("digits_resumed.ckpt").unwrap();
+}
+
 /// Read the file you want to and format it as Inputs
-pub fn xor_file<'a>() -> Box<[Series]> {
+pub fn xor_file() -> Box<[Series]> {
     let file = match fs::File::open("training_data/xor.txt") {
         Ok(file) => file,
         Err(error) => panic!("Panic opening the file: {:?}", error),
@@ -105,9 +453,243 @@ fn train_test_digits() {
 
     let mut model = train_network_digits(data.clone(), categories.clone(), learning_rate);
 
+    let report = model.evaluate(&data, &categories).unwrap();
+    println!("{}", report);
+    assert_eq!(
+        report
+            .classes
+            .iter()
+            .map(|class| class.support)
+            .sum::<usize>(),
+        data.len()
+    );
     model.test(&data, categories).unwrap();
 }
 
+#[test]
+fn classification_report_metrics() {
+    let categories: Box<[String]> = vec!["a".to_string(), "b".to_string()].into_boxed_slice();
+    let answers: Box<[String]> = vec!["a", "a", "a", "b"]
+        .into_iter()
+        .map(|answer| answer.to_string())
+        .collect();
+    let scores: Box<[Box<[f32]>]> = vec![
+        vec![1.0, 0.0].into_boxed_slice(),
+        vec![0.5, 0.5].into_boxed_slice(),
+        vec![0.2, 0.8].into_boxed_slice(),
+        vec![0.0, 1.0].into_boxed_slice(),
+    ]
+    .into_boxed_slice();
+
+    let report = ClassificationReport::new(&categories, &answers, &scores).unwrap();
+    assert_eq!(report.confusion_matrix[0].as_ref(), &[2, 1]);
+    assert_eq!(report.confusion_matrix[1].as_ref(), &[0, 1]);
+    assert_eq!(report.accuracy, 0.75);
+    assert_eq!(report.classes[0].precision, 1.0);
+    assert!((report.classes[0].recall - 2.0 / 3.0).abs() < 1e-6);
+    assert_eq!(report.classes[1].precision, 0.5);
+    assert!((report.macro_avg.f1 - (0.8 + 2.0 / 3.0) / 2.0).abs() < 1e-6);
+    assert!((report.weighted_avg.recall - 0.75).abs() < 1e-6);
+    let log_loss = -(1.0f32.ln() + 0.5f32.ln() + 0.2f32.ln() + 1.0f32.ln()) / 4.0;
+    assert!((report.log_loss - log_loss).abs() < 1e-6);
+
+    let json = report.to_json().unwrap();
+    assert_eq!(
+        serde_json::from_str::<ClassificationReport>(&json).unwrap(),
+        report
+    );
+    assert!(report.to_string().contains("weighted avg"));
+
+    let unknown: Box<[String]> = vec!["c".to_string(); 4].into_boxed_slice();
+    assert!(ClassificationReport::new(&categories, &unknown, &scores).is_err());
+
+    assert_eq!(macro_auc(&categories, &answers, &scores).unwrap(), 1.0);
+    let roc = RocCurve::one_vs_rest(&categories, &answers, &scores, "b").unwrap();
+    assert_eq!(roc.auc, 1.0);
+}
+
+#[test]
+fn roc_and_pr_curves() {
+    let labels = [true, false, true, false];
+    let scores = [0.9, 0.8, 0.7, 0.1];
+
+    let roc = RocCurve::new(&labels, &scores).unwrap();
+    assert_eq!(
+        roc.false_positive_rates.as_ref(),
+        &[0.0, 0.0, 0.5, 0.5, 1.0]
+    );
+    assert_eq!(roc.true_positive_rates.as_ref(), &[0.0, 0.5, 0.5, 1.0, 1.0]);
+    assert_eq!(roc.auc, 0.75);
+
+    let pr = PrCurve::new(&labels, &scores).unwrap();
+    assert_eq!(pr.recalls.as_ref(), &[0.0, 0.5, 0.5, 1.0, 1.0]);
+    assert!((pr.average_precision - (0.5 + 1.0 / 3.0)).abs() < 1e-6);
+
+    let csv = roc.to_csv();
+    assert_eq!(csv.lines().count(), 6);
+    assert_eq!(
+        csv.lines().next(),
+        Some("threshold,false_positive_rate,true_positive_rate")
+    );
+    pr.write_csv("pr_curve.csv").unwrap();
+    assert_eq!(fs::read_to_string("pr_curve.csv").unwrap(), pr.to_csv());
+    fs::remove_file("pr_curve.csv").unwrap();
+
+    assert!(RocCurve::new(&[true, true], &[0.1, 0.2]).is_err());
+}
+
+#[test]
+fn cross_validate_digits() {
+    let categories: Box<[String]> = (0..10).map(|n| n.to_string()).collect();
+    let data: Box<[Series]> = digits_file();
+
+    let folds = Folds::new(3).stratified();
+    let held_out = folds.split(&data, &mut Pcg64::seed_from_u64(7)).unwrap();
+    let mut indices: Vec<usize> = held_out
+        .iter()
+        .flat_map(|fold| fold.iter().copied())
+        .collect();
+    indices.sort_unstable();
+    assert_eq!(indices, (0..data.len()).collect::<Vec<usize>>());
+    for category in categories.iter() {
+        let counts: Vec<usize> = held_out
+            .iter()
+            .map(|fold| {
+                fold.iter()
+                    .filter(|i| data[**i].answer == *category)
+                    .count()
+            })
+            .collect();
+        assert!(counts.iter().max().unwrap() - counts.iter().min().unwrap() <= 1);
+    }
+    assert!(Folds::new(1)
+        .split(&data, &mut Pcg64::seed_from_u64(7))
+        .is_err());
+
+    let builder = NetworkBuilder::new(64)
+        .activation(ActivationFunction::Sigmoid)
+        .hidden(32)
+        .hidden(10);
+    let config = TrainConfig {
+        learning_rate: 0.05,
+        epochs: 5,
+        seed: Some(7),
+        ..Default::default()
+    };
+    let report = cross_validate(&builder, &config, &data, &categories, folds.parallel()).unwrap();
+    println!("{}", report);
+    assert_eq!(report.folds.len(), 3);
+    assert_eq!(
+        report
+            .folds
+            .iter()
+            .map(|fold| fold.test_size)
+            .sum::<usize>(),
+        data.len()
+    );
+    assert!(report.accuracy.mean > 0.5);
+}
+
+#[test]
+fn search_digits() {
+    let categories: Box<[String]> = (0..10).map(|n| n.to_string()).collect();
+    let data: Box<[Series]> = digits_file();
+    let (validation, train) = data.split_at(data.len() / 4);
+
+    let builder = NetworkBuilder::new(64)
+        .activation(ActivationFunction::Sigmoid)
+        .hidden(16)
+        .hidden(10);
+    let config = TrainConfig {
+        epochs: 8,
+        seed: Some(3),
+        ..Default::default()
+    };
+    let space = SearchSpace {
+        learning_rates: vec![0.01, 0.1],
+        hidden_widths: vec![8, 32],
+        hidden_layers: vec![1, 2],
+        ..Default::default()
+    };
+    let grid = space.grid(&builder, &config);
+    assert_eq!(grid.len(), 8);
+    assert_eq!(grid[7].builder.layers().len(), 3);
+    assert_eq!(grid[7].builder.layers()[1].width, 32);
+    assert_eq!(grid[7].builder.layers()[2].width, 10);
+
+    let search = Search {
+        space,
+        strategy: Strategy::SuccessiveHalving {
+            candidates: Some(4),
+            min_epochs: 2,
+            factor: 2,
+        },
+        evaluation: Evaluation::Holdout(validation.into()),
+        metric: Metric::Accuracy,
+    };
+    let leaderboard = search.run(&builder, &config, train, &categories).unwrap();
+    println!("{}", leaderboard);
+    assert_eq!(leaderboard.results.len(), 4);
+    let best = leaderboard.best().unwrap();
+    assert_eq!(best.epochs, 8);
+    assert!(leaderboard
+        .results
+        .iter()
+        .all(|result| result.epochs <= best.epochs));
+
+    let json = best.candidate.to_json().unwrap();
+    let candidate = Candidate::from_json(&json).unwrap();
+    assert_eq!(candidate, best.candidate);
+    assert_eq!(candidate.config.epochs, 8);
+    assert!(candidate.builder.build_cat().is_ok());
+}
+
+#[test]
+fn search_ranks_diverged_candidates_last() {
+    let categories: Box<[String]> = vec!["1".to_string(), "0".to_string()].into_boxed_slice();
+    let data: Box<[Series]> = xor_file();
+    let builder = NetworkBuilder::new(2)
+        .activation(ActivationFunction::Linear)
+        .hidden(4)
+        .hidden(2);
+    let config = TrainConfig {
+        epochs: 50,
+        seed: Some(5),
+        ..Default::default()
+    };
+
+    for strategy in [
+        Strategy::Grid,
+        Strategy::SuccessiveHalving {
+            candidates: None,
+            min_epochs: 10,
+            factor: 2,
+        },
+    ] {
+        let search = Search {
+            space: SearchSpace {
+                // The largest learning rate sends the weights to infinity
+                learning_rates: vec![f32::MAX, 0.01, 0.05],
+                ..Default::default()
+            },
+            strategy,
+            evaluation: Evaluation::Holdout(data.clone()),
+            metric: Metric::LogLoss,
+        };
+        let leaderboard = search.run(&builder, &config, &data, &categories).unwrap();
+        println!("{}", leaderboard);
+        assert_eq!(leaderboard.results.len(), 3);
+
+        let last = leaderboard.results.last().unwrap();
+        assert_eq!(last.candidate.config.learning_rate, f32::MAX);
+        assert!(last.score.is_nan());
+        assert!(last.error.is_some());
+        let best = leaderboard.best().unwrap();
+        assert!(best.score.is_finite());
+        assert!(best.error.is_none());
+    }
+}
+
 /// # Panics
 /// If the learn function returns an Err
 fn train_network_digits(
@@ -116,95 +698,1583 @@ fn train_network_digits(
     learning_rate: f32,
 ) -> CatNetwork {
     let mut net = CatNetwork::new(64, 128, 10, 2, Some(ActivationFunction::Sigmoid));
+    // The raw pixels are 0-16, which saturates the sigmoid units
+    net.set_scaler(MinMaxScaler::fit(&data).unwrap());
 
-    net.train(&data, categories, learning_rate, "digits", 99.0, true)
+    let config = TrainConfig {
+        learning_rate,
+        epochs: 100,
+        target_accuracy: Some(99.0),
+        save_path: Some("digits.darj".to_string()),
+        ..Default::default()
+    };
+    net.train(&data, categories, &config)
         .expect("Training Digits Network Failed");
     net
 }
 
-fn digits_file() -> Box<[Series]> {
-    let file = match fs::File::open("training_data/train-digits.txt") {
-        Ok(file) => file,
-        Err(error) => panic!("Panic opening the file: {:?}", error),
+#[test]
+fn unused_config_fields() {
+    let unsupported = |result: Result<_, DarjeelingError>| {
+        matches!(result, Err(DarjeelingError::UnsupportedConfig(_)))
     };
+    let rows: Box<[Box<[f32]>]> = vec![vec![0.5; 8].into(); 4].into();
 
-    let reader = BufReader::new(file);
-    let mut inputs: Vec<Series> = vec![];
+    let mut net = GenNetwork::new(2, 4, 8, 1, Some(ActivationFunction::Tanh));
+    let distinguisher = NetworkBuilder::new(8)
+        .activation(ActivationFunction::Tanh)
+        .hidden(4)
+        .hidden(1);
+    let seeded = TrainConfig {
+        seed: Some(1),
+        ..Default::default()
+    };
+    assert!(unsupported(
+        net.train(&rows, &TrainConfig::default(), &distinguisher, &seeded)
+            .map(|_| ())
+    ));
+    let stopping = TrainConfig {
+        early_stopping: Some(EarlyStopping {
+            patience: 2,
+            min_delta: 0.0,
+        }),
+        ..Default::default()
+    };
+    assert!(unsupported(
+        net.train(&rows, &stopping, &distinguisher, &TrainConfig::default())
+            .map(|_| ())
+    ));
+    let checkpointed = TrainConfig {
+        checkpoint: Some(CheckpointConfig {
+            path: "gen.ckpt".to_string(),
+            every_epochs: Some(1),
+            every_minutes: None,
+        }),
+        ..Default::default()
+    };
+    assert!(unsupported(
+        net.train(
+            &rows,
+            &checkpointed,
+            &distinguisher,
+            &TrainConfig::default()
+        )
+        .map(|_| ())
+    ));
+    assert!(fs::metadata("gen.ckpt").is_err());
+    let weighted = TrainConfig {
+        class_weights: Some(ClassWeights::Balanced),
+        ..Default::default()
+    };
+    assert!(unsupported(
+        net.train(&rows, &weighted, &distinguisher, &TrainConfig::default())
+            .map(|_| ())
+    ));
 
-    for l in reader.lines() {
-        let line = match l {
-            Ok(line) => line,
-            Err(error) => panic!("{:?}", error),
-        };
+    let labelled = [MultiLabelSeries::new(vec![0.0, 1.0], &["a"])];
+    let mut multi_label = MultiLabelNetwork::new(2, 2, 1, 1, Some(ActivationFunction::Tanh));
+    assert!(unsupported(
+        multi_label
+            .train(&labelled, vec!["a".to_string()].into(), &weighted)
+            .map(|_| ())
+    ));
+    let mut regression = RegNetwork::new(1, 4, 1, 1, Some(ActivationFunction::Tanh));
+    assert!(unsupported(
+        regression
+            .train(&[RegressionSeries::new(vec![0.5], vec![0.5])], &weighted)
+            .map(|_| ())
+    ));
+    let mut autoencoder = AutoEncoder::new(8, 4, 2, 1, Some(ActivationFunction::Tanh));
+    assert!(unsupported(autoencoder.train(&rows, &weighted).map(|_| ())));
+    let mut variational = VarAutoEncoder::new(8, 4, 2, 1, Some(ActivationFunction::Tanh));
+    assert!(unsupported(variational.train(&rows, &weighted).map(|_| ())));
+    let checkpointed = TrainConfig {
+        checkpoint: Some(CheckpointConfig {
+            path: "vae.ckpt".to_string(),
+            every_epochs: Some(1),
+            every_minutes: None,
+        }),
+        ..Default::default()
+    };
+    assert!(unsupported(
+        variational.train(&rows, &checkpointed).map(|_| ())
+    ));
+    assert!(fs::metadata("vae.ckpt").is_err());
+
+    let absolute = TrainConfig {
+        loss: Loss::Mae,
+        ..Default::default()
+    };
+    assert!(unsupported(
+        net.train(&rows, &absolute, &distinguisher, &TrainConfig::default())
+            .map(|_| ())
+    ));
+    let mut categorizer = CatNetwork::new(2, 2, 2, 1, Some(ActivationFunction::Sigmoid));
+    let categories: Box<[String]> = ["0".to_string(), "1".to_string()].into();
+    assert!(unsupported(
+        categorizer
+            .train(&xor_file(), categories, &absolute)
+            .map(|_| ())
+    ));
+    assert!(unsupported(
+        multi_label
+            .train(&labelled, vec!["a".to_string()].into(), &absolute)
+            .map(|_| ())
+    ));
+}
+
+#[test]
+fn read_csv() {
+    let text = "x, y ,label\n1,2,\"a, \"\"quoted\"\" label\"\n\n3,4.5,\"multi\nline\"\r\n-1,0,b\n";
+    let reader = CsvReader::new()
+        .headers(true)
+        .label(Column::Name("label".to_string()));
+    let data = reader.parse_series(text).unwrap();
+    assert_eq!(data.len(), 3);
+    assert_eq!(data[0].data.as_ref(), &[1.0, 2.0]);
+    assert_eq!(data[0].answer, "a, \"quoted\" label");
+    assert_eq!(data[1].data.as_ref(), &[3.0, 4.5]);
+    assert_eq!(data[1].answer, "multi\nline");
+    assert_eq!(data[2].answer, "b");
+
+    let rows = CsvReader::new()
+        .delimiter(';')
+        .parse_rows("1;2;3\n4;5;6")
+        .unwrap();
+    assert_eq!(rows[1].as_ref(), &[4.0, 5.0, 6.0]);
+    let rows = CsvReader::new()
+        .label(Column::Index(0))
+        .parse_rows("1,2,3\n4,5,6")
+        .unwrap();
+    assert_eq!(rows[1].as_ref(), &[5.0, 6.0]);
+
+    match CsvReader::new().parse_series("1,2,a\n\n1,x,b\n") {
+        Err(DarjeelingError::DatasetParseFailed(line, _)) => assert_eq!(line, 3),
+        other => panic!("Expected a parse error, got {:?}", other),
+    }
+    match CsvReader::new().parse_series("1,2,a\n1,b\n") {
+        Err(DarjeelingError::DatasetParseFailed(line, _)) => assert_eq!(line, 2),
+        other => panic!("Expected a parse error, got {:?}", other),
+    }
+    assert!(reader.parse_series("x,y,z\n1,2,3").is_err());
+    assert!(CsvReader::new()
+        .label(Column::Name("label".to_string()))
+        .parse_series("1,2,3")
+        .is_err());
+}
+
+#[test]
+fn dataset_round_trips() {
+    let data: Box<[Series]> = vec![
+        Series::new(vec![0.0, 1.5, 0.0], "3"),
+        Series::new(vec![2.0, 0.0, -4.25], "7"),
+    ]
+    .into_boxed_slice();
+
+    idx::write_series("round_trip_images.idx", "round_trip_labels.idx", &data).unwrap();
+    let read = idx::read_series("round_trip_images.idx", "round_trip_labels.idx").unwrap();
+    fs::remove_file("round_trip_images.idx").unwrap();
+    fs::remove_file("round_trip_labels.idx").unwrap();
+    assert_eq!(read, data);
+
+    libsvm::write_series("round_trip.svm", &data).unwrap();
+    let read = libsvm::read_series("round_trip.svm", Some(3)).unwrap();
+    fs::remove_file("round_trip.svm").unwrap();
+    assert_eq!(read, data);
+
+    let named: Box<[Series]> = data
+        .iter()
+        .map(|series| Series::new(series.data.clone(), format!("bee {}", series.answer)))
+        .collect();
+    npy::write_series("round_trip_x.npy", "round_trip_y.npy", &named).unwrap();
+    let read = npy::read_series("round_trip_x.npy", "round_trip_y.npy").unwrap();
+    fs::remove_file("round_trip_x.npy").unwrap();
+    fs::remove_file("round_trip_y.npy").unwrap();
+    assert_eq!(read, named);
+}
+
+#[test]
+fn read_foreign_datasets() {
+    // Two 2x2 unsigned byte images, as MNIST stores them
+    let images = [
+        0, 0, 0x08, 3, 0, 0, 0, 2, 0, 0, 0, 2, 0, 0, 0, 2, 0, 255, 1, 2, 3, 4, 5, 6,
+    ];
+    let images = idx::parse(&images).unwrap();
+    assert_eq!(images.shape.as_ref(), &[2, 2, 2]);
+    assert_eq!(images.rows()[0].as_ref(), &[0.0, 255.0, 1.0, 2.0]);
+    assert!(idx::parse(&[0, 0, 0x08, 1, 0, 0, 0, 3, 1]).is_err());
+    // Two images without any pixels
+    let empty = idx::parse(&[0, 0, 0x08, 2, 0, 0, 0, 2, 0, 0, 0, 0]).unwrap();
+    assert_eq!(empty.rows().len(), 2);
+    assert!(empty.rows().iter().all(|row| row.is_empty()));
+
+    let data = libsvm::parse_series("+1 1:0.5 3:2 # comment\n\n-1 2:1\n", None).unwrap();
+    assert_eq!(data[0].data.as_ref(), &[0.5, 0.0, 2.0]);
+    assert_eq!(data[1].answer, "-1");
+    match libsvm::parse_series("1 1:1\n0 0:1\n", None) {
+        Err(DarjeelingError::DatasetParseFailed(line, _)) => assert_eq!(line, 2),
+        other => panic!("Expected a parse error, got {:?}", other),
+    }
+
+    // A fortran ordered 2x3 array of little endian i16, [[1, 2, 3], [4, 5, 6]]
+    let mut npy = b"\x93NUMPY\x01\x00".to_vec();
+    let header = "{'descr': '<i2', 'fortran_order': True, 'shape': (2, 3), }\n";
+    npy.extend((header.len() as u16).to_le_bytes());
+    npy.extend(header.bytes());
+    [1i16, 4, 2, 5, 3, 6]
+        .iter()
+        .for_each(|value| npy.extend(value.to_le_bytes()));
+    let array = npy::parse(&npy).unwrap();
+    assert_eq!(array.shape.as_ref(), &[2, 3]);
+    assert_eq!(array.data.as_ref(), &[1.0, 2.0, 3.0, 4.0, 5.0, 6.0]);
+    assert!(npy::parse_labels(&npy).is_err());
+}
+
+#[test]
+fn dataframe_operations() {
+    let text = "name,legs,weight,flies\nbee,6,0.1,true\nant,6,0.003,false\ncat,4,4,false\n";
+    let mut frame = CsvReader::new().headers(true).parse_frame(text).unwrap();
+    assert_eq!(frame.col_labels(), &["name", "legs", "weight", "flies"]);
+    assert_eq!(
+        frame.column("name").unwrap(),
+        &DataColumn::Categorical(vec![
+            "bee".to_string(),
+            "ant".to_string(),
+            "cat".to_string()
+        ])
+    );
+    assert_eq!(
+        frame.column("legs").unwrap(),
+        &DataColumn::Integer(vec![6, 6, 4])
+    );
+    assert_eq!(frame.value("2", "weight").unwrap(), Value::Float(4.0));
+    assert!(frame.to_series("flies").is_err());
+
+    frame = frame.with_row_labels(vec!["bee", "ant", "cat"]).unwrap();
+    frame
+        .append_row(
+            "crow",
+            vec![
+                Value::Categorical("crow".to_string()),
+                Value::Integer(2),
+                Value::Float(0.5),
+                Value::Boolean(true),
+            ],
+        )
+        .unwrap();
+    assert!(frame.append_row("dog", vec![Value::Integer(4)]).is_err());
+    assert!(frame
+        .append_row(
+            "bee",
+            vec![
+                Value::Categorical("bee".to_string()),
+                Value::Integer(6),
+                Value::Float(0.1),
+                Value::Boolean(true),
+            ],
+        )
+        .is_err());
+
+    let flying = frame.filter(|row| row.get("flies") == Some(Value::Boolean(true)));
+    assert_eq!(flying.row_labels(), &["bee", "crow"]);
+
+    let mut numeric = frame.select(&["weight", "legs", "flies"]).unwrap();
+    let data = numeric.to_series("legs").unwrap();
+    assert_eq!(data[3].data.as_ref(), &[0.5, 1.0]);
+    assert_eq!(data[3].answer, "2");
+
+    numeric.drop_rows(&["ant", "cat"]).unwrap();
+    numeric.drop_columns(&["flies"]).unwrap();
+    assert!(numeric.drop_columns(&["flies"]).is_err());
+    let rows = numeric.to_rows().unwrap();
+    assert_eq!(rows.len(), 2);
+    assert_eq!(rows[1].as_ref(), &[0.5, 2.0]);
+
+    let other = DataFrame::from_columns(vec![
+        ("weight", DataColumn::Float(vec![30.0])),
+        ("legs", DataColumn::Integer(vec![4])),
+    ])
+    .unwrap()
+    .with_row_labels(vec!["dog"])
+    .unwrap();
+    numeric.append(&other).unwrap();
+    assert_eq!(numeric.value("dog", "weight").unwrap(), Value::Float(30.0));
+    assert!(numeric.append(&other).is_err());
+    assert!(numeric.to_string().contains("dog"));
+}
+
+#[test]
+fn feature_scalers() {
+    let data: Box<[Series]> = vec![
+        Series::new(vec![1.0, 5.0], "a".to_string()),
+        Series::new(vec![2.0, 5.0], "b".to_string()),
+        Series::new(vec![3.0, 5.0], "a".to_string()),
+        Series::new(vec![10.0, 5.0], "b".to_string()),
+    ]
+    .into_boxed_slice();
+
+    let standard = StandardScaler::fit(&data).unwrap();
+    assert_eq!(standard.means.as_ref(), &[4.0, 5.0]);
+    assert!((standard.stds[0] - 3.535_534).abs() < 1e-5);
+    // A constant feature is only centered
+    assert_eq!(standard.stds[1], 1.0);
+    let scaled = standard.transform(&data).unwrap();
+    assert_eq!(scaled[1].answer, "b");
+    let mean: f32 = scaled.iter().map(|series| series.data[0]).sum::<f32>() / 4.0;
+    assert!(mean.abs() < 1e-6);
+
+    let min_max = MinMaxScaler::fit(&data).unwrap();
+    assert_eq!(
+        min_max.transform_row(&[10.0, 5.0]).unwrap().as_ref(),
+        &[1.0, 0.0]
+    );
+    assert_eq!(
+        min_max.transform_row(&[1.0, 5.0]).unwrap().as_ref(),
+        &[0.0, 0.0]
+    );
+
+    let robust = RobustScaler::fit(&data).unwrap();
+    assert_eq!(robust.medians.as_ref(), &[2.5, 5.0]);
+    assert_eq!(robust.ranges[0], 4.75 - 1.75);
+
+    for scaler in [
+        Scaler::from(standard),
+        Scaler::from(min_max),
+        Scaler::from(robust),
+    ] {
+        let restored = scaler
+            .inverse_transform(&scaler.transform(&data).unwrap())
+            .unwrap();
+        restored
+            .iter()
+            .zip(data.iter())
+            .for_each(|(restored, series)| {
+                restored
+                    .data
+                    .iter()
+                    .zip(series.data.iter())
+                    .for_each(|(a, b)| assert!((a - b).abs() < 1e-5));
+            });
+        assert!(scaler.transform_row(&[1.0]).is_err());
+    }
+    assert!(StandardScaler::fit(&[]).is_err());
+
+    let categories: Box<[String]> = vec!["a".to_string(), "b".to_string()].into_boxed_slice();
+    let mut net = NetworkBuilder::new(2)
+        .activation(ActivationFunction::Sigmoid)
+        .hidden(4)
+        .hidden(2)
+        .build_cat()
+        .unwrap();
+    assert!(matches!(
+        net.predict(&[1.0, 5.0]),
+        Err(DarjeelingError::ModelMissingCategories)
+    ));
+    net.set_scaler(StandardScaler::fit(&data).unwrap());
+    let config = TrainConfig {
+        epochs: 5,
+        save_path: Some("scaled.darj".to_string()),
+        ..Default::default()
+    };
+    net.train(&data, categories, &config).unwrap();
+
+    let mut loaded = CatNetwork::read_model("scaled.darj".to_string()).unwrap();
+    fs::remove_file("scaled.darj").unwrap();
+    assert_eq!(loaded.pipeline(), net.pipeline());
+    assert_eq!(
+        loaded.predict(&[10.0, 5.0]).unwrap(),
+        net.predict(&[10.0, 5.0]).unwrap()
+    );
+    assert!(loaded.predict(&[10.0]).is_err());
+}
+
+#[test]
+fn categorical_encoders() {
+    let categories = |values: &[&str]| {
+        DataColumn::Categorical(values.iter().map(|value| value.to_string()).collect())
+    };
+    let frame = DataFrame::from_columns(vec![
+        ("colour", categories(&["red", "blue", "red", "green"])),
+        ("size", categories(&["s", "m", "l", "m"])),
+        ("city", categories(&["paris", "oslo", "lima", "oslo"])),
+        (
+            "bought",
+            DataColumn::Boolean(vec![true, false, true, false]),
+        ),
+    ])
+    .unwrap();
+
+    let encoder = FrameEncoder::fit(
+        &frame,
+        &[
+            ("colour", Encoding::OneHot),
+            ("size", Encoding::Ordinal),
+            ("city", Encoding::Target { smoothing: 0.0 }),
+        ],
+        Unknown::Error,
+        Some("bought"),
+    )
+    .unwrap();
+    let encoded = encoder.transform(&frame).unwrap();
+    assert_eq!(
+        encoded.col_labels(),
+        &[
+            "colour=blue",
+            "colour=green",
+            "colour=red",
+            "size",
+            "city",
+            "bought"
+        ]
+    );
+    let data = encoded.to_series("bought").unwrap();
+    assert_eq!(data[0].data.as_ref(), &[0.0, 0.0, 1.0, 2.0, 1.0]);
+    assert_eq!(data[1].data.as_ref(), &[1.0, 0.0, 0.0, 1.0, 0.0]);
+    assert_eq!(data[0].answer, "true");
 
-        let init_inputs: Vec<&str> = line.split(",").collect();
-        let mut float_inputs: Vec<f32> = vec![];
+    // The fitted vocabularies survive being saved
+    let saved: FrameEncoder = bincode::deserialize(&bincode::serialize(&encoder).unwrap()).unwrap();
+    assert_eq!(saved, encoder);
 
-        for i in 0..init_inputs.len() - 1 {
-            float_inputs.push(init_inputs[i].parse().unwrap());
+    let labels: Vec<String> = frame.col_labels().to_vec();
+    let unseen = vec![
+        Value::Categorical("purple".to_string()),
+        Value::Categorical("s".to_string()),
+        Value::Categorical("rome".to_string()),
+        Value::Boolean(true),
+    ];
+    assert!(matches!(
+        encoder.transform_record(&labels, &unseen),
+        Err(DarjeelingError::TransformFailed(_))
+    ));
+
+    let ignoring = FrameEncoder::fit(
+        &frame,
+        &[
+            ("colour", Encoding::OneHot),
+            ("size", Encoding::Ordinal),
+            ("city", Encoding::Target { smoothing: 2.0 }),
+        ],
+        Unknown::Ignore,
+        Some("bought"),
+    )
+    .unwrap();
+    assert_eq!(
+        ignoring
+            .transform_record(&labels, &unseen)
+            .unwrap()
+            .as_ref(),
+        &[
+            Value::Float(0.0),
+            Value::Float(0.0),
+            Value::Float(0.0),
+            Value::Float(2.0),
+            Value::Float(0.5),
+            Value::Boolean(true),
+        ]
+    );
+    // Oslo's targets are both zero, pulled halfway to the overall mean of 0.5
+    match &ignoring.columns[2].1 {
+        Encoder::Target(target) => assert_eq!(target.encode("oslo").unwrap(), 0.25),
+        encoder => panic!("Expected a target encoder, found {:?}", encoder),
+    }
+
+    let hashing = HashingEncoder::new(4).unwrap();
+    assert_eq!(hashing.encode("paris"), hashing.encode("paris"));
+    assert_eq!(hashing.encode("rome").iter().sum::<f32>(), 1.0);
+    assert!(HashingEncoder::new(0).is_err());
+
+    assert!(matches!(
+        FrameEncoder::fit(
+            &frame,
+            &[("bought", Encoding::OneHot)],
+            Unknown::Error,
+            None
+        ),
+        Err(DarjeelingError::ColumnMismatch(_))
+    ));
+    assert!(FrameEncoder::fit(
+        &frame,
+        &[("city", Encoding::Target { smoothing: 1.0 })],
+        Unknown::Error,
+        None
+    )
+    .is_err());
+}
+
+#[test]
+fn missing_values() {
+    let text = "a,b,c,d,label\n1,2.5,true,x,yes\n,NA,false,,no\n3,?,,y,yes\n";
+    // A question mark isn't missing by default
+    assert!(matches!(
+        CsvReader::new().parse_series("1,NA,yes\n,?,no"),
+        Err(DarjeelingError::DatasetParseFailed(2, _))
+    ));
+    let reader = CsvReader::new().headers(true).missing(&["NA", "?"]);
+    let frame = reader.parse_frame(text).unwrap();
+    // Missing values turn integer and boolean columns into floats
+    assert_eq!(frame.column("a").unwrap().missing(), 1);
+    assert!(frame.value("1", "a").unwrap().is_missing());
+    assert_eq!(frame.value("2", "a").unwrap(), Value::Float(3.0));
+    assert_eq!(frame.column("b").unwrap().missing(), 2);
+    assert_eq!(frame.value("0", "c").unwrap(), Value::Float(1.0));
+    assert_eq!(frame.column("d").unwrap().missing(), 1);
+    assert_eq!(
+        frame.value("1", "d").unwrap(),
+        Value::Categorical(String::new())
+    );
+    assert_eq!(frame.column("label").unwrap().missing(), 0);
+
+    let data = CsvReader::new()
+        .missing(&["NA", "?"])
+        .parse_series("1,2,yes\nNA,4,no\n5,?,yes\n7,8,no\n")
+        .unwrap();
+    assert!(data[1].data[0].is_nan() && data[2].data[1].is_nan());
+
+    let mean = Imputer::fit(&data, Imputation::Mean, false).unwrap();
+    assert_eq!(mean.fills.as_ref(), &[13.0 / 3.0, 14.0 / 3.0]);
+    let median = Imputer::fit(&data, Imputation::Median, true).unwrap();
+    assert_eq!(median.fills.as_ref(), &[5.0, 4.0]);
+    assert_eq!(median.indicators.as_deref(), Some(&[0, 1][..]));
+    let imputed = median.transform(&data).unwrap();
+    assert_eq!(imputed[1].data.as_ref(), &[5.0, 4.0, 1.0, 0.0]);
+    assert_eq!(imputed[3].data.as_ref(), &[7.0, 8.0, 0.0, 0.0]);
+    let frequent = Imputer::fit_rows(
+        &[&[1.0, 2.0], &[3.0, 2.0], &[3.0, f32::NAN], &[1.0, 9.0]],
+        Imputation::MostFrequent,
+        false,
+    )
+    .unwrap();
+    assert_eq!(frequent.fills.as_ref(), &[1.0, 2.0]);
+    let constant = Imputer::fit(&data, Imputation::Constant(-1.0), false).unwrap();
+    assert_eq!(
+        constant.transform_row(&[f32::NAN, 0.0]).unwrap().as_ref(),
+        &[-1.0, 0.0]
+    );
+    assert!(Imputer::fit_rows(&[&[f32::NAN], &[f32::NAN]], Imputation::Mean, false).is_err());
+    // Scalers are fitted after missing values are imputed
+    let nan = |result: Result<Scaler, DarjeelingError>| {
+        matches!(result, Err(DarjeelingError::NanEncountered(_)))
+    };
+    assert!(nan(StandardScaler::fit(&data).map(Scaler::from)));
+    assert!(nan(MinMaxScaler::fit(&data).map(Scaler::from)));
+    assert!(nan(RobustScaler::fit(&data).map(Scaler::from)));
+    assert!(RobustScaler::fit(&imputed).is_ok());
+
+    let categories: Box<[String]> = vec!["yes".to_string(), "no".to_string()].into_boxed_slice();
+    let mut net = CatNetwork::new(2, 2, 2, 1, Some(ActivationFunction::Sigmoid));
+    let config = TrainConfig {
+        epochs: 1,
+        ..Default::default()
+    };
+    assert!(matches!(
+        net.train(&data, categories.clone(), &config),
+        Err(DarjeelingError::NanEncountered(_))
+    ));
+    let mut net = CatNetwork::new(4, 2, 2, 1, Some(ActivationFunction::Sigmoid));
+    net.train(&imputed, categories, &config).unwrap();
+}
+
+#[test]
+fn preprocessing_pipeline() {
+    let text = "colour,size,weight,animal\n\
+        red,1,10,cat\n\
+        blue,,20,dog\n\
+        red,3,NA,cat\n\
+        green,4,40,dog\n\
+        blue,5,50,dog\n";
+    let frame = CsvReader::new().headers(true).parse_frame(text).unwrap();
+    let categories: Box<[String]> = vec!["cat".to_string(), "dog".to_string()].into_boxed_slice();
+
+    let mut net = NetworkBuilder::new(7)
+        .activation(ActivationFunction::Sigmoid)
+        .hidden(4)
+        .hidden(2)
+        .build_cat()
+        .unwrap();
+    net.set_pipeline(
+        Pipeline::new()
+            .encode("colour", Encoding::OneHot)
+            .unknown(Unknown::Ignore)
+            .impute(Imputation::Mean, true)
+            .scale(Scaling::MinMax),
+    );
+    let config = TrainConfig {
+        epochs: 5,
+        save_path: Some("pipeline.darj".to_string()),
+        ..Default::default()
+    };
+    net.train_frame(&frame, "animal", categories.clone(), &config)
+        .unwrap();
+
+    let pipeline = net.pipeline().unwrap();
+    assert!(pipeline.is_fitted());
+    // Three colours, two numbers and their two missing indicators, scaled to [0, 1]
+    let record = vec![
+        Value::Categorical("red".to_string()),
+        Value::Float(f32::NAN),
+        Value::Float(30.0),
+    ];
+    let features = pipeline.transform_record(&record).unwrap();
+    assert_eq!(features.len(), 7);
+    assert_eq!(&features[..3], &[0.0, 0.0, 1.0]);
+    assert_eq!(&features[3..], &[0.5625, 0.5, 1.0, 0.0]);
+    match pipeline.transformers().unwrap() {
+        [Transformer::Imputer(imputer), Transformer::Scaler(_)] => {
+            assert_eq!(imputer.fills[3], 3.25)
         }
-        let input = Series::new(
-            float_inputs,
-            String::from(init_inputs[init_inputs.len() - 1]),
-        );
-        dbg_println!(
-            "Correct Answer: {:?}",
-            init_inputs[init_inputs.len() - 1].to_string()
+        transformers => panic!("Expected an imputer and a scaler, found {:?}", transformers),
+    }
+    let mut loaded = CatNetwork::read_model("pipeline.darj".to_string()).unwrap();
+    fs::remove_file("pipeline.darj").unwrap();
+    assert_eq!(loaded.pipeline(), net.pipeline());
+    let purple = vec![
+        Value::Categorical("purple".to_string()),
+        Value::Float(2.0),
+        Value::Float(f32::NAN),
+    ];
+    assert_eq!(
+        loaded.predict_record(&purple).unwrap(),
+        net.predict_record(&purple).unwrap()
+    );
+    // Columns can come in any order, and the label is ignored
+    let reordered = frame
+        .select(&["weight", "animal", "size", "colour"])
+        .unwrap();
+    let answers = loaded.predict_frame(&reordered).unwrap();
+    assert_eq!(answers, net.predict_frame(&frame).unwrap());
+    assert!(answers.iter().all(|answer| categories.contains(answer)));
+    let data = loaded
+        .pipeline()
+        .unwrap()
+        .encode_frame(&frame, Some("animal"))
+        .unwrap();
+    assert_eq!(loaded.test(&data, categories.clone()).unwrap(), answers);
+
+    // A pipeline that encodes columns can't be fitted without a dataframe
+    let mut net = CatNetwork::new(3, 2, 2, 1, Some(ActivationFunction::Sigmoid));
+    net.set_pipeline(Pipeline::new().encode("colour", Encoding::OneHot));
+    assert!(matches!(
+        net.train(&data, categories, &TrainConfig::default()),
+        Err(DarjeelingError::TransformFailed(_))
+    ));
+
+    let rows: Box<[Box<[f32]>]> = vec![
+        vec![0.0, 10.0].into_boxed_slice(),
+        vec![4.0, 30.0].into_boxed_slice(),
+    ]
+    .into_boxed_slice();
+    let mut pipeline = Pipeline::new().scale(Scaling::Standard);
+    pipeline
+        .fit_rows(&rows.iter().map(|row| row.as_ref()).collect::<Vec<&[f32]>>())
+        .unwrap();
+    let scaled = pipeline.transform_row(&rows[1]).unwrap();
+    assert_eq!(scaled.as_ref(), &[1.0, 1.0]);
+    assert_eq!(pipeline.inverse_transform_row(&scaled).unwrap(), rows[1]);
+    assert!(Pipeline::new().transform_row(&rows[0]).is_err());
+}
+
+#[test]
+fn input_width_checked() {
+    let invalid = |result: Result<String, DarjeelingError>| {
+        matches!(result, Err(DarjeelingError::InvalidLayerSpec(_)))
+    };
+    let categories: Box<[String]> = vec!["1".to_string(), "0".to_string()].into_boxed_slice();
+    let mut net = CatNetwork::new(2, 2, 2, 1, Some(ActivationFunction::Sigmoid));
+    let config = TrainConfig {
+        epochs: 1,
+        ..Default::default()
+    };
+    let short = [Series::new(vec![1.0], "1")];
+    assert!(matches!(
+        net.train(&short, categories.clone(), &config),
+        Err(DarjeelingError::InvalidLayerSpec(_))
+    ));
+
+    net.train(&xor_file(), categories, &config).unwrap();
+    assert!(invalid(net.predict(&[1.0])));
+    assert!(invalid(net.predict(&[1.0, 0.0, 1.0])));
+    assert!(net.predict(&[1.0, 0.0]).is_ok());
+    assert!(net.scores(&short).is_err());
+}
+
+#[test]
+fn split_and_shuffle() {
+    let mut rng = Pcg64::seed_from_u64(7);
+    let mut numbers: Vec<usize> = (0..100).collect();
+    shuffle(&mut numbers, &mut rng);
+    assert_ne!(numbers, (0..100).collect::<Vec<usize>>());
+    let mut sorted = numbers.clone();
+    sorted.sort_unstable();
+    assert_eq!(sorted, (0..100).collect::<Vec<usize>>());
+    let mut again: Vec<usize> = (0..100).collect();
+    shuffle(&mut again, &mut Pcg64::seed_from_u64(7));
+    assert_eq!(again, numbers);
+    assert_eq!(shuffled(&[1], &mut rng), vec![&1]);
+
+    // 90 of one answer and 10 of another
+    let data: Box<[Series]> = (0..100)
+        .map(|i| Series::new(vec![i as f32], if i % 10 == 0 { "rare" } else { "common" }))
+        .collect();
+    let (train, test) = train_test_split(&data, 0.25, 3).unwrap();
+    assert_eq!((train.len(), test.len()), (75, 25));
+    let mut all: Vec<f32> = train
+        .iter()
+        .chain(test.iter())
+        .map(|series| series.data[0])
+        .collect();
+    all.sort_by(f32::total_cmp);
+    assert_eq!(all, (0..100).map(|i| i as f32).collect::<Vec<f32>>());
+    assert_eq!(train_test_split(&data, 0.25, 3).unwrap(), (train, test));
+
+    let (train, test) = stratified_split(&data, 0.2, 3).unwrap();
+    let rare = |set: &[Series]| set.iter().filter(|series| series.answer == "rare").count();
+    assert_eq!((train.len(), test.len()), (80, 20));
+    assert_eq!((rare(&train), rare(&test)), (8, 2));
+
+    assert!(matches!(
+        train_test_split(&data, 0.0, 3),
+        Err(DarjeelingError::InvalidSplit(_))
+    ));
+    assert!(train_test_split(&data, 1.0, 3).is_err());
+    assert!(stratified_split(&data[..2], 0.4, 3).is_err());
+}
+
+#[test]
+fn class_imbalance() {
+    let categories: Box<[String]> = vec!["common".to_string(), "rare".to_string()].into();
+    let mut data: Vec<Series> = (0..18)
+        .map(|i| Series::new(vec![0.0, (i % 3) as f32 / 10.0], "common"))
+        .collect();
+    data.push(Series::new(vec![1.0, 0.0], "rare"));
+    data.push(Series::new(vec![1.0, 1.0], "rare"));
+
+    assert_eq!(
+        ClassWeights::Balanced
+            .weights(&categories, &data)
+            .unwrap()
+            .as_ref(),
+        &[20.0 / 36.0, 5.0]
+    );
+    let custom = ClassWeights::Custom(vec![("rare".to_string(), 3.0)]);
+    assert_eq!(
+        custom.weights(&categories, &data).unwrap().as_ref(),
+        &[1.0, 3.0]
+    );
+    assert!(matches!(
+        ClassWeights::Custom(vec![("missing".to_string(), 3.0)]).weights(&categories, &data),
+        Err(DarjeelingError::CategoryDoesNotExist(_))
+    ));
+
+    let count =
+        |set: &[Series], answer: &str| set.iter().filter(|series| series.answer == answer).count();
+    let over = oversample(&data, 5);
+    assert_eq!((count(&over, "common"), count(&over, "rare")), (18, 18));
+    assert_eq!(oversample(&data, 5), over);
+    let under = undersample(&data, 5);
+    assert_eq!((count(&under, "common"), count(&under, "rare")), (2, 2));
+
+    // The only two rare inputs are neighbours, so every synthetic input lies between them
+    let synthetic = smote(&data, 5, 5).unwrap();
+    assert_eq!(
+        (count(&synthetic, "common"), count(&synthetic, "rare")),
+        (18, 18)
+    );
+    assert!(synthetic
+        .iter()
+        .filter(|series| series.answer == "rare")
+        .all(|series| series.data[0] == 1.0 && (0.0..=1.0).contains(&series.data[1])));
+    assert!(matches!(
+        smote(&data, 0, 5),
+        Err(DarjeelingError::InvalidSampling(_))
+    ));
+    assert!(smote(&data[16..19], 5, 5).is_err());
+
+    let config = TrainConfig {
+        learning_rate: 0.5,
+        epochs: 20,
+        seed: Some(5),
+        class_weights: Some(ClassWeights::Balanced),
+        ..Default::default()
+    };
+    let mut net = CatNetwork::new(2, 4, 2, 1, Some(ActivationFunction::Sigmoid));
+    net.train(&data, categories.clone(), &config).unwrap();
+    assert_eq!(net.test(&data, categories).unwrap().len(), data.len());
+}
+
+#[test]
+fn multi_label() {
+    let labels: Box<[String]> = vec!["left".to_string(), "top".to_string()].into();
+    let to_labels =
+        |names: &[&str]| -> Box<[String]> { names.iter().map(|name| name.to_string()).collect() };
+    let answers = [
+        to_labels(&["left", "top"]),
+        to_labels(&["left"]),
+        to_labels(&[]),
+        to_labels(&["top"]),
+    ];
+    let predictions = [
+        to_labels(&["left", "top"]),
+        to_labels(&["left", "top"]),
+        to_labels(&[]),
+        to_labels(&[]),
+    ];
+    let report = MultiLabelReport::new(&labels, &answers, &predictions).unwrap();
+    assert_eq!(report.hamming_loss, 0.25);
+    assert_eq!(report.subset_accuracy, 0.5);
+    assert_eq!(report.micro_avg.precision, 0.75);
+    assert_eq!(report.micro_avg.recall, 0.75);
+    // left is perfect, top has a precision and recall of a half
+    assert_eq!(report.classes[0].f1, 1.0);
+    assert_eq!(report.macro_avg.f1, 0.75);
+    assert!(matches!(
+        MultiLabelReport::new(&labels, &[to_labels(&["right"])], &[to_labels(&[])]),
+        Err(DarjeelingError::CategoryDoesNotExist(_))
+    ));
+
+    let mut rng = Pcg64::seed_from_u64(11);
+    let data: Box<[MultiLabelSeries]> = (0..200)
+        .map(|_| {
+            let (x, y): (f32, f32) = (rng.gen(), rng.gen());
+            let mut answers = vec![];
+            if x < 0.5 {
+                answers.push("left");
+            }
+            if y > 0.5 {
+                answers.push("top");
+            }
+            MultiLabelSeries::new(vec![x, y], &answers)
+        })
+        .collect();
+    let config = TrainConfig {
+        learning_rate: 0.5,
+        epochs: 60,
+        seed: Some(11),
+        save_path: Some("multi_label.darj".to_string()),
+        ..Default::default()
+    };
+    let mut net = MultiLabelNetwork::new(2, 8, 2, 1, Some(ActivationFunction::Tanh));
+    let (model_name, subset_accuracy, loss) = net.train(&data, labels.clone(), &config).unwrap();
+    assert!(
+        subset_accuracy > 80.0,
+        "subset accuracy {}",
+        subset_accuracy
+    );
+    assert!(loss.is_finite());
+
+    let mut loaded = MultiLabelNetwork::read_model(model_name.unwrap()).unwrap();
+    assert_eq!(loaded.labels(), Some(labels.as_ref()));
+    assert_eq!(
+        loaded.predict(&[0.1, 0.9]).unwrap(),
+        to_labels(&["left", "top"])
+    );
+    assert_eq!(loaded.predict(&[0.9, 0.1]).unwrap(), to_labels(&[]));
+    let report = loaded.evaluate(&data).unwrap();
+    assert!(report.hamming_loss < 0.1, "{}", report);
+
+    let thresholds = loaded.fit_thresholds(&data).unwrap();
+    assert!(thresholds
+        .iter()
+        .all(|threshold| (0.0..1.0).contains(threshold)));
+    assert!(loaded.evaluate(&data).unwrap().micro_avg.f1 >= report.micro_avg.f1);
+    assert!(matches!(
+        loaded.set_thresholds(&[0.5]),
+        Err(DarjeelingError::InvalidThresholds(_))
+    ));
+    assert!(matches!(
+        net.train(&data, to_labels(&["left"]), &config),
+        Err(DarjeelingError::InvalidLayerSpec(_))
+    ));
+    assert!(matches!(
+        loaded.predict(&[0.1]),
+        Err(DarjeelingError::InvalidLayerSpec(_))
+    ));
+
+    // Interrupted after the checkpoint at epoch 2, then resumed
+    let checkpointed = TrainConfig {
+        epochs: 2,
+        save_path: None,
+        checkpoint: Some(CheckpointConfig {
+            path: "multi_label.ckpt".to_string(),
+            every_epochs: Some(2),
+            every_minutes: None,
+        }),
+        ..config.clone()
+    };
+    MultiLabelNetwork::new(2, 8, 2, 1, Some(ActivationFunction::Tanh))
+        .train(&data, labels.clone(), &checkpointed)
+        .unwrap();
+    let mut checkpoint = Checkpoint::<MultiLabelNetwork>::read("multi_label.ckpt").unwrap();
+    assert_eq!(checkpoint.state.epoch, 2);
+    assert_eq!(checkpoint.categories, labels);
+    checkpoint.config.epochs = 4;
+    let (resumed, _, _, loss) = MultiLabelNetwork::resume_from(checkpoint, &data).unwrap();
+    assert_eq!(resumed.labels(), Some(labels.as_ref()));
+    assert!(loss.is_finite());
+    fs::remove_file("multi_label.ckpt").unwrap();
+
+    // An answer node that's saturated and wrong still learns
+    let builder = NetworkBuilder::new(1)
+        .activation(ActivationFunction::Tanh)
+        .hidden(2)
+        .hidden(1);
+    let (mut node_array, layers) = builder.build_parts().unwrap();
+    node_array[2][0].b_weight = 30.0;
+    let mut saturated =
+        MultiLabelNetwork::from_parts(node_array, layers, Some(ActivationFunction::Tanh));
+    let data = [MultiLabelSeries::new(vec![1.0], &[] as &[&str])];
+    assert_eq!(saturated.scores(&data).unwrap()[0][0], 1.0);
+    let config = TrainConfig {
+        learning_rate: 0.5,
+        epochs: 100,
+        ..Default::default()
+    };
+    saturated.train(&data, to_labels(&["a"]), &config).unwrap();
+    let score = saturated.scores(&data).unwrap()[0][0];
+    assert!(score < 0.5, "the score is still {}", score);
+}
+
+#[test]
+fn regression() {
+    let targets: Vec<Box<[f32]>> = vec![vec![1.0, 0.0].into(), vec![3.0, 0.0].into()];
+    let predictions: Vec<Box<[f32]>> = vec![vec![1.0, 1.0].into(), vec![3.0, -1.0].into()];
+    let report = RegressionReport::new(&targets, &predictions).unwrap();
+    assert_eq!(report.targets[0].r2, 1.0);
+    assert_eq!(report.targets[1].r2, 0.0);
+    assert_eq!((report.r2, report.mae), (0.5, 0.5));
+    assert_eq!(report.rmse, 0.5f32.sqrt());
+    assert!(RegressionReport::new(&targets, &predictions[..1]).is_err());
+
+    assert_eq!(Loss::Mae.loss(1.0, 3.0), 2.0);
+    assert_eq!(Loss::Huber { delta: 1.0 }.loss(0.5, 0.0), 0.125);
+    assert_eq!(Loss::Huber { delta: 1.0 }.loss(3.0, 0.0), 2.5);
+    assert_eq!(Loss::Huber { delta: 1.0 }.derivative(3.0, 0.0), 1.0);
+
+    let mut rng = Pcg64::seed_from_u64(13);
+    let data: Box<[RegressionSeries]> = (0..200)
+        .map(|_| {
+            let (x, y): (f32, f32) = (rng.gen(), rng.gen());
+            RegressionSeries::new(vec![x, y], vec![0.6 * x - 0.4 * y + 0.1])
+        })
+        .collect();
+    for loss in [Loss::Mse, Loss::Mae, Loss::Huber { delta: 0.1 }] {
+        let config = TrainConfig {
+            learning_rate: 0.05,
+            epochs: 100,
+            // The absolute error's gradient never shrinks, so it needs a decaying learning rate to settle
+            schedule: Schedule::Exponential { decay: 0.97 },
+            seed: Some(13),
+            loss,
+            save_path: Some("regression.darj".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(
+            TrainConfig::from_toml(&config.to_toml().unwrap()).unwrap(),
+            config
         );
-        inputs.push(input);
+        let mut net = RegNetwork::new(2, 8, 1, 1, Some(ActivationFunction::Tanh));
+        let (model_name, r2, _) = net.train(&data, &config).unwrap();
+        assert!(r2 > 0.9, "{:?} only reached an R2 of {}", loss, r2);
+
+        let mut loaded = RegNetwork::read_model(model_name.unwrap()).unwrap();
+        let report = loaded.evaluate(&data).unwrap();
+        assert!(report.r2 > 0.9 && report.mae < 0.1, "{}", report);
+        let prediction = loaded.predict(&[1.0, 0.0]).unwrap();
+        assert!((prediction[0] - 0.7).abs() < 0.1, "{:?}", prediction);
     }
 
-    inputs.into_boxed_slice()
+    let mut net = RegNetwork::new(2, 8, 2, 1, Some(ActivationFunction::Tanh));
+    assert!(matches!(
+        net.train(&data, &TrainConfig::default()),
+        Err(DarjeelingError::InvalidLayerSpec(_))
+    ));
+}
+
+#[test]
+fn resume_regression_from_checkpoint() {
+    let mut rng = Pcg64::seed_from_u64(29);
+    let data: Box<[RegressionSeries]> = (0..100)
+        .map(|_| {
+            let x: f32 = rng.gen();
+            RegressionSeries::new(vec![x], vec![0.5 * x])
+        })
+        .collect();
+    let config = TrainConfig {
+        learning_rate: 0.05,
+        epochs: 6,
+        batch_size: 4,
+        optimizer: Optimizer::adam(),
+        seed: Some(29),
+        save_path: Some("regression_resumed.darj".to_string()),
+        checkpoint: Some(CheckpointConfig {
+            path: "regression_resumed.ckpt".to_string(),
+            every_epochs: Some(3),
+            every_minutes: None,
+        }),
+        ..Default::default()
+    };
+    let initial = NetworkBuilder::new(1)
+        .activation(ActivationFunction::Tanh)
+        .layer(LayerSpec::new(8).dropout(0.1))
+        .hidden(1)
+        .build_reg()
+        .unwrap();
+
+    initial.clone().train(&data, &config).unwrap();
+    let uninterrupted_bin = fs::read("regression_resumed.darj").unwrap();
+
+    // Interrupted after the checkpoint at epoch 3, then resumed
+    let interrupted_config = TrainConfig {
+        epochs: 3,
+        ..config.clone()
+    };
+    initial.clone().train(&data, &interrupted_config).unwrap();
+    let mut checkpoint = Checkpoint::<RegNetwork>::read("regression_resumed.ckpt").unwrap();
+    assert_eq!(checkpoint.state.epoch, 3);
+    checkpoint.config.epochs = 6;
+    RegNetwork::resume_from(checkpoint, &data).unwrap();
+
+    assert_eq!(
+        fs::read("regression_resumed.darj").unwrap(),
+        uninterrupted_bin
+    );
+    fs::remove_file("regression_resumed.ckpt").unwrap();
+}
+
+fn digits_file() -> Box<[Series]> {
+    CsvReader::new()
+        .read_series("training_data/train-digits.txt")
+        .expect("Failed to read the digits file")
 }
 
 #[test]
 fn train_test_gen() {
     let model_name = train_gen();
-    let data = gen_data_file();
     let mut model = GenNetwork::read_model(model_name).unwrap();
-    let output = model.test(&data).unwrap();
+    assert_eq!(model.config().map(|config| config.epochs), Some(5));
+
+    let output = model.sample(10, &mut Pcg64::seed_from_u64(17)).unwrap();
+    // Sigmoid outputs are mapped back to the range of the pixels and labels
+    assert!(output
+        .iter()
+        .all(|row| row.len() == 65 && row.iter().all(|value| (0.0..=16.0).contains(value))));
     println!("{:?}", output);
 }
 
 fn train_gen() -> String {
-    let inputs = gen_data_file();
-    let mut net = GenNetwork::new(8, 8, 8, 1, Some(ActivationFunction::Sigmoid));
-    net.train(
-        &inputs,
-        1.0,
-        "dummy_gen",
-        100,
-        0.5,
-        8,
-        1,
-        ActivationFunction::Sigmoid,
-        99.0,
+    let inputs: Box<[Box<[f32]>]> = gen_data_file()[..500].into();
+    let mut net = GenNetwork::new(8, 32, 65, 1, Some(ActivationFunction::Sigmoid));
+    net.set_pipeline(Pipeline::new().scale(Scaling::MinMax));
+    let distinguisher = NetworkBuilder::new(65)
+        .activation(ActivationFunction::Sigmoid)
+        .hidden(16)
+        .hidden(1);
+    let config = TrainConfig {
+        learning_rate: 0.1,
+        epochs: 5,
+        batch_size: 16,
+        seed: Some(17),
+        save_path: Some("dummy_gen.darj".to_string()),
+        ..Default::default()
+    };
+    let distinguishing_config = TrainConfig {
+        learning_rate: 0.1,
+        epochs: 1,
+        ..Default::default()
+    };
+    net.train(&inputs, &config, &distinguisher, &distinguishing_config)
+        .unwrap()
+        .expect("The config has a save path")
+}
+
+#[test]
+fn adversarial_training() {
+    // Real values are all close to 0.8, while the untrained generator's outputs are spread around zero
+    let mut rng = Pcg64::seed_from_u64(19);
+    let data: Box<[Box<[f32]>]> = (0..256)
+        .map(|_| vec![rng.gen_range(0.75..0.85)].into_boxed_slice())
+        .collect();
+    let mut net = NetworkBuilder::new(2)
+        .activation(ActivationFunction::Tanh)
+        .hidden(8)
+        .layer(LayerSpec::new(1).activation(ActivationFunction::Linear))
+        .build_gen()
+        .unwrap();
+    let distinguisher = NetworkBuilder::new(1)
+        .activation(ActivationFunction::Tanh)
+        .hidden(8)
+        .hidden(1);
+    let config = TrainConfig {
+        learning_rate: 0.01,
+        epochs: 30,
+        batch_size: 8,
+        optimizer: Optimizer::adam(),
+        seed: Some(19),
+        ..Default::default()
+    };
+    let distinguishing_config = TrainConfig {
+        learning_rate: 0.01,
+        epochs: 1,
+        optimizer: Optimizer::adam(),
+        ..Default::default()
+    };
+    net.train(&data, &config, &distinguisher, &distinguishing_config)
+        .unwrap();
+
+    let generated = net.sample(200, &mut rng).unwrap();
+    let mean = generated.iter().map(|row| row[0]).sum::<f32>() / generated.len() as f32;
+    assert!((mean - 0.8).abs() < 0.1, "generated mean {}", mean);
+
+    assert_eq!(
+        net.sample(5, &mut Pcg64::seed_from_u64(1)).unwrap(),
+        net.sample(5, &mut Pcg64::seed_from_u64(1)).unwrap()
+    );
+    let (a, b) = ([-1.0, -1.0], [1.0, 1.0]);
+    let line = net.interpolate(&a, &b, 5).unwrap();
+    assert_eq!(line.len(), 5);
+    assert_eq!(line[0], net.test(&[a.into()]).unwrap()[0]);
+    assert_eq!(line[4], net.test(&[b.into()]).unwrap()[0]);
+    assert_eq!(line[2], net.test(&[[0.0, 0.0].into()]).unwrap()[0]);
+    assert!(matches!(
+        net.interpolate(&a, &b, 1),
+        Err(DarjeelingError::InvalidLatent(_))
+    ));
+    assert!(net.test(&[[0.0].into()]).is_err());
+
+    assert!(net
+        .set_latent(Latent::Uniform {
+            low: 1.0,
+            high: 1.0
+        })
+        .is_err());
+    let normal = Latent::Normal {
+        mean: 2.0,
+        std_dev: 0.5,
+    };
+    net.set_latent(normal).unwrap();
+    assert_eq!(net.latent(), normal);
+    let draws = normal.sample(10_000, &mut rng).unwrap();
+    let mean = draws.iter().sum::<f32>() / draws.len() as f32;
+    let std_dev =
+        (draws.iter().map(|draw| (draw - mean).powi(2)).sum::<f32>() / draws.len() as f32).sqrt();
+    assert!((mean - 2.0).abs() < 0.05 && (std_dev - 0.5).abs() < 0.05);
+
+    let wrong = NetworkBuilder::new(1)
+        .activation(ActivationFunction::Tanh)
+        .hidden(2);
+    assert!(matches!(
+        net.train(&data, &config, &wrong, &distinguishing_config),
+        Err(DarjeelingError::InvalidLayerSpec(_))
+    ));
+}
+
+#[test]
+fn generation_metrics() {
+    let mut rng = Pcg64::seed_from_u64(37);
+    let mut normal = |mean: f32, n: usize| -> Box<[Box<[f32]>]> {
+        (0..n)
+            .map(|_| {
+                let row: Box<[f32]> = (0..3)
+                    .map(|_| mean + rng.gen_range(-1.0..1.0) + rng.gen_range(-1.0..1.0))
+                    .collect();
+                row
+            })
+            .collect()
+    };
+    let real = normal(0.0, 300);
+    let same = normal(0.0, 200);
+    let shifted = normal(1.0, 200);
+
+    let close = GenerationReport::new(&real, &same).unwrap();
+    let far = GenerationReport::new(&real, &shifted).unwrap();
+    assert_eq!(close.features.len(), 3);
+    assert!(close.mean_drift < 0.2 && close.variance_drift < 0.2);
+    assert!((far.mean_drift - 1.0).abs() < 0.2);
+    assert!(far.features.iter().all(|feature| feature.mean_drift > 0.0));
+    assert!(close.ks < 0.15 && far.ks > 0.3);
+    assert!(close.mmd < 0.02 && far.mmd > close.mmd * 5.0);
+    assert_eq!(GenerationReport::new(&real, &real).unwrap().mmd, 0.0);
+    assert_eq!(close.classifier_accuracy, None);
+    assert!(close.to_json().is_ok());
+    assert!(close.to_string().contains("mmd"));
+
+    assert!(matches!(
+        GenerationReport::new(&real, &[]),
+        Err(DarjeelingError::InvalidMetricInput(_))
+    ));
+    assert!(GenerationReport::new(&real, &[vec![0.0].into()]).is_err());
+    // A diverged generator's rows would otherwise never finish being compared
+    let mut diverged = real.to_vec();
+    diverged[0] = vec![f32::NAN; real[0].len()].into();
+    assert!(matches!(
+        GenerationReport::new(&real, &diverged),
+        Err(DarjeelingError::InvalidMetricInput(_))
+    ));
+}
+
+#[test]
+fn monitor_report_cost() {
+    let mut rng = Pcg64::seed_from_u64(41);
+    let rows = |n: usize, rng: &mut Pcg64| -> Box<[Box<[f32]>]> {
+        (0..n)
+            .map(|_| (0..3).map(|_| rng.gen_range(-1.0..1.0)).collect())
+            .collect()
+    };
+    let small = rows(200, &mut rng);
+    let large = rows(50_000, &mut rng);
+    let mut net = NetworkBuilder::new(2)
+        .activation(ActivationFunction::Tanh)
+        .hidden(4)
+        .layer(LayerSpec::new(3).activation(ActivationFunction::Linear))
+        .build_gen()
+        .unwrap();
+    let mut monitor = Monitor::new(100);
+
+    // Comparing every pair of the large training set's rows would take far longer
+    let mut time_report = |real: &[Box<[f32]>]| {
+        let start = Instant::now();
+        let report = net.report(&mut monitor, real, &[]).unwrap();
+        (start.elapsed(), report)
+    };
+    let (small_time, small_report) = time_report(&small);
+    let (large_time, large_report) = time_report(&large);
+    assert!(
+        large_time < small_time * 10 + Duration::from_millis(200),
+        "{:?} for 200 rows, {:?} for 50000 rows",
+        small_time,
+        large_time
+    );
+    assert!(small_report.mmd.is_finite() && large_report.mmd.is_finite());
+    // The same real rows are drawn every time
+    assert_eq!(time_report(&large).1.mmd, large_report.mmd);
+}
+
+#[test]
+fn conditional_generation() {
+    // "low" values are close to 0.2 and "high" values are close to 0.8
+    let mut rng = Pcg64::seed_from_u64(23);
+    let data: Box<[Series]> = (0..256)
+        .map(|i| match i % 2 {
+            0 => Series::new(vec![rng.gen_range(0.15..0.25)], "low"),
+            _ => Series::new(vec![rng.gen_range(0.75..0.85)], "high"),
+        })
+        .collect();
+    let categories: Box<[String]> = ["low".to_string(), "high".to_string()].into();
+    let mut classifier = CatNetwork::new(1, 4, 2, 1, Some(ActivationFunction::Sigmoid));
+    let classifier_config = TrainConfig {
+        epochs: 20,
+        seed: Some(23),
+        ..Default::default()
+    };
+    let (_, accuracy, _) = classifier
+        .train(&data, categories.clone(), &classifier_config)
+        .unwrap();
+    assert!(accuracy > 99.0);
+    // 2 latent nodes and 2 category nodes
+    let mut net = NetworkBuilder::new(4)
+        .activation(ActivationFunction::Tanh)
+        .hidden(8)
+        .layer(LayerSpec::new(1).activation(ActivationFunction::Linear))
+        .build_gen()
+        .unwrap();
+    assert!(matches!(
+        net.sample_conditional("low", 1, &mut rng),
+        Err(DarjeelingError::ModelMissingCategories)
+    ));
+    net.set_monitor(Monitor::new(100).classifier(classifier));
+    let distinguisher = NetworkBuilder::new(3)
+        .activation(ActivationFunction::Tanh)
+        .hidden(8)
+        .hidden(1);
+    let config = TrainConfig {
+        learning_rate: 0.01,
+        epochs: 30,
+        batch_size: 8,
+        optimizer: Optimizer::adam(),
+        seed: Some(23),
+        ..Default::default()
+    };
+    let distinguishing_config = TrainConfig {
+        learning_rate: 0.01,
+        epochs: 1,
+        optimizer: Optimizer::adam(),
+        ..Default::default()
+    };
+    net.train_conditional(
+        &data,
+        categories.clone(),
+        &config,
+        &distinguisher,
+        &distinguishing_config,
     )
-    .unwrap()
+    .unwrap();
+    assert_eq!(net.categories(), Some(categories.as_ref()));
+    let reports = net.reports();
+    assert_eq!(reports.len(), 30);
+    let (first, last) = (&reports[0], &reports[29]);
+    assert!(last.ks < first.ks && last.mmd < first.mmd);
+    let accuracy = last.classifier_accuracy.unwrap();
+    assert!(accuracy > 0.9, "classifier accuracy {}", accuracy);
+
+    for (category, target) in [("low", 0.2), ("high", 0.8)] {
+        let generated = net.sample_conditional(category, 200, &mut rng).unwrap();
+        let mean = generated.iter().map(|row| row[0]).sum::<f32>() / generated.len() as f32;
+        assert!(
+            (mean - target).abs() < 0.15,
+            "generated {} mean {}",
+            category,
+            mean
+        );
+    }
+    assert_eq!(net.sample(3, &mut rng).unwrap().len(), 3);
+    assert!(matches!(
+        net.sample_conditional("middle", 1, &mut rng),
+        Err(DarjeelingError::CategoryDoesNotExist(_))
+    ));
+
+    let unconditioned = NetworkBuilder::new(1)
+        .activation(ActivationFunction::Tanh)
+        .hidden(8)
+        .hidden(1);
+    assert!(matches!(
+        net.train_conditional(
+            &data,
+            categories,
+            &config,
+            &unconditioned,
+            &distinguishing_config
+        ),
+        Err(DarjeelingError::InvalidLayerSpec(_))
+    ));
 }
 
-/// Read the file you want to and format it as Inputs
-pub fn gen_data_file<'a>() -> Box<[Box<[f32]>]> {
-    let file = match fs::File::open("training_data/train-digits.txt") {
-        Ok(file) => file,
-        Err(error) => panic!("Panic opening the file: {:?}", error),
+#[test]
+fn autoencoder() {
+    // 8 values that only depend on 2, so a bottleneck of 2 nodes can hold everything
+    let row = |a: f32, b: f32| -> Box<[f32]> {
+        [
+            a,
+            b,
+            1.0 - a,
+            1.0 - b,
+            (a + b) / 2.0,
+            a,
+            b,
+            (a - b) / 2.0 + 0.5,
+        ]
+        .into()
+    };
+    let mut rng = Pcg64::seed_from_u64(29);
+    let data: Box<[Box<[f32]>]> = (0..200)
+        .map(|_| row(rng.gen_range(0.0..1.0), rng.gen_range(0.0..1.0)))
+        .collect();
+    let config = TrainConfig {
+        learning_rate: 0.01,
+        epochs: 150,
+        batch_size: 4,
+        optimizer: Optimizer::adam(),
+        seed: Some(29),
+        ..Default::default()
     };
 
-    let reader = BufReader::new(file);
-    let mut inputs: Vec<Box<[f32]>> = vec![];
+    let mut net = NetworkBuilder::new(8)
+        .activation(ActivationFunction::Tanh)
+        .hidden(6)
+        .hidden(2)
+        .hidden(6)
+        .hidden(8)
+        .build_autoencoder()
+        .unwrap();
+    assert_eq!(net.bottleneck_width(), 2);
+    let (_, r2, loss) = net.train(&data, &config).unwrap();
+    assert!(r2 > 0.9 && loss < 0.01, "r2 {} loss {}", r2, loss);
 
-    for l in reader.lines() {
-        let line: String = match l {
-            Ok(line) => line,
-            Err(error) => panic!("{:?}", error),
-        };
+    let code = net.encode(&data[0]).unwrap();
+    assert_eq!(code.len(), 2);
+    assert_eq!(
+        net.decode(&code).unwrap(),
+        net.reconstruct(&data[0]).unwrap()
+    );
+    assert!(matches!(
+        net.decode(&[0.0]),
+        Err(DarjeelingError::InvalidLayerSpec(_))
+    ));
+    assert!(net.encode(&[0.0]).is_err());
 
-        let init_inputs: Vec<&str> = line.split(",").collect();
-        let mut float_inputs: Vec<f32> = vec![];
-        for i in 0..init_inputs.len() {
-            float_inputs.push(init_inputs[i].parse().unwrap());
-        }
-        inputs.push(float_inputs.into_boxed_slice());
+    // An input that breaks the pattern reconstructs much worse than the ones it was trained on
+    let scores = net.anomaly_scores(&data).unwrap();
+    let normal = scores.iter().sum::<f32>() / scores.len() as f32;
+    let anomaly = net
+        .anomaly_score(&[0.9, 0.1, 0.9, 0.1, 0.1, 0.1, 0.9, 0.1])
+        .unwrap();
+    assert!(
+        anomaly > normal * 10.0,
+        "anomaly {} normal {}",
+        anomaly,
+        normal
+    );
+
+    net.save_model("autoencoder.darj").unwrap();
+    let mut model = AutoEncoder::read_model("autoencoder.darj".to_string()).unwrap();
+    assert_eq!(
+        model.encode(&data[1]).unwrap(),
+        net.encode(&data[1]).unwrap()
+    );
+    fs::remove_file("autoencoder.darj").unwrap();
+
+    // Denoising
+    let mut net = AutoEncoder::new(8, 6, 2, 1, Some(ActivationFunction::Tanh));
+    assert!(matches!(
+        net.set_corruption(Some(Corruption::Masking { probability: 1.0 })),
+        Err(DarjeelingError::InvalidCorruption(_))
+    ));
+    let corruption = Corruption::Gaussian { std_dev: 0.05 };
+    net.set_corruption(Some(corruption)).unwrap();
+    assert_eq!(net.corruption(), Some(corruption));
+    let (_, r2, _) = net.train(&data, &config).unwrap();
+    assert!(r2 > 0.8, "denoising r2 {}", r2);
+    let noisy: Box<[f32]> = Corruption::Masking { probability: 0.2 }
+        .corrupt(&data[2], &mut rng)
+        .unwrap();
+    assert_eq!(noisy.len(), 8);
+    assert!(noisy
+        .iter()
+        .zip(data[2].iter())
+        .all(|(noisy, clean)| *noisy == 0.0 || noisy == clean));
+
+    // Interrupted after the checkpoint at epoch 2, then resumed, with the same corruption as an uninterrupted run
+    let mut initial = AutoEncoder::new(8, 6, 2, 1, Some(ActivationFunction::Tanh));
+    initial.set_corruption(Some(corruption)).unwrap();
+    let config = TrainConfig {
+        epochs: 4,
+        save_path: Some("autoencoder_resumed.darj".to_string()),
+        checkpoint: Some(CheckpointConfig {
+            path: "autoencoder_resumed.ckpt".to_string(),
+            every_epochs: Some(2),
+            every_minutes: None,
+        }),
+        ..config
+    };
+    initial.clone().train(&data, &config).unwrap();
+    let uninterrupted_bin = fs::read("autoencoder_resumed.darj").unwrap();
+    let interrupted_config = TrainConfig {
+        epochs: 2,
+        ..config.clone()
+    };
+    initial.clone().train(&data, &interrupted_config).unwrap();
+    let mut checkpoint = Checkpoint::<AutoEncoder>::read("autoencoder_resumed.ckpt").unwrap();
+    assert_eq!(checkpoint.state.epoch, 2);
+    checkpoint.config.epochs = 4;
+    AutoEncoder::resume_from(checkpoint, &data).unwrap();
+    assert_eq!(
+        fs::read("autoencoder_resumed.darj").unwrap(),
+        uninterrupted_bin
+    );
+    fs::remove_file("autoencoder_resumed.ckpt").unwrap();
+
+    assert!(NetworkBuilder::new(8)
+        .hidden(8)
+        .build_autoencoder()
+        .is_err());
+    assert!(NetworkBuilder::new(8)
+        .hidden(2)
+        .hidden(4)
+        .build_autoencoder()
+        .is_err());
+}
+
+#[test]
+fn variational_autoencoder() {
+    let row = |a: f32, b: f32| -> Box<[f32]> {
+        [
+            a,
+            b,
+            1.0 - a,
+            1.0 - b,
+            (a + b) / 2.0,
+            a,
+            b,
+            (a - b) / 2.0 + 0.5,
+        ]
+        .into()
+    };
+    let mut rng = Pcg64::seed_from_u64(31);
+    let data: Box<[Box<[f32]>]> = (0..300)
+        .map(|_| row(rng.gen_range(0.0..1.0), rng.gen_range(0.0..1.0)))
+        .collect();
+    let mut net = VarAutoEncoder::new(8, 16, 2, 1, Some(ActivationFunction::Tanh));
+    net.set_pipeline(Pipeline::new().scale(Scaling::Standard));
+    let config = TrainConfig {
+        learning_rate: 0.005,
+        epochs: 100,
+        batch_size: 8,
+        optimizer: Optimizer::adam(),
+        seed: Some(31),
+        ..Default::default()
+    };
+    let (_, reconstruction, kl) = net.train(&data, &config).unwrap();
+    // The 8 standardized values have a total variance of 8
+    assert!(
+        reconstruction < 3.0,
+        "reconstruction loss {}",
+        reconstruction
+    );
+    assert!(kl > 0.0 && kl.is_finite(), "kl divergence {}", kl);
+
+    let (mean, log_variance) = net.encode_distribution(&data[0]).unwrap();
+    assert_eq!(mean.len(), 2);
+    assert_eq!(log_variance.len(), 2);
+    assert_eq!(net.encode(&data[0]).unwrap(), mean);
+    assert_eq!(
+        net.reconstruct(&data[0]).unwrap(),
+        net.decode(&mean).unwrap()
+    );
+    assert!(matches!(
+        net.decode(&[0.0]),
+        Err(DarjeelingError::InvalidLatent(_))
+    ));
+
+    // Generated rows spread over the data like the real ones
+    let generated = net.sample(500, &mut rng).unwrap();
+    for column in 0..8 {
+        let real = data.iter().map(|row| row[column]).sum::<f32>() / data.len() as f32;
+        let fake = generated.iter().map(|row| row[column]).sum::<f32>() / generated.len() as f32;
+        assert!(
+            (real - fake).abs() < 0.15,
+            "column {} real mean {} generated mean {}",
+            column,
+            real,
+            fake
+        );
     }
-    inputs.into_boxed_slice()
+    assert_eq!(
+        net.sample(3, &mut Pcg64::seed_from_u64(1)).unwrap(),
+        net.sample(3, &mut Pcg64::seed_from_u64(1)).unwrap()
+    );
+
+    net.save_model("vae.darj").unwrap();
+    let mut model = VarAutoEncoder::read_model("vae.darj".to_string()).unwrap();
+    assert_eq!(
+        model.encode(&data[1]).unwrap(),
+        net.encode(&data[1]).unwrap()
+    );
+    fs::remove_file("vae.darj").unwrap();
+
+    let encoder = NetworkBuilder::new(8)
+        .activation(ActivationFunction::Tanh)
+        .hidden(4);
+    assert!(encoder
+        .build_vae(&NetworkBuilder::new(3).hidden(8))
+        .is_err());
+    assert!(encoder
+        .build_vae(&NetworkBuilder::new(2).hidden(6))
+        .is_err());
+    assert!(encoder.build_vae(&NetworkBuilder::new(2).hidden(8)).is_ok());
+}
+
+/// Read the file you want to and format it as Inputs
+pub fn gen_data_file() -> Box<[Box<[f32]>]> {
+    CsvReader::new()
+        .read_rows("training_data/train-digits.txt")
+        .expect("Failed to read the digits file")
 }
@@ -0,0 +1,219 @@
+use crate::{
+    activation::ActivationFunction,
+    checkpoint::{Checkpoint, TrainState},
+    config::TrainConfig,
+    error::DarjeelingError,
+    layer::{self, LayerSpec, NodeArray},
+    neural_network::Model,
+    split,
+};
+use rand_pcg::Pcg64;
+use serde::{de::DeserializeOwned, Serialize};
+use std::{borrow::Cow, fmt, time::Instant};
+
+/// The mean loss of an epoch, and the answer layer's outputs for each row
+pub(crate) type EpochOutputs = (f32, Box<[Box<[f32]>]>);
+
+/// The derivative of an input's loss that backpropogation starts from
+pub(crate) enum Gradient {
+    /// With respect to each answer node's output
+    Outputs(Box<[f32]>),
+    /// With respect to each answer node's weighted input, see [`layer::backpropogate_weighted`]
+    Weighted(Box<[f32]>),
+}
+
+/// A network with a single node array, trained one input at a time by [`epoch`]
+pub(crate) trait Supervised {
+    /// The network's node array and the specs of its layers
+    fn parts(&mut self) -> (&mut NodeArray, &[LayerSpec]);
+
+    /// What the network is given while it's trained on a row, the row itself unless the network corrupts it
+    fn training_input<'a>(
+        &self,
+        row: &'a [f32],
+        _rng: &mut Pcg64,
+    ) -> Result<Cow<'a, [f32]>, DarjeelingError> {
+        Ok(Cow::Borrowed(row))
+    }
+}
+
+/// Trains a network on every row once, in a random order.
+/// The optimizer steps after every batch, and once more for the rows left over.
+///
+/// ## Params
+/// - Rows: The inputs, already through the network's pipeline
+/// - Loss: The loss of the answer layer's outputs for the row with the given index, and its derivative
+///
+/// ## Returns
+/// The mean loss, and the answer layer's outputs for each row, in the order of the rows
+///
+/// ## Err
+/// The errors of [`Supervised::training_input`]
+pub(crate) fn epoch(
+    network: &mut impl Supervised,
+    activation_function: ActivationFunction,
+    rows: &[Box<[f32]>],
+    config: &TrainConfig,
+    state: &mut TrainState,
+    mut loss: impl FnMut(usize, &[f32]) -> (f32, Gradient),
+) -> Result<EpochOutputs, DarjeelingError> {
+    let learning_rate = config.learning_rate_at(state.epoch);
+    let mut total_loss = 0.0;
+    let mut batch_len = 0;
+    let mut outputs: Vec<Box<[f32]>> = vec![Box::new([]); rows.len()];
+
+    let mut order: Vec<usize> = (0..rows.len()).collect();
+    split::shuffle(&mut order, &mut state.rng);
+    for i in order {
+        let input = network.training_input(&rows[i], &mut state.rng)?;
+        let (node_array, layers) = network.parts();
+        layer::push_downstream(
+            node_array,
+            layers,
+            activation_function,
+            &input,
+            true,
+            &mut state.rng,
+        );
+        let answer = layer::answer_outputs(node_array);
+        let (input_loss, gradient) = loss(i, &answer);
+        total_loss += input_loss;
+
+        match gradient {
+            Gradient::Outputs(grads) => {
+                layer::backpropogate(node_array, layers, activation_function, &grads)
+            }
+            Gradient::Weighted(grads) => {
+                layer::backpropogate_weighted(node_array, layers, activation_function, &grads)
+            }
+        };
+        layer::accumulate_grads(node_array, layers);
+        outputs[i] = answer;
+        batch_len += 1;
+        if batch_len == config.batch_size.max(1) {
+            state
+                .optimizer_state
+                .step(config.optimizer, node_array, learning_rate, batch_len);
+            batch_len = 0;
+        }
+    }
+    if batch_len > 0 {
+        let (node_array, _) = network.parts();
+        state
+            .optimizer_state
+            .step(config.optimizer, node_array, learning_rate, batch_len);
+    }
+
+    Ok((total_loss / rows.len().max(1) as f32, outputs.into()))
+}
+
+/// Counts a finished epoch, prints it, and keeps track of the best loss and accuracy so far
+///
+/// ## Params
+/// - Loss: The epoch's mean loss, early stopping stops once it hasn't improved for long enough
+/// - Accuracy: The epoch's accuracy percent that the config's target accuracy is compared to, none if the network has none
+/// - Summary: Printed after the epoch's number
+///
+/// ## Returns
+/// Whether training should stop, because the target accuracy was reached or the loss stopped improving
+///
+/// ## Err
+/// ### NanEncountered
+/// The loss is NaN, so the training diverged
+pub(crate) fn end_epoch(
+    config: &TrainConfig,
+    state: &mut TrainState,
+    loss: f32,
+    accuracy: Option<f32>,
+    summary: fmt::Arguments,
+) -> Result<bool, DarjeelingError> {
+    if loss.is_nan() {
+        return Err(DarjeelingError::NanEncountered(format!(
+            "the loss of epoch {} is NaN, try a lower learning rate",
+            state.epoch + 1
+        )));
+    }
+    state.epoch += 1;
+    if let Some(accuracy) = accuracy {
+        state.best_accuracy = state.best_accuracy.max(accuracy);
+    }
+    println!("Epoch: {:?}", state.epoch);
+    println!("{}", summary);
+
+    let improved = state.best_mse - loss
+        > config
+            .early_stopping
+            .map_or(0.0, |early_stopping| early_stopping.min_delta);
+    if improved {
+        state.best_mse = loss;
+        state.epochs_without_improvement = 0;
+    } else {
+        state.epochs_without_improvement += 1;
+    }
+
+    if let (Some(target), Some(accuracy)) = (config.target_accuracy, accuracy) {
+        if accuracy >= target {
+            return Ok(true);
+        }
+    }
+    if let Some(early_stopping) = config.early_stopping {
+        if state.epochs_without_improvement >= early_stopping.patience {
+            println!(
+                "Training: Stopping early, loss hasn't improved in {:?} epochs",
+                state.epochs_without_improvement
+            );
+            return Ok(true);
+        }
+    }
+    Ok(false)
+}
+
+/// Writes a checkpoint of the training run to the config's checkpoint path, if one is due
+///
+/// ## Params
+/// - Categories: What the network is being trained on, empty if it has no categories
+/// - Last Checkpoint: When the last checkpoint was written, it's updated if another one is
+///
+/// ## Err
+/// The errors of [`Checkpoint::write`]
+pub(crate) fn checkpoint<N: Serialize + DeserializeOwned + Clone>(
+    network: &N,
+    categories: &[String],
+    config: &TrainConfig,
+    state: &TrainState,
+    last_checkpoint: &mut Instant,
+) -> Result<(), DarjeelingError> {
+    if let Some(checkpoint) = &config.checkpoint {
+        if checkpoint.is_due(state.epoch, *last_checkpoint) {
+            Checkpoint {
+                network: network.clone(),
+                categories: categories.into(),
+                config: config.clone(),
+                state: state.clone(),
+            }
+            .write(&checkpoint.path)?;
+            *last_checkpoint = Instant::now();
+        }
+    }
+    Ok(())
+}
+
+/// Writes a trained network to the config's save path, if it has one
+///
+/// ## Returns
+/// The save path the network was written to
+///
+/// ## Err
+/// The errors of [`Model::save_model`]
+pub(crate) fn save(
+    network: &impl Model,
+    config: &TrainConfig,
+) -> Result<Option<String>, DarjeelingError> {
+    match &config.save_path {
+        Some(save_path) => {
+            network.save_model(save_path)?;
+            Ok(Some(save_path.clone()))
+        }
+        None => Ok(None),
+    }
+}
@@ -1,7 +1,5 @@
-use std::collections::HashSet;
 pub use std::time::Instant;
 
-use rand::Rng;
 #[macro_export]
 macro_rules! dbg_println {
     // `()` indicates that the macro takes no argument.
@@ -20,38 +18,3 @@ macro_rules! bench {
         }
     };
 }
-
-pub struct RandomIter<'a, T> {
-    data: &'a [T],
-    indices: HashSet<usize>,
-    count: usize,
-}
-
-impl<'a, T> RandomIter<'a, T> {
-    pub fn new(data: &'a [T]) -> Self {
-        let count = data.len();
-        RandomIter {
-            data,
-            indices: HashSet::with_capacity(count),
-            count,
-        }
-    }
-}
-
-impl<'a, T> Iterator for RandomIter<'a, T> {
-    type Item = &'a T;
-
-    fn next(&mut self) -> Option<Self::Item> {
-        if self.indices.len() == self.count {
-            return None;
-        }
-
-        let mut rng = rand::thread_rng();
-        loop {
-            let index = rng.gen_range(0..self.count);
-            if self.indices.insert(index) {
-                return Some(&self.data[index]);
-            }
-        }
-    }
-}
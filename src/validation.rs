@@ -0,0 +1,270 @@
+use crate::{
+    builder::NetworkBuilder, config::TrainConfig, error::DarjeelingError,
+    metrics::ClassificationReport, neural_network::NeuralNetwork, series::Series, split::shuffle,
+};
+use rand::SeedableRng;
+use rand_pcg::Pcg64;
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// How data is split into folds for [`cross_validate`]
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+pub struct Folds {
+    /// The number of folds, each one is held out for evaluation once
+    pub k: usize,
+    /// Whether every fold gets the same share of each answer as the whole data
+    pub stratified: bool,
+    /// Whether the folds are trained at the same time
+    pub parallel: bool,
+}
+
+impl Folds {
+    /// Plain folds, trained one after another
+    pub fn new(k: usize) -> Folds {
+        Folds {
+            k,
+            stratified: false,
+            parallel: false,
+        }
+    }
+
+    pub fn stratified(mut self) -> Folds {
+        self.stratified = true;
+        self
+    }
+
+    pub fn parallel(mut self) -> Folds {
+        self.parallel = true;
+        self
+    }
+
+    /// The indices of the inputs held out in each fold, shuffled by the given rng
+    pub fn split(
+        &self,
+        data: &[Series],
+        rng: &mut Pcg64,
+    ) -> Result<Box<[Box<[usize]>]>, DarjeelingError> {
+        if self.k < 2 || self.k > data.len() {
+            return Err(DarjeelingError::InvalidFolds(format!(
+                "{} folds can't be made from {} inputs",
+                self.k,
+                data.len()
+            )));
+        }
+
+        let mut groups: Vec<Vec<usize>> = if self.stratified {
+            let mut answers: Vec<&str> = data.iter().map(|series| series.answer.as_str()).collect();
+            answers.sort_unstable();
+            answers.dedup();
+            answers
+                .iter()
+                .map(|answer| {
+                    (0..data.len())
+                        .filter(|i| data[*i].answer == *answer)
+                        .collect()
+                })
+                .collect()
+        } else {
+            vec![(0..data.len()).collect()]
+        };
+
+        // Dealing every group out in turn keeps the folds within one input of each other's size
+        let mut folds = vec![vec![]; self.k];
+        let mut fold = 0;
+        for group in groups.iter_mut() {
+            shuffle(group, rng);
+            for index in group.iter() {
+                folds[fold].push(*index);
+                fold = (fold + 1) % self.k;
+            }
+        }
+        Ok(folds
+            .into_iter()
+            .map(|fold| fold.into_boxed_slice())
+            .collect())
+    }
+}
+
+/// The results of training on every fold but one and evaluating on that one
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct FoldResult {
+    pub fold: usize,
+    pub train_size: usize,
+    pub test_size: usize,
+    /// The training accuracy percent of the last epoch
+    pub train_accuracy: f32,
+    /// The training mse of the last epoch
+    pub train_mse: f32,
+    /// The evaluation of the held out fold
+    pub report: ClassificationReport,
+}
+
+/// The mean and standard deviation of a metric over every fold
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+pub struct Summary {
+    pub mean: f32,
+    pub std: f32,
+}
+
+impl Summary {
+    fn new(values: impl Iterator<Item = f32> + Clone) -> Summary {
+        let count = values.clone().count() as f32;
+        let mean = values.clone().sum::<f32>() / count;
+        let variance = values.map(|value| (value - mean).powi(2)).sum::<f32>() / count;
+        Summary {
+            mean,
+            std: variance.sqrt(),
+        }
+    }
+}
+
+impl fmt::Display for Summary {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:.4} ± {:.4}", self.mean, self.std)
+    }
+}
+
+/// The results of [`cross_validate`]
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct CrossValidationReport {
+    pub folds: Box<[FoldResult]>,
+    /// The held out accuracy
+    pub accuracy: Summary,
+    pub macro_f1: Summary,
+    pub weighted_f1: Summary,
+    pub log_loss: Summary,
+}
+
+impl CrossValidationReport {
+    fn new(folds: Box<[FoldResult]>) -> CrossValidationReport {
+        let reports = folds.iter().map(|fold| &fold.report);
+        CrossValidationReport {
+            accuracy: Summary::new(reports.clone().map(|report| report.accuracy)),
+            macro_f1: Summary::new(reports.clone().map(|report| report.macro_avg.f1)),
+            weighted_f1: Summary::new(reports.clone().map(|report| report.weighted_avg.f1)),
+            log_loss: Summary::new(reports.map(|report| report.log_loss)),
+            folds,
+        }
+    }
+
+    pub fn to_json(&self) -> Result<String, DarjeelingError> {
+        serde_json::to_string_pretty(self)
+            .map_err(|err| DarjeelingError::SerializationFailed(err.to_string()))
+    }
+}
+
+impl fmt::Display for CrossValidationReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "{:>6} {:>8} {:>8} {:>10} {:>10} {:>10}",
+            "fold", "train", "test", "accuracy", "macro f1", "log loss"
+        )?;
+        for fold in self.folds.iter() {
+            writeln!(
+                f,
+                "{:>6} {:>8} {:>8} {:>10.4} {:>10.4} {:>10.4}",
+                fold.fold,
+                fold.train_size,
+                fold.test_size,
+                fold.report.accuracy,
+                fold.report.macro_avg.f1,
+                fold.report.log_loss
+            )?;
+        }
+        writeln!(f)?;
+        writeln!(f, "accuracy:    {}", self.accuracy)?;
+        writeln!(f, "macro f1:    {}", self.macro_f1)?;
+        writeln!(f, "weighted f1: {}", self.weighted_f1)?;
+        writeln!(f, "log loss:    {}", self.log_loss)
+    }
+}
+
+/// Estimates how well a network generalizes by training a fresh one on every fold but one,
+/// then evaluating it on the held out fold.
+///
+/// ## Params
+/// - Builder: Builds the network trained on each fold
+/// - Config: The config every fold is trained with.
+///   Folds are never saved or checkpointed, and when the config has a seed each fold is trained with a different seed derived from it.
+/// - Data: Every input, with its correct answer
+/// - Categories: The categories of the answer layer, in order
+/// - Folds: How many folds there are, and how they're made and trained
+///
+/// ## Returns
+/// The evaluation of each fold, along with the mean and standard deviation of its metrics
+///
+/// ## Err
+/// ### InvalidFolds
+/// There are fewer than two folds, or more folds than inputs
+/// ### InvalidLayerSpec
+/// The builder couldn't build a network
+///
+/// ## Examples
+/// ```ignore
+/// use darjeeling::validation::{cross_validate, Folds};
+///
+/// let builder = NetworkBuilder::new(64).hidden(128).hidden(10);
+/// let report = cross_validate(&builder, &config, &data, &categories, Folds::new(5).stratified().parallel()).unwrap();
+/// println!("{}", report);
+/// ```
+pub fn cross_validate(
+    builder: &NetworkBuilder,
+    config: &TrainConfig,
+    data: &[Series],
+    categories: &[String],
+    folds: Folds,
+) -> Result<CrossValidationReport, DarjeelingError> {
+    let mut rng = match config.seed {
+        Some(seed) => Pcg64::seed_from_u64(seed),
+        None => Pcg64::from_entropy(),
+    };
+    let held_out = folds.split(data, &mut rng)?;
+
+    let run_fold = |fold: usize| -> Result<FoldResult, DarjeelingError> {
+        let mut in_test = vec![false; data.len()];
+        held_out[fold]
+            .iter()
+            .for_each(|index| in_test[*index] = true);
+        let mut train = vec![];
+        let mut test = vec![];
+        for (series, in_test) in data.iter().zip(in_test) {
+            if in_test {
+                test.push(series.clone());
+            } else {
+                train.push(series.clone());
+            }
+        }
+
+        let fold_config = TrainConfig {
+            seed: config.seed.map(|seed| seed.wrapping_add(fold as u64 + 1)),
+            save_path: None,
+            checkpoint: None,
+            ..config.clone()
+        };
+        let mut net = builder.build_cat()?;
+        let (_, train_accuracy, train_mse) = net.train(&train, categories.into(), &fold_config)?;
+
+        Ok(FoldResult {
+            fold,
+            train_size: train.len(),
+            test_size: test.len(),
+            train_accuracy,
+            train_mse,
+            report: net.evaluate(&test, categories)?,
+        })
+    };
+
+    let results = if folds.parallel {
+        (0..folds.k)
+            .into_par_iter()
+            .map(run_fold)
+            .collect::<Result<Vec<FoldResult>, DarjeelingError>>()?
+    } else {
+        (0..folds.k)
+            .map(run_fold)
+            .collect::<Result<Vec<FoldResult>, DarjeelingError>>()?
+    };
+    Ok(CrossValidationReport::new(results.into_boxed_slice()))
+}
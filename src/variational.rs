@@ -0,0 +1,522 @@
+use crate::{
+    activation::ActivationFunction,
+    builder::NetworkBuilder,
+    checkpoint::TrainState,
+    config::{ConfigField, TrainConfig},
+    error::DarjeelingError,
+    layer::{self, LayerSpec, NodeArray},
+    neural_network::Model,
+    optimizer::OptimizerState,
+    preprocessing::{check_missing, pipeline::Pipeline, Transform},
+    split, trainer,
+};
+use rand::Rng;
+use rand_distr::StandardNormal;
+use serde::{Deserialize, Serialize};
+
+/// The mean and the log variance of each value of an input's latent distribution
+pub type LatentDistribution = (Box<[f32]>, Box<[f32]>);
+
+/// The variational autoencoder struct, a generative model that learns a smooth latent space of its inputs.
+///
+/// The encoder turns an input into the mean and log variance of a normal distribution for each latent value,
+/// its answer layer has the mean nodes followed by the log variance nodes.
+/// A latent vector is drawn from those distributions with the reparameterisation trick,
+/// and the decoder turns it back into an input. Training minimizes the config's [`Loss`](crate::config::Loss)
+/// between the input and its reconstruction, plus the KL divergence of the distributions from a standard normal one,
+/// so new data can be generated by decoding latent vectors drawn from a standard normal distribution.
+///
+/// Both answer layers always use the linear activation function.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct VarAutoEncoder {
+    encoder: NodeArray,
+    encoder_layers: Box<[LayerSpec]>,
+    decoder: NodeArray,
+    decoder_layers: Box<[LayerSpec]>,
+    activation_function: Option<ActivationFunction>,
+    /// The config the network was last trained with
+    config: Option<TrainConfig>,
+    /// Every input goes through it before it reaches the network
+    pipeline: Option<Pipeline>,
+}
+
+impl VarAutoEncoder {
+    /// Constructor function for a variational autoencoder
+    /// Fills both networks' node arrays with empty nodes.
+    /// Initializes random starting link and bias weights between -.5 and .5
+    ///
+    /// ## Params
+    /// - Inputs: The number of sensors in the encoder's input layer, and the number of the decoder's answer nodes
+    /// - Hidden: The number of hidden nodes per hidden layer of the encoder and decoder
+    /// - Latent: The number of values in a latent vector
+    /// - Hidden Layers: The number of hidden layers in the encoder and in the decoder
+    /// - Activation Function: Which activation function is used by the hidden layers, the answer layers are always linear
+    ///
+    /// The decoder mirrors the encoder, use [`NetworkBuilder::build_vae`] for anything else.
+    ///
+    /// ## Examples
+    /// ``` rust
+    /// use darjeeling::{activation::ActivationFunction, variational::VarAutoEncoder};
+    ///
+    /// // 64 -> 32 -> 2 x 4, then 4 -> 32 -> 64
+    /// let net = VarAutoEncoder::new(64, 32, 4, 1, Some(ActivationFunction::Tanh));
+    /// assert_eq!(net.latent_width(), 4);
+    /// ```
+    pub fn new(
+        input_nodes: usize,
+        hidden_nodes: usize,
+        latent_nodes: usize,
+        hidden_layers: usize,
+        activation_function: Option<ActivationFunction>,
+    ) -> VarAutoEncoder {
+        let mut encoder = (0..hidden_layers)
+            .fold(NetworkBuilder::new(input_nodes), |builder, _| {
+                builder.hidden(hidden_nodes)
+            })
+            .hidden(2 * latent_nodes);
+        let mut decoder = (0..hidden_layers)
+            .fold(NetworkBuilder::new(latent_nodes), |builder, _| {
+                builder.hidden(hidden_nodes)
+            })
+            .hidden(input_nodes);
+        if let Some(activation_function) = activation_function {
+            encoder = encoder.activation(activation_function);
+            decoder = decoder.activation(activation_function);
+        }
+
+        encoder
+            .build_vae(&decoder)
+            .expect("Every layer of the network needs at least one node")
+    }
+
+    /// Expects layers that have been checked by [`NetworkBuilder::build_vae`]
+    pub(crate) fn from_parts(
+        encoder: NodeArray,
+        mut encoder_layers: Box<[LayerSpec]>,
+        decoder: NodeArray,
+        mut decoder_layers: Box<[LayerSpec]>,
+        activation_function: Option<ActivationFunction>,
+    ) -> VarAutoEncoder {
+        for layers in [&mut encoder_layers, &mut decoder_layers] {
+            if let Some(answer) = layers.last_mut() {
+                answer.activation = Some(ActivationFunction::Linear);
+            }
+        }
+
+        VarAutoEncoder {
+            encoder,
+            encoder_layers,
+            decoder,
+            decoder_layers,
+            activation_function,
+            config: None,
+            pipeline: None,
+        }
+    }
+
+    /// The config the network was last trained with, saved models keep it
+    pub fn config(&self) -> Option<&TrainConfig> {
+        self.config.as_ref()
+    }
+
+    /// Sets the pipeline every input goes through before it reaches the network.
+    /// An unfitted pipeline is fitted on the training data the next time the network is trained,
+    /// and it's saved with the model. Decoded inputs are turned back to the scale of the raw inputs.
+    pub fn set_pipeline(&mut self, pipeline: Pipeline) {
+        self.pipeline = Some(pipeline);
+    }
+
+    /// The pipeline every input goes through, saved models keep it
+    pub fn pipeline(&self) -> Option<&Pipeline> {
+        self.pipeline.as_ref()
+    }
+
+    /// The number of values in a latent vector
+    pub fn latent_width(&self) -> usize {
+        self.decoder[0].len()
+    }
+
+    /// Trains the encoder and decoder together to reconstruct each input through a random latent vector.
+    /// Inputs far from zero train better once they're scaled down.
+    ///
+    /// ## Params
+    /// - Data: List of inputs, each as wide as the encoder's input layer
+    /// - Config: The hyperparameters of the training, see [`TrainConfig`].
+    ///   Its loss is the reconstruction loss, summed over the values of each input.
+    ///   Early stopping watches the reconstruction loss plus the KL divergence.
+    ///   The model is written to the config's save path once training finishes, if it has one.
+    ///
+    /// ## Returns
+    /// The fallible:
+    /// - save path the model was written to, none if the config has no save path
+    /// - the mean reconstruction loss of the last epoch
+    /// - the mean KL divergence of the last epoch
+    ///
+    /// ## Err
+    /// - ### ModelMissingActivationFunction
+    /// The network has no activation function to train with
+    /// - ### InvalidLayerSpec
+    /// An input isn't as wide as the encoder's input layer
+    /// - ### NanEncountered
+    /// An input has a missing (NaN) value, or the training diverged
+    /// - ### WriteModelFailed
+    /// There was a problem when saving the model to a file
+    /// - ### UnsupportedConfig
+    /// The config has a target accuracy, checkpoints or class weights, which variational autoencoders don't use
+    ///
+    /// ## Examples
+    /// ```ignore
+    /// let mut net = VarAutoEncoder::new(64, 32, 4, 1, Some(ActivationFunction::Tanh));
+    /// net.set_pipeline(Pipeline::new().scale(Scaling::MinMax));
+    /// let (model_name, reconstruction, kl) = net.train(&digits, &TrainConfig::default()).unwrap();
+    /// let new_digits = net.sample(10, &mut rand::thread_rng()).unwrap();
+    /// ```
+    pub fn train(
+        &mut self,
+        data: &[Box<[f32]>],
+        config: &TrainConfig,
+    ) -> Result<(Option<String>, f32, f32), DarjeelingError> {
+        config.check_unused(
+            "variational autoencoder training",
+            &[
+                ConfigField::TargetAccuracy,
+                ConfigField::Checkpoint,
+                ConfigField::ClassWeights,
+            ],
+        )?;
+        let activation_function = match self.activation_function {
+            Some(s) => s,
+            None => return Err(DarjeelingError::ModelMissingActivationFunction),
+        };
+        let width = self.encoder[0].len();
+        if let Some(row) = data.iter().find(|row| row.len() != width) {
+            return Err(DarjeelingError::InvalidLayerSpec(format!(
+                "the input layer has {} nodes, but an input has {} values",
+                width,
+                row.len()
+            )));
+        }
+
+        if let Some(pipeline) = self
+            .pipeline
+            .as_mut()
+            .filter(|pipeline| !pipeline.is_fitted())
+        {
+            pipeline.fit_rows(&data.iter().map(|row| row.as_ref()).collect::<Vec<&[f32]>>())?;
+        }
+        let rows = self.preprocess(data)?;
+        check_missing(&rows.iter().map(|row| row.as_ref()).collect::<Vec<&[f32]>>())?;
+
+        let mut state = TrainState::new(config.seed);
+        // The train state's optimizer state is the encoder's
+        let mut decoder_state = OptimizerState::new();
+        let latent_width = self.latent_width();
+        let mut reconstruction_loss = 0.0;
+        let mut kl_divergence = 0.0;
+        while state.epoch < config.epochs {
+            reconstruction_loss = 0.0;
+            kl_divergence = 0.0;
+            let learning_rate = config.learning_rate_at(state.epoch);
+            let mut batch_len = 0;
+
+            let mut order: Vec<usize> = (0..rows.len()).collect();
+            split::shuffle(&mut order, &mut state.rng);
+            for i in order {
+                let row = &rows[i];
+                let (mean, log_variance) =
+                    self.encode_row(activation_function, row, true, &mut state.rng);
+                // The reparameterisation trick, the randomness is an input so gradients can flow through the mean and variance
+                let noise: Box<[f32]> = (0..latent_width)
+                    .map(|_| state.rng.sample(StandardNormal))
+                    .collect();
+                let latent: Box<[f32]> = (0..latent_width)
+                    .map(|j| mean[j] + (0.5 * log_variance[j]).exp() * noise[j])
+                    .collect();
+                let outputs = self.decode_row(activation_function, &latent, true, &mut state.rng);
+
+                reconstruction_loss += outputs
+                    .iter()
+                    .zip(row.iter())
+                    .map(|(output, target)| config.loss.loss(*output, *target))
+                    .sum::<f32>();
+                kl_divergence += (0..latent_width)
+                    .map(|j| {
+                        -0.5 * (1.0 + log_variance[j] - mean[j].powi(2) - log_variance[j].exp())
+                    })
+                    .sum::<f32>();
+
+                let output_grads: Box<[f32]> = outputs
+                    .iter()
+                    .zip(row.iter())
+                    .map(|(output, target)| config.loss.derivative(*output, *target))
+                    .collect();
+                let latent_grads = layer::backpropogate(
+                    &mut self.decoder,
+                    &self.decoder_layers,
+                    activation_function,
+                    &output_grads,
+                );
+                layer::accumulate_grads(&mut self.decoder, &self.decoder_layers);
+
+                // The reconstruction's gradient through the latent vector, plus the KL divergence's own gradient
+                let head_grads: Box<[f32]> = (0..latent_width)
+                    .map(|j| latent_grads[j] + mean[j])
+                    .chain((0..latent_width).map(|j| {
+                        let std_dev = (0.5 * log_variance[j]).exp();
+                        latent_grads[j] * noise[j] * 0.5 * std_dev
+                            + 0.5 * (log_variance[j].exp() - 1.0)
+                    }))
+                    .collect();
+                layer::backpropogate(
+                    &mut self.encoder,
+                    &self.encoder_layers,
+                    activation_function,
+                    &head_grads,
+                );
+                layer::accumulate_grads(&mut self.encoder, &self.encoder_layers);
+
+                batch_len += 1;
+                if batch_len == config.batch_size.max(1) {
+                    self.step(
+                        config,
+                        &mut state,
+                        &mut decoder_state,
+                        learning_rate,
+                        batch_len,
+                    );
+                    batch_len = 0;
+                }
+            }
+            if batch_len > 0 {
+                self.step(
+                    config,
+                    &mut state,
+                    &mut decoder_state,
+                    learning_rate,
+                    batch_len,
+                );
+            }
+
+            reconstruction_loss /= rows.len().max(1) as f32;
+            kl_divergence /= rows.len().max(1) as f32;
+            if trainer::end_epoch(
+                config,
+                &mut state,
+                reconstruction_loss + kl_divergence,
+                None,
+                format_args!(
+                    "Training Reconstruction Loss: {:?}, KL Divergence: {:?}",
+                    reconstruction_loss, kl_divergence
+                ),
+            )? {
+                break;
+            }
+        }
+
+        self.config = Some(config.clone());
+        let model_name = trainer::save(self, config)?;
+
+        println!(
+            "Training: Finished after {:?} epochs\nreconstruction loss: {}\nKL divergence: {}",
+            state.epoch, reconstruction_loss, kl_divergence
+        );
+
+        Ok((model_name, reconstruction_loss, kl_divergence))
+    }
+
+    /// Runs a raw input through the pipeline and the encoder
+    ///
+    /// ## Returns
+    /// The mean of the input's latent distribution, the most likely latent vector
+    ///
+    /// ## Err
+    /// The errors of [`encode_distribution`](VarAutoEncoder::encode_distribution)
+    pub fn encode(&mut self, data: &[f32]) -> Result<Box<[f32]>, DarjeelingError> {
+        self.encode_distribution(data).map(|(mean, _)| mean)
+    }
+
+    /// Runs a raw input through the pipeline and the encoder
+    ///
+    /// ## Returns
+    /// The mean and the log variance of the input's latent distribution
+    ///
+    /// ## Err
+    /// ### ModelMissingActivationFunction
+    /// The network has no activation function
+    /// ### InvalidLayerSpec
+    /// The input isn't as wide as the encoder's input layer
+    /// ### TransformFailed
+    /// The input doesn't have the features the pipeline was fitted on
+    pub fn encode_distribution(
+        &mut self,
+        data: &[f32],
+    ) -> Result<LatentDistribution, DarjeelingError> {
+        let activation_function = match self.activation_function {
+            Some(s) => s,
+            None => return Err(DarjeelingError::ModelMissingActivationFunction),
+        };
+        let data = self.transform(data)?;
+        Ok(self.encode_row(activation_function, &data, false, &mut rand::thread_rng()))
+    }
+
+    /// Runs a latent vector through the decoder
+    ///
+    /// ## Returns
+    /// The decoded input, on the same scale as the raw inputs
+    ///
+    /// ## Err
+    /// ### ModelMissingActivationFunction
+    /// The network has no activation function
+    /// ### InvalidLatent
+    /// The latent vector isn't as wide as the decoder's input layer
+    pub fn decode(&mut self, latent: &[f32]) -> Result<Box<[f32]>, DarjeelingError> {
+        let activation_function = match self.activation_function {
+            Some(s) => s,
+            None => return Err(DarjeelingError::ModelMissingActivationFunction),
+        };
+        if latent.len() != self.latent_width() {
+            return Err(DarjeelingError::InvalidLatent(format!(
+                "the decoder's input layer has {} nodes, but the latent vector has {} values",
+                self.latent_width(),
+                latent.len()
+            )));
+        }
+
+        let output = self.decode_row(activation_function, latent, false, &mut rand::thread_rng());
+        match &self.pipeline {
+            Some(pipeline) if pipeline.is_fitted() => pipeline.inverse_transform_row(&output),
+            _ => Ok(output),
+        }
+    }
+
+    /// Decodes the mean of a raw input's latent distribution
+    ///
+    /// ## Err
+    /// The errors of [`encode`](VarAutoEncoder::encode) and [`decode`](VarAutoEncoder::decode)
+    pub fn reconstruct(&mut self, data: &[f32]) -> Result<Box<[f32]>, DarjeelingError> {
+        let latent = self.encode(data)?;
+        self.decode(&latent)
+    }
+
+    /// Generates new data by decoding latent vectors drawn from a standard normal distribution
+    ///
+    /// ## Params
+    /// - N: The number of rows to generate
+    /// - Rng: Draws the latent vectors, seed it to generate the same rows again
+    ///
+    /// ## Returns
+    /// The generated rows, on the same scale as the raw training data
+    ///
+    /// ## Err
+    /// The errors of [`decode`](VarAutoEncoder::decode)
+    ///
+    /// ## Examples
+    /// ```ignore
+    /// let rows = net.sample(10, &mut Pcg64::seed_from_u64(42)).unwrap();
+    /// ```
+    pub fn sample(
+        &mut self,
+        n: usize,
+        rng: &mut impl Rng,
+    ) -> Result<Box<[Box<[f32]>]>, DarjeelingError> {
+        (0..n)
+            .map(|_| {
+                let latent: Box<[f32]> = (0..self.latent_width())
+                    .map(|_| rng.sample(StandardNormal))
+                    .collect();
+                self.decode(&latent)
+            })
+            .collect()
+    }
+
+    /// Runs inputs through the pipeline, if the network has one
+    fn preprocess(&self, data: &[Box<[f32]>]) -> Result<Box<[Box<[f32]>]>, DarjeelingError> {
+        data.iter().map(|row| self.transform(row)).collect()
+    }
+
+    /// Runs an input through the pipeline, if the network has one, and checks its width
+    fn transform(&self, data: &[f32]) -> Result<Box<[f32]>, DarjeelingError> {
+        let data = match &self.pipeline {
+            Some(pipeline) => pipeline.transform_row(data)?,
+            None => data.into(),
+        };
+        if data.len() != self.encoder[0].len() {
+            return Err(DarjeelingError::InvalidLayerSpec(format!(
+                "the input layer has {} nodes, but the input has {} values",
+                self.encoder[0].len(),
+                data.len()
+            )));
+        }
+        Ok(data)
+    }
+
+    /// The mean and log variance the encoder gives an input that's already been through the pipeline
+    fn encode_row(
+        &mut self,
+        activation_function: ActivationFunction,
+        data: &[f32],
+        training: bool,
+        rng: &mut impl Rng,
+    ) -> LatentDistribution {
+        layer::push_downstream(
+            &mut self.encoder,
+            &self.encoder_layers,
+            activation_function,
+            data,
+            training,
+            rng,
+        );
+        let heads = layer::answer_outputs(&self.encoder);
+        let (mean, log_variance) = heads.split_at(heads.len() / 2);
+        (mean.into(), log_variance.into())
+    }
+
+    /// The decoder's outputs for a latent vector, before the pipeline is inverted
+    fn decode_row(
+        &mut self,
+        activation_function: ActivationFunction,
+        latent: &[f32],
+        training: bool,
+        rng: &mut impl Rng,
+    ) -> Box<[f32]> {
+        layer::push_downstream(
+            &mut self.decoder,
+            &self.decoder_layers,
+            activation_function,
+            latent,
+            training,
+            rng,
+        );
+        layer::answer_outputs(&self.decoder)
+    }
+
+    /// Adjusts the weights of both networks by the gradients of the current batch
+    fn step(
+        &mut self,
+        config: &TrainConfig,
+        state: &mut TrainState,
+        decoder_state: &mut OptimizerState,
+        learning_rate: f32,
+        batch_len: usize,
+    ) {
+        state.optimizer_state.step(
+            config.optimizer,
+            &mut self.encoder,
+            learning_rate,
+            batch_len,
+        );
+        decoder_state.step(
+            config.optimizer,
+            &mut self.decoder,
+            learning_rate,
+            batch_len,
+        );
+    }
+
+    /// Sets the activation function of the hidden layers, the answer layers are always linear
+    pub fn set_activation_func(&mut self, new_activation_function: ActivationFunction) {
+        self.activation_function = Some(new_activation_function);
+    }
+}
+
+impl Model for VarAutoEncoder {}